@@ -0,0 +1,151 @@
+//! Normalisation BER → DER : longueurs indéfinies et OCTET STRING construites
+//! (P7M produits en flux, certaines signatures PDF) que le décodeur DER refuse.
+//! Un élément déjà DER est restitué à l’identique.
+
+use anyhow::{Context, Result};
+
+/// Imbrication max des éléments construits.
+const MAX_DEPTH: usize = 64;
+
+const OCTET_STRING: u8 = 0x04;
+const CONSTRUCTED: u8 = 0x20;
+
+/// Premier élément de `buf` réencodé en DER (longueurs définies minimales,
+/// OCTET STRING fragmentées recollées) ; les octets qui suivent sont ignorés.
+pub fn normalize(buf: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(buf.len());
+    element(buf, 0, &mut out).context("Encodage BER/DER invalide")?;
+    Ok(out)
+}
+
+/// Longueur encodée de l’élément en tête de `buf`, longueurs indéfinies
+/// comprises ; `None` si l’élément est malformé ou dépasse `buf`.
+pub fn encoded_len(buf: &[u8]) -> Option<usize> {
+    element_len(buf, 0)
+}
+
+fn element_len(buf: &[u8], depth: usize) -> Option<usize> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let (length, mut pos) = length(buf, tag_len(buf)?)?;
+    match length {
+        Some(len) => pos.checked_add(len).filter(|end| *end <= buf.len()),
+        // Longueur indéfinie : éléments jusqu’au marqueur de fin `00 00`.
+        None if buf[0] & CONSTRUCTED != 0 => loop {
+            match buf.get(pos..pos + 2)? {
+                [0, 0] => return Some(pos + 2),
+                _ => pos += element_len(&buf[pos..], depth + 1)?,
+            }
+        },
+        None => None,
+    }
+}
+
+/// Réencode l’élément en tête de `buf` dans `out` ; retourne les octets lus.
+fn element(buf: &[u8], depth: usize, out: &mut Vec<u8>) -> Option<usize> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let tag_len = tag_len(buf)?;
+    let tag = &buf[..tag_len];
+    let constructed = tag[0] & CONSTRUCTED != 0;
+    let (length, mut pos) = length(buf, tag_len)?;
+
+    if !constructed {
+        let len = length?;
+        let value = buf.get(pos..pos.checked_add(len)?)?;
+        out.extend_from_slice(tag);
+        push_length(out, len);
+        out.extend_from_slice(value);
+        return Some(pos + len);
+    }
+
+    // Enfants réencodés à part : la longueur DER n’est connue qu’après coup.
+    let end = match length {
+        Some(len) => Some(pos.checked_add(len).filter(|end| *end <= buf.len())?),
+        None => None,
+    };
+    let mut children = Vec::new();
+    loop {
+        match end {
+            Some(end) if pos == end => break,
+            Some(end) if pos > end => return None,
+            None if buf.get(pos..pos + 2)? == [0, 0] => {
+                pos += 2;
+                break;
+            }
+            _ => {}
+        }
+        let limit = end.unwrap_or(buf.len());
+        pos += element(&buf[pos..limit], depth + 1, &mut children)?;
+    }
+
+    if tag == [OCTET_STRING | CONSTRUCTED] {
+        // Fragments primitifs recollés en une seule OCTET STRING.
+        let value = fragments(&children)?;
+        out.push(OCTET_STRING);
+        push_length(out, value.len());
+        out.extend_from_slice(&value);
+    } else {
+        out.extend_from_slice(tag);
+        push_length(out, children.len());
+        out.extend_from_slice(&children);
+    }
+    Some(pos)
+}
+
+/// Contenu concaténé d’une suite d’OCTET STRING DER.
+fn fragments(mut der: &[u8]) -> Option<Vec<u8>> {
+    let mut value = Vec::new();
+    while !der.is_empty() {
+        if der[0] != OCTET_STRING {
+            return None;
+        }
+        let (len, start) = length(der, 1)?;
+        let end = start.checked_add(len?)?;
+        value.extend_from_slice(der.get(start..end)?);
+        der = &der[end..];
+    }
+    Some(value)
+}
+
+fn tag_len(buf: &[u8]) -> Option<usize> {
+    if *buf.first()? & 0x1f != 0x1f {
+        return Some(1);
+    }
+    // Numéro d’étiquette en base 128.
+    let more = buf[1..].iter().position(|b| b & 0x80 == 0)?;
+    Some(more + 2)
+}
+
+/// Longueur lue à `pos` (`None` si indéfinie) et position du contenu.
+fn length(buf: &[u8], pos: usize) -> Option<(Option<usize>, usize)> {
+    let first = *buf.get(pos)?;
+    match first {
+        0x00..=0x7f => Some((Some(usize::from(first)), pos + 1)),
+        0x80 => Some((None, pos + 1)),
+        0x81..=0x88 => {
+            let n = usize::from(first & 0x7f);
+            let len = buf
+                .get(pos + 1..pos + 1 + n)?
+                .iter()
+                .try_fold(0usize, |acc, b| {
+                    acc.checked_mul(256)?.checked_add(usize::from(*b))
+                })?;
+            Some((Some(len), pos + 1 + n))
+        }
+        _ => None,
+    }
+}
+
+fn push_length(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+        return;
+    }
+    let bytes = len.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count();
+    out.push(0x80 | (bytes.len() - skip) as u8);
+    out.extend_from_slice(&bytes[skip..]);
+}
@@ -0,0 +1,98 @@
+use der::asn1::ObjectIdentifier;
+use sha1::Sha1;
+use sha2::digest::DynDigest;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
+
+/// Algorithme d’empreinte reconnu (identifiants NIST / OIW).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DigestAlgorithm {
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+}
+
+impl DigestAlgorithm {
+    pub fn from_oid(oid: &ObjectIdentifier) -> Option<Self> {
+        match oid.to_string().as_str() {
+            "1.3.14.3.2.26" => Some(Self::Sha1),
+            "2.16.840.1.101.3.4.2.4" => Some(Self::Sha224),
+            "2.16.840.1.101.3.4.2.1" => Some(Self::Sha256),
+            "2.16.840.1.101.3.4.2.2" => Some(Self::Sha384),
+            "2.16.840.1.101.3.4.2.3" => Some(Self::Sha512),
+            "2.16.840.1.101.3.4.2.7" => Some(Self::Sha3_224),
+            "2.16.840.1.101.3.4.2.8" => Some(Self::Sha3_256),
+            "2.16.840.1.101.3.4.2.9" => Some(Self::Sha3_384),
+            "2.16.840.1.101.3.4.2.10" => Some(Self::Sha3_512),
+            _ => None,
+        }
+    }
+
+    /// Empreinte d’un contenu en mémoire.
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize().into_vec()
+    }
+
+    fn hasher(self) -> Box<dyn DynDigest> {
+        match self {
+            Self::Sha1 => Box::new(Sha1::new()),
+            Self::Sha224 => Box::new(Sha224::new()),
+            Self::Sha256 => Box::new(Sha256::new()),
+            Self::Sha384 => Box::new(Sha384::new()),
+            Self::Sha512 => Box::new(Sha512::new()),
+            Self::Sha3_224 => Box::new(Sha3_224::new()),
+            Self::Sha3_256 => Box::new(Sha3_256::new()),
+            Self::Sha3_384 => Box::new(Sha3_384::new()),
+            Self::Sha3_512 => Box::new(Sha3_512::new()),
+        }
+    }
+}
+
+/// Empreintes d’un même contenu selon plusieurs algorithmes, calculées en
+/// une seule lecture (octets couverts par un ByteRange lus depuis le disque).
+#[derive(Debug, Clone, Default)]
+pub struct Digests(Vec<(DigestAlgorithm, Vec<u8>)>);
+
+impl Digests {
+    pub fn get(&self, algorithm: DigestAlgorithm) -> Option<&[u8]> {
+        self.0
+            .iter()
+            .find(|(a, _)| *a == algorithm)
+            .map(|(_, d)| d.as_slice())
+    }
+}
+
+/// Calcul simultané de plusieurs empreintes sur un contenu lu par blocs.
+pub struct MultiHasher(Vec<(DigestAlgorithm, Box<dyn DynDigest>)>);
+
+impl MultiHasher {
+    pub fn new(algorithms: &[DigestAlgorithm]) -> Self {
+        let mut algorithms = algorithms.to_vec();
+        algorithms.sort();
+        algorithms.dedup();
+        Self(algorithms.into_iter().map(|a| (a, a.hasher())).collect())
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        for (_, hasher) in &mut self.0 {
+            hasher.update(chunk);
+        }
+    }
+
+    pub fn finish(self) -> Digests {
+        Digests(
+            self.0
+                .into_iter()
+                .map(|(a, h)| (a, h.finalize().into_vec()))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,267 @@
+//! Vérification Ed448 (RFC 8032 §5.2), sans contexte ni préhachage : seule
+//! la clé publique intervient, l’arithmétique n’a pas à être à temps
+//! constant.
+
+use crypto_bigint::modular::constant_mod::{Residue, ResidueParams};
+use crypto_bigint::{impl_modulus, U448};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::Shake256;
+
+impl_modulus!(
+    P448,
+    U448,
+    "fffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+);
+
+type Fe = Residue<P448, { U448::LIMBS }>;
+
+/// d = -39081 de la courbe edwards448.
+const D: U448 = U448::from_be_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffffffffffffffffffffffffffffffffffffffffffffffff6756");
+/// Ordre L du sous-groupe engendré par le point de base.
+const L: U448 = U448::from_be_hex("3fffffffffffffffffffffffffffffffffffffffffffffffffffffff7cca23e9c44edb49aed63690216cc2728dc58f552378c292ab5844f3");
+const BASE_X: U448 = U448::from_be_hex("4f1970c66bed0ded221d15a622bf36da9e146570470f1767ea6de324a3d3a46412ae1af72ab66511433b80e18b00938e2626a82bc70cc05e");
+const BASE_Y: U448 = U448::from_be_hex("693f46716eb6bc248876203756c9c7624bea73736ca3984087789c1e05a0c2d73ad3ff1ce67c39c4fdbd132c4ed7c8ad9808795bf230fa14");
+/// (p - 3) / 4, exposant de la racine carrée (p ≡ 3 mod 4).
+const SQRT_EXP: U448 = U448::from_be_hex("3fffffffffffffffffffffffffffffffffffffffffffffffffffffffbfffffffffffffffffffffffffffffffffffffffffffffffffffffff");
+
+const KEY_LEN: usize = 57;
+const SIGNATURE_LEN: usize = 2 * KEY_LEN;
+
+fn fe(value: &U448) -> Fe {
+    Fe::new(value)
+}
+
+/// Point en coordonnées projectives (X : Y : Z).
+#[derive(Clone, Copy)]
+struct Point {
+    x: Fe,
+    y: Fe,
+    z: Fe,
+}
+
+impl Point {
+    const IDENTITY: Point = Point {
+        x: Fe::ZERO,
+        y: Fe::ONE,
+        z: Fe::ONE,
+    };
+
+    fn base() -> Point {
+        Point {
+            x: fe(&BASE_X),
+            y: fe(&BASE_Y),
+            z: Fe::ONE,
+        }
+    }
+
+    /// Addition complète (RFC 8032 §5.2.4), valable aussi pour le doublement.
+    fn add(&self, other: &Point) -> Point {
+        let a = self.z * other.z;
+        let b = a.square();
+        let c = self.x * other.x;
+        let d = self.y * other.y;
+        let e = fe(&D) * c * d;
+        let f = b - e;
+        let g = b + e;
+        let h = (self.x + self.y) * (other.x + other.y);
+        Point {
+            x: a * f * (h - c - d),
+            y: a * g * (d - c),
+            z: f * g,
+        }
+    }
+
+    /// [k]P, `scalar` en petit-boutiste.
+    fn mul(&self, scalar: &[u8]) -> Point {
+        let mut acc = Point::IDENTITY;
+        for byte in scalar.iter().rev() {
+            for bit in (0..8).rev() {
+                acc = acc.add(&acc);
+                if byte >> bit & 1 == 1 {
+                    acc = acc.add(self);
+                }
+            }
+        }
+        acc
+    }
+
+    /// [4]P : élimine la composante de torsion (cofacteur 4).
+    fn clear_cofactor(&self) -> Point {
+        let double = self.add(self);
+        double.add(&double)
+    }
+
+    fn eq(&self, other: &Point) -> bool {
+        (self.x * other.z).retrieve() == (other.x * self.z).retrieve()
+            && (self.y * other.z).retrieve() == (other.y * self.z).retrieve()
+    }
+
+    /// Décodage RFC 8032 §5.2.3 : y sur 56 octets, bit de signe de x en tête
+    /// du 57e.
+    fn decode(bytes: &[u8]) -> Option<Point> {
+        let (y_bytes, last) = bytes.split_at(KEY_LEN - 1);
+        if last[0] & 0x7f != 0 {
+            return None;
+        }
+        let y = U448::from_le_slice(y_bytes);
+        if y >= P448::MODULUS {
+            return None;
+        }
+        let y = fe(&y);
+        let y2 = y.square();
+        let u = y2 - Fe::ONE;
+        let v = fe(&D) * y2 - Fe::ONE;
+        // x = u³v (u⁵v³)^((p-3)/4), puis v·x² = u à contrôler.
+        let u3v = u.square() * u * v;
+        let x = u3v * (u3v * u.square() * v.square()).pow(&SQRT_EXP);
+        if (v * x.square()).retrieve() != u.retrieve() {
+            return None;
+        }
+        let sign = last[0] >> 7;
+        let raw = x.retrieve();
+        if raw == U448::ZERO && sign == 1 {
+            return None;
+        }
+        let x = if (raw.as_words()[0] & 1) as u8 == sign {
+            x
+        } else {
+            -x
+        };
+        Some(Point { x, y, z: Fe::ONE })
+    }
+}
+
+/// Vérifie la signature Ed448 pure `signature` de `message` par la clé
+/// publique encodée `key`. `None` si la clé n’est pas un point valide.
+pub fn verify(key: &[u8], message: &[u8], signature: &[u8]) -> Option<bool> {
+    if key.len() != KEY_LEN {
+        return None;
+    }
+    let public = Point::decode(key)?;
+    if signature.len() != SIGNATURE_LEN {
+        return Some(false);
+    }
+    let (r_bytes, s_bytes) = signature.split_at(KEY_LEN);
+    // S < L < 2^446 : le 57e octet est nul.
+    if s_bytes[KEY_LEN - 1] != 0 || U448::from_le_slice(&s_bytes[..KEY_LEN - 1]) >= L {
+        return Some(false);
+    }
+    let Some(r) = Point::decode(r_bytes) else {
+        return Some(false);
+    };
+
+    // k = SHAKE256(dom4(0, "") || R || A || M, 114)
+    let mut hasher = Shake256::default();
+    hasher.update(b"SigEd448\x00\x00");
+    hasher.update(r_bytes);
+    hasher.update(key);
+    hasher.update(message);
+    let mut k = [0u8; SIGNATURE_LEN];
+    hasher.finalize_xof().read(&mut k);
+
+    // [4][S]B = [4]R + [4][k]A
+    let lhs = Point::base().mul(s_bytes).clear_cofactor();
+    let rhs = r.add(&public.mul(&k)).clear_cofactor();
+    Some(lhs.eq(&rhs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_bigint::Encoding;
+
+    /// RFC 8032 §7.4 : (clé publique, message, signature).
+    const VECTORS: [(&str, &str, &str); 3] = [
+        (
+            "5fd7449b59b461fd2ce787ec616ad46a1da1342485a70e1f8a0ea75d80e96778edf124769b46c7061bd6783df1e50f6cd1fa1abeafe8256180",
+            "",
+            "533a37f6bbe457251f023c0d88f976ae2dfb504a843e34d2074fd823d41a591f2b233f034f628281f2fd7a22ddd47d7828c59bd0a21bfd3980ff0d2028d4b18a9df63e006c5d1c2d345b925d8dc00b4104852db99ac5c7cdda8530a113a0f4dbb61149f05a7363268c71d95808ff2e652600",
+        ),
+        (
+            "43ba28f430cdff456ae531545f7ecd0ac834a55d9358c0372bfa0c6c6798c0866aea01eb00742802b8438ea4cb82169c235160627b4c3a9480",
+            "03",
+            "26b8f91727bd62897af15e41eb43c377efb9c610d48f2335cb0bd0087810f4352541b143c4b981b7e18f62de8ccdf633fc1bf037ab7cd779805e0dbcc0aae1cbcee1afb2e027df36bc04dcecbf154336c19f0af7e0a6472905e799f1953d2a0ff3348ab21aa4adafd1d234441cf807c03a00",
+        ),
+        (
+            "dcea9e78f35a1bf3499a831b10b86c90aac01cd84b67a0109b55a36e9328b1e365fce161d71ce7131a543ea4cb5f7e9f1d8b00696447001400",
+            "0c3e544074ec63b0265e0c",
+            "1f0a8888ce25e8d458a21130879b840a9089d999aaba039eaf3e3afa090a09d389dba82c4ff2ae8ac5cdfb7c55e94d5d961a29fe0109941e00b8dbdeea6d3b051068df7254c0cdc129cbe62db2dc957dbb47b51fd3f213fb8698f064774250a5028961c9bf8ffd973fe5d5c206492b140e00",
+        ),
+    ];
+
+    fn vector(i: usize) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let (key, message, signature) = VECTORS[i];
+        let decode = |s: &str| hex::decode(s).unwrap();
+        (decode(key), decode(message), decode(signature))
+    }
+
+    /// Encodage de `y` avec le bit de signe `sign`.
+    fn encode(y: &U448, sign: u8) -> Vec<u8> {
+        let mut bytes = y.to_le_bytes().to_vec();
+        bytes.push(sign << 7);
+        bytes
+    }
+
+    #[test]
+    fn rfc8032_vectors_verify() {
+        for i in 0..VECTORS.len() {
+            let (key, message, signature) = vector(i);
+            assert_eq!(
+                verify(&key, &message, &signature),
+                Some(true),
+                "vecteur {i}"
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_key_or_message_is_rejected() {
+        let (key, message, signature) = vector(1);
+        let (other_key, ..) = vector(2);
+        assert_eq!(verify(&other_key, &message, &signature), Some(false));
+        assert_eq!(verify(&key, b"\x04", &signature), Some(false));
+        assert_eq!(
+            verify(&key, &message, &signature[..SIGNATURE_LEN - 1]),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn scalar_not_reduced_modulo_l_is_rejected() {
+        // [S + L]B = [S]B : seule la borne S < L écarte cette malléabilité.
+        let (key, message, mut signature) = vector(1);
+        let s = U448::from_le_slice(&signature[KEY_LEN..SIGNATURE_LEN - 1]);
+        let malleated = s.wrapping_add(&L).to_le_bytes();
+        signature[KEY_LEN..SIGNATURE_LEN - 1].copy_from_slice(&malleated);
+        assert_eq!(verify(&key, &message, &signature), Some(false));
+    }
+
+    #[test]
+    fn non_canonical_y_is_rejected() {
+        // y = p + 1 encode l’élément neutre (y = 1) de façon non canonique.
+        let y = P448::MODULUS.wrapping_add(&U448::ONE);
+        assert!(Point::decode(&encode(&U448::ONE, 0)).is_some());
+        assert_eq!(verify(&encode(&y, 0), b"", &[0; SIGNATURE_LEN]), None);
+
+        let (key, message, mut signature) = vector(1);
+        signature[..KEY_LEN].copy_from_slice(&encode(&y, 0));
+        assert_eq!(verify(&key, &message, &signature), Some(false));
+    }
+
+    #[test]
+    fn invalid_point_encoding_is_rejected() {
+        // Aucun x ne vérifie la courbe pour y = 2.
+        let off_curve = encode(&U448::from_u8(2), 0);
+        assert_eq!(verify(&off_curve, b"", &[0; SIGNATURE_LEN]), None);
+        // x = 0 n’a pas de signe.
+        assert_eq!(
+            verify(&encode(&U448::ONE, 1), b"", &[0; SIGNATURE_LEN]),
+            None
+        );
+        // Bits de poids fort du dernier octet non nuls, longueur invalide.
+        let (mut key, message, signature) = vector(1);
+        assert_eq!(verify(&key[..KEY_LEN - 1], &message, &signature), None);
+        key[KEY_LEN - 1] |= 0x01;
+        assert_eq!(verify(&key, &message, &signature), None);
+    }
+}
@@ -1 +1,7 @@
+pub mod ber;
+pub mod digest;
+pub mod ed448;
+pub mod signature;
+pub mod signed_data;
+pub mod tsp;
 pub mod verify;
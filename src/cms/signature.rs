@@ -0,0 +1,290 @@
+//! Vérification native des valeurs de signature (RSA PKCS#1 v1.5, RSA-PSS,
+//! ECDSA P-256/P-384/P-521, Ed25519, Ed448) pour les SignerInfo et les certificats,
+//! sans OpenSSL.
+
+use crate::cms::digest::DigestAlgorithm;
+use crate::cms::ed448;
+use crate::cms::signed_data::oid_name;
+use anyhow::{bail, Context, Result};
+use der::asn1::ObjectIdentifier;
+use der::{Decode, Encode};
+use rsa::pkcs1::{DecodeRsaPublicKey, RsaPssParams, TrailerField};
+use rsa::{Pkcs1v15Sign, Pss, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+use sha3::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
+use signature::hazmat::PrehashVerifier;
+use x509_cert::spki::{AlgorithmIdentifierOwned, SubjectPublicKeyInfoOwned};
+use x509_cert::Certificate;
+
+const RSA_ENCRYPTION: &str = "1.2.840.113549.1.1.1";
+const RSASSA_PSS: &str = "1.2.840.113549.1.1.10";
+const MGF1: &str = "1.2.840.113549.1.1.8";
+const EC_PUBLIC_KEY: &str = "1.2.840.10045.2.1";
+const ED25519: &str = "1.3.101.112";
+const ED448: &str = "1.3.101.113";
+
+/// Vérification qui ne peut conclure (algorithme ou clé hors du périmètre
+/// natif) : le résultat est indéterminé, pas invalide.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+pub struct Indeterminate(pub String);
+
+/// Schéma de signature résolu à partir des identifiants d’algorithme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scheme {
+    Pkcs1(DigestAlgorithm),
+    Pss {
+        digest: DigestAlgorithm,
+        salt: usize,
+    },
+    Ecdsa(DigestAlgorithm),
+    Ed25519,
+    Ed448,
+}
+
+/// Vérifie `signature` sur `message` par la clé `spki`, selon l’algorithme de
+/// signature `algorithm`. `digest` est l’algorithme d’empreinte du SignerInfo,
+/// utilisé quand `algorithm` ne le fixe pas (rsaEncryption, ecPublicKey).
+///
+/// Erreur si l’algorithme ou la clé ne sont pas pris en charge ; `Ok(false)`
+/// si la signature ne correspond pas.
+pub fn verify(
+    spki: &SubjectPublicKeyInfoOwned,
+    algorithm: &AlgorithmIdentifierOwned,
+    digest: Option<DigestAlgorithm>,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    let scheme = scheme(algorithm, digest)?;
+    let digest = match scheme {
+        Scheme::Pkcs1(d) | Scheme::Pss { digest: d, .. } | Scheme::Ecdsa(d) => d,
+        Scheme::Ed25519 => return ed25519(spki, message, signature),
+        Scheme::Ed448 => return ed448(spki, message, signature),
+    };
+    verify_prehashed(spki, scheme, &digest.digest(message), signature)
+}
+
+/// Signature RSA PKCS#1 v1.5 d’une empreinte déjà calculée
+/// (adbe.x509.rsa_sha1).
+pub fn verify_pkcs1_digest(
+    spki: &SubjectPublicKeyInfoOwned,
+    digest: DigestAlgorithm,
+    hashed: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    verify_prehashed(spki, Scheme::Pkcs1(digest), hashed, signature)
+}
+
+/// Le certificat `cert` est-il signé par la clé de `issuer` ?
+pub fn certificate_signed_by(cert: &Certificate, issuer: &Certificate) -> Result<bool> {
+    let tbs = cert.tbs_certificate.to_der()?;
+    let signature = cert
+        .signature
+        .as_bytes()
+        .context("Signature de certificat non alignée")?;
+    verify(
+        &issuer.tbs_certificate.subject_public_key_info,
+        &cert.signature_algorithm,
+        None,
+        &tbs,
+        signature,
+    )
+}
+
+fn scheme(algorithm: &AlgorithmIdentifierOwned, digest: Option<DigestAlgorithm>) -> Result<Scheme> {
+    use DigestAlgorithm::*;
+    let with_digest = |scheme: fn(DigestAlgorithm) -> Scheme| match digest {
+        Some(digest) => Ok(scheme(digest)),
+        None => unsupported("Algorithme d’empreinte du signataire non pris en charge".into()),
+    };
+    Ok(match algorithm.oid.to_string().as_str() {
+        RSA_ENCRYPTION => with_digest(Scheme::Pkcs1)?,
+        EC_PUBLIC_KEY => with_digest(Scheme::Ecdsa)?,
+        "1.2.840.113549.1.1.5" => Scheme::Pkcs1(Sha1),
+        "1.2.840.113549.1.1.14" => Scheme::Pkcs1(Sha224),
+        "1.2.840.113549.1.1.11" => Scheme::Pkcs1(Sha256),
+        "1.2.840.113549.1.1.12" => Scheme::Pkcs1(Sha384),
+        "1.2.840.113549.1.1.13" => Scheme::Pkcs1(Sha512),
+        "2.16.840.1.101.3.4.3.13" => Scheme::Pkcs1(Sha3_224),
+        "2.16.840.1.101.3.4.3.14" => Scheme::Pkcs1(Sha3_256),
+        "2.16.840.1.101.3.4.3.15" => Scheme::Pkcs1(Sha3_384),
+        "2.16.840.1.101.3.4.3.16" => Scheme::Pkcs1(Sha3_512),
+        "1.2.840.10045.4.1" => Scheme::Ecdsa(Sha1),
+        "1.2.840.10045.4.3.1" => Scheme::Ecdsa(Sha224),
+        "1.2.840.10045.4.3.2" => Scheme::Ecdsa(Sha256),
+        "1.2.840.10045.4.3.3" => Scheme::Ecdsa(Sha384),
+        "1.2.840.10045.4.3.4" => Scheme::Ecdsa(Sha512),
+        "2.16.840.1.101.3.4.3.9" => Scheme::Ecdsa(Sha3_224),
+        "2.16.840.1.101.3.4.3.10" => Scheme::Ecdsa(Sha3_256),
+        "2.16.840.1.101.3.4.3.11" => Scheme::Ecdsa(Sha3_384),
+        "2.16.840.1.101.3.4.3.12" => Scheme::Ecdsa(Sha3_512),
+        RSASSA_PSS => pss(algorithm)?,
+        ED25519 => Scheme::Ed25519,
+        ED448 => Scheme::Ed448,
+        _ => {
+            return unsupported(format!(
+                "Algorithme de signature non pris en charge ({})",
+                oid_name(&algorithm.oid)
+            ))
+        }
+    })
+}
+
+fn unsupported<T>(detail: String) -> Result<T> {
+    Err(Indeterminate(detail).into())
+}
+
+/// Paramètres RSASSA-PSS (RFC 4055 §3.1) : MGF1 sur la même empreinte que le
+/// message, sel explicite.
+fn pss(algorithm: &AlgorithmIdentifierOwned) -> Result<Scheme> {
+    let der = match &algorithm.parameters {
+        Some(params) => params.to_der()?,
+        None => bail!("RSASSA-PSS sans paramètres"),
+    };
+    let params = RsaPssParams::from_der(&der).context("Paramètres RSASSA-PSS invalides")?;
+    let Some(digest) = DigestAlgorithm::from_oid(&params.hash.oid) else {
+        return unsupported(format!(
+            "Empreinte RSASSA-PSS non prise en charge ({})",
+            oid_name(&params.hash.oid)
+        ));
+    };
+    let mgf_digest = params.mask_gen.parameters.map(|p| p.oid);
+    if params.mask_gen.oid.to_string() != MGF1 || mgf_digest != Some(params.hash.oid) {
+        return unsupported("RSASSA-PSS : fonction de masquage non prise en charge".into());
+    }
+    if params.trailer_field != TrailerField::BC {
+        return unsupported("RSASSA-PSS : trailerField non pris en charge".into());
+    }
+    Ok(Scheme::Pss {
+        digest,
+        salt: usize::from(params.salt_len),
+    })
+}
+
+fn verify_prehashed(
+    spki: &SubjectPublicKeyInfoOwned,
+    scheme: Scheme,
+    hashed: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    let key_bytes = spki.subject_public_key.raw_bytes();
+    match scheme {
+        Scheme::Pkcs1(digest) | Scheme::Pss { digest, .. } => {
+            let key_oid = spki.algorithm.oid.to_string();
+            if key_oid != RSA_ENCRYPTION && key_oid != RSASSA_PSS {
+                bail!(
+                    "Clé {} incompatible avec RSA",
+                    oid_name(&spki.algorithm.oid)
+                );
+            }
+            let key = RsaPublicKey::from_pkcs1_der(key_bytes).context("Clé publique RSA")?;
+            let verified = match scheme {
+                Scheme::Pss { salt, .. } => {
+                    key.verify(pss_padding(digest, salt), hashed, signature)
+                }
+                _ => key.verify(pkcs1_padding(digest), hashed, signature),
+            };
+            Ok(verified.is_ok())
+        }
+        Scheme::Ecdsa(_) => {
+            if spki.algorithm.oid.to_string() != EC_PUBLIC_KEY {
+                bail!(
+                    "Clé {} incompatible avec ECDSA",
+                    oid_name(&spki.algorithm.oid)
+                );
+            }
+            let curve = spki
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|p| p.decode_as::<ObjectIdentifier>().ok())
+                .context("Courbe elliptique non précisée")?;
+            ecdsa(&curve, key_bytes, hashed, signature)
+        }
+        Scheme::Ed25519 | Scheme::Ed448 => {
+            bail!("EdDSA ne signe pas une empreinte précalculée")
+        }
+    }
+}
+
+fn pkcs1_padding(digest: DigestAlgorithm) -> Pkcs1v15Sign {
+    match digest {
+        DigestAlgorithm::Sha1 => Pkcs1v15Sign::new::<Sha1>(),
+        DigestAlgorithm::Sha224 => Pkcs1v15Sign::new::<Sha224>(),
+        DigestAlgorithm::Sha256 => Pkcs1v15Sign::new::<Sha256>(),
+        DigestAlgorithm::Sha384 => Pkcs1v15Sign::new::<Sha384>(),
+        DigestAlgorithm::Sha512 => Pkcs1v15Sign::new::<Sha512>(),
+        DigestAlgorithm::Sha3_224 => Pkcs1v15Sign::new::<Sha3_224>(),
+        DigestAlgorithm::Sha3_256 => Pkcs1v15Sign::new::<Sha3_256>(),
+        DigestAlgorithm::Sha3_384 => Pkcs1v15Sign::new::<Sha3_384>(),
+        DigestAlgorithm::Sha3_512 => Pkcs1v15Sign::new::<Sha3_512>(),
+    }
+}
+
+fn pss_padding(digest: DigestAlgorithm, salt: usize) -> Pss {
+    match digest {
+        DigestAlgorithm::Sha1 => Pss::new_with_salt::<Sha1>(salt),
+        DigestAlgorithm::Sha224 => Pss::new_with_salt::<Sha224>(salt),
+        DigestAlgorithm::Sha256 => Pss::new_with_salt::<Sha256>(salt),
+        DigestAlgorithm::Sha384 => Pss::new_with_salt::<Sha384>(salt),
+        DigestAlgorithm::Sha512 => Pss::new_with_salt::<Sha512>(salt),
+        DigestAlgorithm::Sha3_224 => Pss::new_with_salt::<Sha3_224>(salt),
+        DigestAlgorithm::Sha3_256 => Pss::new_with_salt::<Sha3_256>(salt),
+        DigestAlgorithm::Sha3_384 => Pss::new_with_salt::<Sha3_384>(salt),
+        DigestAlgorithm::Sha3_512 => Pss::new_with_salt::<Sha3_512>(salt),
+    }
+}
+
+/// ECDSA sur une empreinte : clé SEC1, signature DER (r, s).
+fn ecdsa(curve: &ObjectIdentifier, key: &[u8], hashed: &[u8], signature: &[u8]) -> Result<bool> {
+    macro_rules! verify_on {
+        ($curve:ident) => {{
+            let key = $curve::ecdsa::VerifyingKey::from_sec1_bytes(key)
+                .map_err(|_| anyhow::anyhow!("Clé publique ECDSA invalide"))?;
+            let Ok(signature) = $curve::ecdsa::Signature::from_der(signature) else {
+                return Ok(false);
+            };
+            Ok(key.verify_prehash(hashed, &signature).is_ok())
+        }};
+    }
+    match curve.to_string().as_str() {
+        "1.2.840.10045.3.1.7" => verify_on!(p256),
+        "1.3.132.0.34" => verify_on!(p384),
+        "1.3.132.0.35" => verify_on!(p521),
+        other => unsupported(format!("Courbe elliptique non prise en charge ({other})")),
+    }
+}
+
+/// Ed25519 (RFC 8419) : le message entier est signé, sans empreinte préalable.
+fn ed25519(spki: &SubjectPublicKeyInfoOwned, message: &[u8], signature: &[u8]) -> Result<bool> {
+    if spki.algorithm.oid.to_string() != ED25519 {
+        bail!(
+            "Clé {} incompatible avec Ed25519",
+            oid_name(&spki.algorithm.oid)
+        );
+    }
+    let key: &[u8; 32] = spki
+        .subject_public_key
+        .raw_bytes()
+        .try_into()
+        .context("Clé publique Ed25519 invalide")?;
+    let key = ed25519_dalek::VerifyingKey::from_bytes(key)
+        .map_err(|_| anyhow::anyhow!("Clé publique Ed25519 invalide"))?;
+    let Ok(signature) = ed25519_dalek::Signature::from_slice(signature) else {
+        return Ok(false);
+    };
+    Ok(key.verify_strict(message, &signature).is_ok())
+}
+
+/// Ed448 (RFC 8419) : le message entier est signé, sans empreinte préalable.
+fn ed448(spki: &SubjectPublicKeyInfoOwned, message: &[u8], signature: &[u8]) -> Result<bool> {
+    if spki.algorithm.oid.to_string() != ED448 {
+        bail!(
+            "Clé {} incompatible avec Ed448",
+            oid_name(&spki.algorithm.oid)
+        );
+    }
+    ed448::verify(spki.subject_public_key.raw_bytes(), message, signature)
+        .context("Clé publique Ed448 invalide")
+}
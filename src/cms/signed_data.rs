@@ -0,0 +1,328 @@
+use crate::cms::ber::normalize;
+use crate::report::{CmsSignerInfo, CmsStructure};
+use crate::revocation::ValidationMaterial;
+use crate::x509::name::display_name;
+use anyhow::{bail, Context, Result};
+use cms::cert::CertificateChoices;
+use cms::content_info::ContentInfo;
+use cms::revocation::RevocationInfoChoice;
+use cms::signed_data::{SignedData, SignerIdentifier, SignerInfo};
+use der::asn1::{ObjectIdentifier, OctetString};
+use der::{Any, Decode, Encode, Header, Reader, SliceReader, Tag, TagNumber, Tagged};
+use time::OffsetDateTime;
+use x509_cert::attr::Attribute;
+use x509_cert::crl::CertificateList;
+use x509_cert::spki::AlgorithmIdentifierOwned;
+use x509_cert::time::Time;
+use x509_cert::Certificate;
+
+const ID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const ID_CONTENT_TYPE: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.3");
+const ID_SIGNING_TIME: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.5");
+const ID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+
+/// SignedData décodée (RFC 5652 §5) : modèle commun à la vérification, à la
+/// chaîne, à la révocation, aux horodatages et au rapport.
+#[derive(Debug, Clone)]
+pub struct CmsSignedData {
+    pub digest_algorithms: Vec<ObjectIdentifier>,
+    /// eContentType de l’EncapsulatedContentInfo.
+    pub content_type: ObjectIdentifier,
+    /// eContent ; absent pour une signature détachée.
+    pub content: Option<Vec<u8>>,
+    pub certificates: Vec<Certificate>,
+    pub crls: Vec<CertificateList>,
+    /// SignerInfos dans l’ordre de l’encodage.
+    pub signers: Vec<CmsSigner>,
+}
+
+/// SignerInfo (RFC 5652 §5.3).
+#[derive(Debug, Clone)]
+pub struct CmsSigner {
+    pub sid: SignerIdentifier,
+    pub digest_algorithm: ObjectIdentifier,
+    pub signature_algorithm: AlgorithmIdentifierOwned,
+    pub signature: Vec<u8>,
+    /// Attributs signés sous la forme couverte par la signature (§5.4) : le
+    /// `[0] IMPLICIT` devient un SET, octets d’origine sans retri DER.
+    pub signed_attrs_der: Option<Vec<u8>>,
+    pub signed_attrs: Vec<Attribute>,
+    pub unsigned_attrs: Vec<Attribute>,
+}
+
+impl CmsSignedData {
+    /// Décode une ContentInfo/SignedData DER ou BER ; un bourrage après
+    /// l’élément (réserve /Contents d’un PDF) est ignoré.
+    pub fn from_der(der: &[u8]) -> Result<Self> {
+        let der = normalize(der)?;
+        let ci = ContentInfo::from_der(&der).context("ContentInfo invalide")?;
+        if ci.content_type != ID_SIGNED_DATA {
+            bail!("SignedData attendu ({})", ci.content_type);
+        }
+        let sd: SignedData = ci.content.decode_as().context("SignedData invalide")?;
+        let signers = raw_signer_infos(&der)
+            .context("SignerInfos invalides")?
+            .iter()
+            .map(CmsSigner::from_raw)
+            .collect::<Result<Vec<_>>>()?;
+        let content = match &sd.encap_content_info.econtent {
+            Some(econtent) => {
+                let octets: OctetString = econtent.decode_as().context("eContent invalide")?;
+                Some(octets.into_bytes())
+            }
+            None => None,
+        };
+
+        Ok(CmsSignedData {
+            digest_algorithms: sd.digest_algorithms.iter().map(|a| a.oid).collect(),
+            content_type: sd.encap_content_info.econtent_type,
+            content,
+            certificates: sd
+                .certificates
+                .iter()
+                .flat_map(|set| set.0.iter())
+                .filter_map(|c| match c {
+                    CertificateChoices::Certificate(cert) => Some(cert.clone()),
+                    _ => None,
+                })
+                .collect(),
+            crls: sd
+                .crls
+                .iter()
+                .flat_map(|set| set.0.iter())
+                .filter_map(|c| match c {
+                    RevocationInfoChoice::Crl(crl) => Some(crl.clone()),
+                    _ => None,
+                })
+                .collect(),
+            signers,
+        })
+    }
+
+    /// Premier SignerInfo (les signatures PDF n’en portent qu’un).
+    pub fn signer(&self) -> Option<&CmsSigner> {
+        self.signers.first()
+    }
+
+    /// Certificat du premier SignerInfo, parmi les certificats embarqués.
+    pub fn signer_certificate(&self) -> Option<&Certificate> {
+        self.certificate_of(self.signer()?)
+    }
+
+    /// Certificat désigné par le sid de `signer`.
+    pub fn certificate_of(&self, signer: &CmsSigner) -> Option<&Certificate> {
+        match &signer.sid {
+            SignerIdentifier::IssuerAndSerialNumber(ias) => self.certificates.iter().find(|c| {
+                c.tbs_certificate.issuer == ias.issuer
+                    && c.tbs_certificate.serial_number == ias.serial_number
+            }),
+            SignerIdentifier::SubjectKeyIdentifier(skid) => self
+                .certificates
+                .iter()
+                .find(|c| subject_key_identifier(c).is_some_and(|id| id == skid.0.as_bytes())),
+        }
+    }
+
+    /// CRL embarquées (RevocationInfoChoices), comme matériel de validation.
+    pub fn validation_material(&self) -> ValidationMaterial {
+        ValidationMaterial {
+            crls: self.crls.iter().filter_map(|c| c.to_der().ok()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Résumé de la structure pour le rapport.
+    pub fn describe(&self) -> CmsStructure {
+        CmsStructure {
+            content_type: oid_name(&self.content_type),
+            encapsulated: self.content.is_some(),
+            digest_algorithms: self.digest_algorithms.iter().map(oid_name).collect(),
+            certificates: self.certificates.len(),
+            crls: self.crls.len(),
+            signers: self
+                .signers
+                .iter()
+                .map(|s| CmsSignerInfo {
+                    signer_dn: self
+                        .certificate_of(s)
+                        .map(|c| display_name(&c.tbs_certificate.subject)),
+                    digest_algorithm: oid_name(&s.digest_algorithm),
+                    signature_algorithm: oid_name(&s.signature_algorithm.oid),
+                    signed_attributes: s.signed_attrs.iter().map(|a| oid_name(&a.oid)).collect(),
+                    unsigned_attributes: s
+                        .unsigned_attrs
+                        .iter()
+                        .map(|a| oid_name(&a.oid))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CmsSigner {
+    fn from_raw(raw: &Any) -> Result<Self> {
+        let si = SignerInfo::from_der(&raw.to_der()?).context("SignerInfo invalide")?;
+        Ok(CmsSigner {
+            sid: si.sid,
+            digest_algorithm: si.digest_alg.oid,
+            signature_algorithm: si.signature_algorithm,
+            signature: si.signature.as_bytes().to_vec(),
+            signed_attrs_der: signed_attributes_der(raw),
+            signed_attrs: si.signed_attrs.map(|a| a.into_vec()).unwrap_or_default(),
+            unsigned_attrs: si.unsigned_attrs.map(|a| a.into_vec()).unwrap_or_default(),
+        })
+    }
+
+    /// Première valeur de l’attribut signé `oid`.
+    pub fn signed_attribute(&self, oid: ObjectIdentifier) -> Option<&Any> {
+        self.signed_attrs
+            .iter()
+            .find(|a| a.oid == oid)?
+            .values
+            .iter()
+            .next()
+    }
+
+    /// Le SignerInfo porte-t-il l’attribut signé `oid` ?
+    pub fn has_signed_attribute(&self, oid: ObjectIdentifier) -> bool {
+        self.signed_attrs.iter().any(|a| a.oid == oid)
+    }
+
+    /// Première valeur de l’attribut non signé `oid`.
+    pub fn unsigned_attribute(&self, oid: ObjectIdentifier) -> Option<&Any> {
+        self.unsigned_attrs
+            .iter()
+            .find(|a| a.oid == oid)?
+            .values
+            .iter()
+            .next()
+    }
+
+    /// Attribut signé contentType (RFC 5652 §11.1).
+    pub fn content_type(&self) -> Option<ObjectIdentifier> {
+        self.signed_attribute(ID_CONTENT_TYPE)?.decode_as().ok()
+    }
+
+    /// Attribut signé signingTime (RFC 5652 §11.3).
+    pub fn signing_time(&self) -> Option<OffsetDateTime> {
+        // Time est un CHOICE (UTCTime | GeneralizedTime) : décodage depuis le DER.
+        let value = self.signed_attribute(ID_SIGNING_TIME)?;
+        let time = Time::from_der(&value.to_der().ok()?).ok()?;
+        let secs = i64::try_from(time.to_unix_duration().as_secs()).ok()?;
+        OffsetDateTime::from_unix_timestamp(secs).ok()
+    }
+
+    /// Attribut signé messageDigest (RFC 5652 §11.2).
+    pub fn message_digest(&self) -> Option<Vec<u8>> {
+        let digest: OctetString = self.signed_attribute(ID_MESSAGE_DIGEST)?.decode_as().ok()?;
+        Some(digest.as_bytes().to_vec())
+    }
+}
+
+/// Nom usuel d’un algorithme, type de contenu ou attribut ; l’OID à défaut.
+pub fn oid_name(oid: &ObjectIdentifier) -> String {
+    let name = match oid.to_string().as_str() {
+        "1.3.14.3.2.26" => "SHA-1",
+        "2.16.840.1.101.3.4.2.4" => "SHA-224",
+        "2.16.840.1.101.3.4.2.1" => "SHA-256",
+        "2.16.840.1.101.3.4.2.2" => "SHA-384",
+        "2.16.840.1.101.3.4.2.3" => "SHA-512",
+        "2.16.840.1.101.3.4.2.7" => "SHA3-224",
+        "2.16.840.1.101.3.4.2.8" => "SHA3-256",
+        "2.16.840.1.101.3.4.2.9" => "SHA3-384",
+        "2.16.840.1.101.3.4.2.10" => "SHA3-512",
+        "2.16.840.1.101.3.4.3.9" => "ecdsa-with-SHA3-224",
+        "2.16.840.1.101.3.4.3.10" => "ecdsa-with-SHA3-256",
+        "2.16.840.1.101.3.4.3.11" => "ecdsa-with-SHA3-384",
+        "2.16.840.1.101.3.4.3.12" => "ecdsa-with-SHA3-512",
+        "2.16.840.1.101.3.4.3.13" => "sha3-224WithRSAEncryption",
+        "2.16.840.1.101.3.4.3.14" => "sha3-256WithRSAEncryption",
+        "2.16.840.1.101.3.4.3.15" => "sha3-384WithRSAEncryption",
+        "2.16.840.1.101.3.4.3.16" => "sha3-512WithRSAEncryption",
+        "1.2.840.113549.1.1.1" => "rsaEncryption",
+        "1.2.840.113549.1.1.5" => "sha1WithRSAEncryption",
+        "1.2.840.113549.1.1.10" => "RSASSA-PSS",
+        "1.2.840.113549.1.1.11" => "sha256WithRSAEncryption",
+        "1.2.840.113549.1.1.12" => "sha384WithRSAEncryption",
+        "1.2.840.113549.1.1.13" => "sha512WithRSAEncryption",
+        "1.2.840.113549.1.1.14" => "sha224WithRSAEncryption",
+        "1.2.840.10045.2.1" => "ecPublicKey",
+        "1.2.840.10045.4.1" => "ecdsa-with-SHA1",
+        "1.2.840.10045.4.3.1" => "ecdsa-with-SHA224",
+        "1.2.840.10045.4.3.2" => "ecdsa-with-SHA256",
+        "1.2.840.10045.4.3.3" => "ecdsa-with-SHA384",
+        "1.2.840.10045.4.3.4" => "ecdsa-with-SHA512",
+        "1.3.101.112" => "Ed25519",
+        "1.3.101.113" => "Ed448",
+        "1.2.840.113549.1.7.1" => "data",
+        "1.2.840.113549.1.9.16.1.4" => "tstInfo",
+        "1.2.840.113549.1.9.3" => "contentType",
+        "1.2.840.113549.1.9.4" => "messageDigest",
+        "1.2.840.113549.1.9.5" => "signingTime",
+        "1.2.840.113549.1.9.6" => "countersignature",
+        "1.2.840.113549.1.9.52" => "cmsAlgorithmProtection",
+        "1.2.840.113549.1.9.16.2.12" => "signingCertificate",
+        "1.2.840.113549.1.9.16.2.14" => "signatureTimeStampToken",
+        "1.2.840.113549.1.9.16.2.15" => "signaturePolicyIdentifier",
+        "1.2.840.113549.1.9.16.2.47" => "signingCertificateV2",
+        "1.2.840.113583.1.1.8" => "adbe-revocationInfoArchival",
+        other => return other.to_string(),
+    };
+    name.to_string()
+}
+
+/// SignerInfos bruts d’une ContentInfo DER, dans l’ordre de l’encodage (le
+/// décodage typé du SET les retrie).
+fn raw_signer_infos(der: &[u8]) -> Option<Vec<Any>> {
+    let content_info = children(&Any::from_der(der).ok()?)?;
+    let explicit = content_info.get(1)?;
+    let signed_data = children(&Any::from_der(explicit.value()).ok()?)?;
+    children(signed_data.last()?)
+}
+
+/// `[0] IMPLICIT signedAttrs` d’un SignerInfo brut, réétiqueté en SET.
+fn signed_attributes_der(signer_info: &Any) -> Option<Vec<u8>> {
+    // version, sid, digestAlgorithm, [0] signedAttrs
+    let attrs = children(signer_info)?.into_iter().nth(3)?;
+    if attrs.tag()
+        != (Tag::ContextSpecific {
+            constructed: true,
+            number: TagNumber::N0,
+        })
+    {
+        return None;
+    }
+    let mut encoded = attrs.to_der().ok()?;
+    encoded[0] = 0x31;
+    Some(encoded)
+}
+
+fn subject_key_identifier(cert: &Certificate) -> Option<Vec<u8>> {
+    const ID_CE_SKI: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.14");
+    let ext = cert
+        .tbs_certificate
+        .extensions
+        .as_ref()?
+        .iter()
+        .find(|e| e.extn_id == ID_CE_SKI)?;
+    let ski = der::asn1::OctetString::from_der(ext.extn_value.as_bytes()).ok()?;
+    Some(ski.as_bytes().to_vec())
+}
+
+/// Éléments d’un type construit.
+fn children(any: &Any) -> Option<Vec<Any>> {
+    let mut reader = SliceReader::new(any.value()).ok()?;
+    let mut out = Vec::new();
+    while !reader.is_finished() {
+        out.push(reader.decode().ok()?);
+    }
+    Some(out)
+}
+
+/// Élément DER complet en tête de `buf`.
+pub fn der_prefix(buf: &[u8]) -> Option<&[u8]> {
+    let header = Header::decode(&mut SliceReader::new(buf).ok()?).ok()?;
+    let total = (header.encoded_len().ok()? + header.length).ok()?;
+    buf.get(..usize::try_from(total).ok()?)
+}
@@ -0,0 +1,141 @@
+use crate::cms::digest::{DigestAlgorithm, Digests};
+use crate::cms::signed_data::CmsSignedData;
+use crate::x509::name::display_name;
+use anyhow::{bail, Context, Result};
+use der::asn1::{Int, ObjectIdentifier, OctetString};
+use der::{Any, Decode, DecodeValue, FixedTag, Header, Reader, Tag};
+use x509_cert::spki::AlgorithmIdentifierOwned;
+
+/// id-aa-signatureTimeStampToken (RFC 3161 annexe A).
+pub const ID_AA_SIGNATURE_TIMESTAMP: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.2.14");
+
+const ID_CT_TST_INFO: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.4");
+
+/// Contenu utile d’un jeton d’horodatage RFC 3161 (TimeStampToken).
+#[derive(Debug, Clone)]
+pub struct TimeStampToken {
+    /// genTime au format RFC 3339 (UTC).
+    pub gen_time: String,
+    pub policy: String,
+    pub serial: String,
+    pub hash_algorithm: ObjectIdentifier,
+    pub hashed_message: Vec<u8>,
+    /// DN du certificat de l’autorité d’horodatage, s’il est embarqué.
+    pub signer_dn: Option<String>,
+    /// Sujets des certificats embarqués.
+    pub certificates: Vec<String>,
+}
+
+/// TSTInfo (RFC 3161 §2.4.2), limité aux champs exploités.
+struct TstInfo {
+    policy: ObjectIdentifier,
+    hash_algorithm: AlgorithmIdentifierOwned,
+    hashed_message: OctetString,
+    serial_number: Int,
+    gen_time: Any,
+}
+
+impl FixedTag for TstInfo {
+    const TAG: Tag = Tag::Sequence;
+}
+
+impl<'a> DecodeValue<'a> for TstInfo {
+    fn decode_value<R: Reader<'a>>(reader: &mut R, header: Header) -> der::Result<Self> {
+        reader.read_nested(header.length, |r| {
+            let _version: u8 = r.decode()?;
+            let policy = r.decode()?;
+            let (hash_algorithm, hashed_message) =
+                r.sequence(|m| Ok((m.decode()?, m.decode()?)))?;
+            let serial_number = r.decode()?;
+            let gen_time = r.decode()?;
+            // accuracy, ordering, nonce, tsa, extensions : ignorés.
+            while !r.is_finished() {
+                let _: Any = r.decode()?;
+            }
+            Ok(TstInfo {
+                policy,
+                hash_algorithm,
+                hashed_message,
+                serial_number,
+                gen_time,
+            })
+        })
+    }
+}
+
+impl TimeStampToken {
+    /// Décode un TimeStampToken DER (ContentInfo / SignedData / TSTInfo).
+    pub fn from_der(token: &[u8]) -> Result<TimeStampToken> {
+        let sd = CmsSignedData::from_der(token).context("TimeStampToken")?;
+        if sd.content_type != ID_CT_TST_INFO {
+            bail!(
+                "TimeStampToken : contenu {} au lieu de TSTInfo",
+                sd.content_type
+            );
+        }
+        let econtent = sd
+            .content
+            .as_deref()
+            .context("TimeStampToken : TSTInfo absent")?;
+        let tst = TstInfo::from_der(econtent).context("TSTInfo invalide")?;
+
+        let signer_dn = sd
+            .signer_certificate()
+            .map(|c| display_name(&c.tbs_certificate.subject));
+
+        Ok(TimeStampToken {
+            gen_time: generalized_time(tst.gen_time.value())
+                .context("TSTInfo : genTime invalide")?,
+            policy: tst.policy.to_string(),
+            serial: hex::encode(tst.serial_number.as_bytes()),
+            hash_algorithm: tst.hash_algorithm.oid,
+            hashed_message: tst.hashed_message.as_bytes().to_vec(),
+            signer_dn,
+            certificates: sd
+                .certificates
+                .iter()
+                .map(|c| display_name(&c.tbs_certificate.subject))
+                .collect(),
+        })
+    }
+
+    /// Algorithme d’empreinte du jeton, s’il est pris en charge.
+    pub fn digest_algorithm(&self) -> Option<DigestAlgorithm> {
+        DigestAlgorithm::from_oid(&self.hash_algorithm)
+    }
+
+    /// Compare l’empreinte du jeton à celle des données horodatées ; `None` si
+    /// l’algorithme d’empreinte n’est pas pris en charge ou n’a pas été calculé.
+    pub fn imprint_matches(&self, digests: &Digests) -> Option<bool> {
+        let digest = digests.get(self.digest_algorithm()?)?;
+        Some(digest == self.hashed_message)
+    }
+}
+
+/// `YYYYMMDDHHMMSS[.f*]Z` → RFC 3339. Les fractions de seconde, que le type
+/// `GeneralizedTime` de `der` refuse, sont conservées.
+fn generalized_time(raw: &[u8]) -> Option<String> {
+    let s = std::str::from_utf8(raw).ok()?.strip_suffix('Z')?;
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+    if whole.len() != 14
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !frac.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let frac = if frac.is_empty() {
+        String::new()
+    } else {
+        format!(".{frac}")
+    };
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}{frac}Z",
+        &whole[0..4],
+        &whole[4..6],
+        &whole[6..8],
+        &whole[8..10],
+        &whole[10..12],
+        &whole[12..14]
+    ))
+}
@@ -1,17 +1,40 @@
+use crate::cms::digest::{DigestAlgorithm, Digests, MultiHasher};
+use crate::cms::signature::Indeterminate;
+use crate::cms::signed_data::{oid_name, CmsSignedData, CmsSigner};
+use crate::cms::tsp::{TimeStampToken, ID_AA_SIGNATURE_TIMESTAMP};
 use crate::infra::fs::{read_file_bounded, Limits};
-use crate::report::{final_verdict, Component, Report, ReportVerdict};
+use crate::report::{
+    aggregate_layers, aggregate_signers, final_verdict, CmsLayer, CmsSignerReport, Component,
+    Report, ReportVerdict,
+};
+use crate::revocation::{assess_chain, ValidationMaterial};
+use crate::x509::chain::anchor_certificates;
+use crate::x509::name::display_name;
 use anyhow::{Context, Result};
 use base64::engine::general_purpose::STANDARD as B64;
 use base64::Engine as _;
+use der::Encode;
 use sha2::{Digest, Sha256};
+use std::time::SystemTime;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
 #[cfg(feature = "openssl-backend")]
 mod openssl_impl {
     use super::*;
+    use der::Encode;
+    use openssl::cms::{CMSOptions, CmsContentInfo};
+    use openssl::hash::MessageDigest;
+    use openssl::md::Md;
     use openssl::nid::Nid;
     use openssl::pkcs7::{Pkcs7, Pkcs7Flags};
+    use openssl::pkey_ctx::PkeyCtx;
+    use openssl::rsa::Padding;
+    use openssl::sign::{RsaPssSaltlen, Verifier};
     use openssl::stack::Stack;
-    use openssl::x509::{store::X509StoreBuilder, X509NameRef, X509};
+    use openssl::x509::store::{X509Store, X509StoreBuilder};
+    use openssl::x509::verify::X509VerifyParam;
+    use openssl::x509::{X509NameRef, X509PurposeId, X509Ref, X509StoreContext, X509};
 
     fn x509_cn_or_first(n: &X509NameRef) -> String {
         if let Some(cn) = n.entries_by_nid(Nid::COMMONNAME).next() {
@@ -28,36 +51,63 @@ mod openssl_impl {
             .unwrap_or_default()
     }
 
-    /// Vérifie un PKCS#7 détaché et retourne (subjects des signataires, DN principal).
-    pub fn verify_detached(
-        sig_der: &[u8],
-        data: &[u8],
-        anchors_pem: &[String],
-    ) -> anyhow::Result<(Vec<String>, Option<String>)> {
-        // 1) PKCS#7
-        let pkcs7 = Pkcs7::from_der(sig_der).context("PKCS#7 DER invalide")?;
-
-        // 2) Store d’ancrages
+    /// Magasin des anchors, évalué à l’instant `at`.
+    fn anchor_store(anchors_pem: &[String], at: SystemTime) -> anyhow::Result<X509Store> {
         let mut store_bld = X509StoreBuilder::new().context("init X509StoreBuilder")?;
+        let mut param = X509VerifyParam::new().context("init X509VerifyParam")?;
+        let secs = at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        param.set_time(secs.try_into().context("instant de validation")?);
+        store_bld
+            .set_param(&param)
+            .context("instant de validation")?;
         for pem in anchors_pem {
             for crt in X509::stack_from_pem(pem.as_bytes()).context("anchors PEM invalides")? {
                 store_bld.add_cert(crt).context("ajout anchor")?;
             }
         }
-        let store = store_bld.build();
+        Ok(store_bld.build())
+    }
 
-        // 3) Pile additionnelle vide
-        let extra = Stack::<X509>::new().context("init stack X509")?;
+    /// Certificats non approuvés proposés pour la construction de chaîne.
+    fn untrusted(intermediates: &[Vec<u8>]) -> anyhow::Result<Stack<X509>> {
+        let mut stack = Stack::<X509>::new().context("init stack X509")?;
+        for der in intermediates {
+            if let Ok(crt) = X509::from_der(der) {
+                stack.push(crt).context("ajout certificat intermédiaire")?;
+            }
+        }
+        Ok(stack)
+    }
 
-        // 4) Vérif détachée
+    /// Vérifie un PKCS#7 (détaché si `data` est fourni, sinon à contenu
+    /// encapsulé) et retourne (contenu signé, signataire).
+    pub fn verify_pkcs7(
+        sig_der: &[u8],
+        data: Option<&[u8]>,
+        trust: &TrustStore,
+        at: ValidationTime,
+    ) -> anyhow::Result<(Vec<u8>, Signer)> {
+        let anchors_pem = trust.anchors_pem;
+        // 1) PKCS#7
+        let pkcs7 = Pkcs7::from_der(sig_der).context("PKCS#7 DER invalide")?;
+
+        // 2) Pile additionnelle : certificats intermédiaires connus (DSS…)
+        let extra = untrusted(trust.intermediates)?;
+
+        // 3) Vérif de la valeur de signature par l’API CMS, qui couvre aussi
+        // RSASSA-PSS (le contenu signé est recopié dans `sink`) ; la chaîne
+        // est évaluée à part.
         let mut sink = Vec::<u8>::new();
-        pkcs7
+        CmsContentInfo::from_der(sig_der)
+            .context("CMS DER invalide")?
             .verify(
-                &extra,
-                &store,
-                Some(data),
+                Some(&extra),
+                None,
+                data,
                 Some(&mut sink),
-                Pkcs7Flags::BINARY,
+                CMSOptions::BINARY | CMSOptions::NO_SIGNER_CERT_VERIFY,
             )
             .map_err(|e| anyhow::anyhow!("Signature PKCS#7 non valide: {e}"))?;
 
@@ -68,12 +118,22 @@ mod openssl_impl {
 
         let mut subjects = Vec::new();
         let mut signer_dn: Option<String> = None;
+        let mut chain = Err("Certificat du signataire absent".to_string());
 
         if !signers.is_empty() {
+            let others = CmsSignedData::from_der(sig_der)
+                .map(|sd| {
+                    sd.certificates
+                        .iter()
+                        .filter_map(|c| X509::from_der(&c.to_der().ok()?).ok())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
             for (i, cert) in signers.iter().enumerate() {
                 let dn = x509_cn_or_first(cert.subject_name());
                 if i == 0 {
                     signer_dn = Some(dn.clone());
+                    chain = chain_outcome(cert, &others, trust, at, "Certificat du signataire");
                 }
                 subjects.push(dn);
             }
@@ -90,7 +150,431 @@ mod openssl_impl {
             }
         }
 
-        Ok((subjects, signer_dn))
+        Ok((
+            sink,
+            Signer {
+                subjects,
+                dn: signer_dn,
+                chain,
+            },
+        ))
+    }
+
+    /// Vérifie la signature d’un TimeStampToken et la chaîne de l’autorité
+    /// d’horodatage (usage timeStamping) contre les anchors.
+    pub fn verify_token(token_der: &[u8], trust: &TrustStore) -> anyhow::Result<Signer> {
+        let mut cms = CmsContentInfo::from_der(token_der).context("TimeStampToken DER invalide")?;
+        let extra = untrusted(trust.intermediates)?;
+        cms.verify(
+            Some(&extra),
+            None,
+            None,
+            None,
+            CMSOptions::BINARY | CMSOptions::NO_SIGNER_CERT_VERIFY,
+        )
+        .map_err(|e| anyhow::anyhow!("Signature du jeton non valide: {e}"))?;
+        let mut store_bld = X509StoreBuilder::new().context("init X509StoreBuilder")?;
+        for pem in trust.anchors_pem {
+            for crt in X509::stack_from_pem(pem.as_bytes()).context("anchors PEM invalides")? {
+                store_bld.add_cert(crt).context("ajout anchor")?;
+            }
+        }
+        store_bld
+            .set_purpose(X509PurposeId::TIMESTAMP_SIGN)
+            .context("usage timeStamping")?;
+        let store = store_bld.build();
+        let chain = cms
+            .verify(Some(&extra), Some(&store), None, None, CMSOptions::BINARY)
+            .map_err(|e| format!("Chaîne de l’autorité d’horodatage non établie: {e}"));
+        Ok(Signer {
+            subjects: Vec::new(),
+            dn: None,
+            chain,
+        })
+    }
+
+    /// Vérifie une signature RSA PKCS#1 v1.5 sur l’empreinte SHA-1 `digest` et
+    /// la chaîne du premier certificat de `certs_der`. Retourne (subjects, DN
+    /// signataire).
+    pub fn verify_rsa_sha1(
+        sig_value: &[u8],
+        certs_der: &[Vec<u8>],
+        digest: &[u8],
+        trust: &TrustStore,
+        at: ValidationTime,
+    ) -> anyhow::Result<Signer> {
+        let certs = certs_der
+            .iter()
+            .map(|der| X509::from_der(der))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Certificat /Cert invalide")?;
+        let (signer, others) = certs.split_first().context("Aucun certificat dans /Cert")?;
+
+        let key = signer.public_key().context("Clé publique du signataire")?;
+        let mut pctx = PkeyCtx::new(&key).context("init PkeyCtx")?;
+        pctx.verify_init().context("init vérification")?;
+        pctx.set_rsa_padding(Padding::PKCS1)
+            .context("bourrage PKCS#1")?;
+        pctx.set_signature_md(Md::sha1())
+            .context("empreinte SHA-1")?;
+        let ok = pctx
+            .verify(digest, sig_value)
+            .map_err(|e| anyhow::anyhow!("Signature PKCS#1 non valide: {e}"))?;
+        if !ok {
+            anyhow::bail!("Signature PKCS#1 non valide pour le certificat /Cert");
+        }
+
+        let chain = chain_outcome(signer, others, trust, at, "Certificat /Cert");
+        let subjects = certs
+            .iter()
+            .map(|c| x509_cn_or_first(c.subject_name()))
+            .collect::<Vec<_>>();
+        Ok(Signer {
+            dn: subjects.first().cloned(),
+            subjects,
+            chain,
+        })
+    }
+
+    /// Vérifie la signature du SignerInfo `si` sur `signed_attrs` (ses
+    /// attributs signés, ou le contenu en leur absence) par le certificat du
+    /// signataire, puis sa chaîne. Le contenu lui-même n’est pas relu : son
+    /// empreinte a été comparée à messageDigest par l’appelant.
+    pub fn verify_signed_attributes(
+        sd: &CmsSignedData,
+        si: &CmsSigner,
+        signed_attrs: &[u8],
+        trust: &TrustStore,
+        at: ValidationTime,
+    ) -> anyhow::Result<Signer> {
+        let signer = sd
+            .certificate_of(si)
+            .context("Certificat du signataire absent")?;
+        let signer = X509::from_der(&signer.to_der()?).context("Certificat du signataire")?;
+        let others = sd
+            .certificates
+            .iter()
+            .filter_map(|c| X509::from_der(&c.to_der().ok()?).ok())
+            .collect::<Vec<_>>();
+
+        let md = match DigestAlgorithm::from_oid(&si.digest_algorithm) {
+            Some(DigestAlgorithm::Sha1) => MessageDigest::sha1(),
+            Some(DigestAlgorithm::Sha224) => MessageDigest::sha224(),
+            Some(DigestAlgorithm::Sha256) => MessageDigest::sha256(),
+            Some(DigestAlgorithm::Sha384) => MessageDigest::sha384(),
+            Some(DigestAlgorithm::Sha512) => MessageDigest::sha512(),
+            Some(DigestAlgorithm::Sha3_224) => MessageDigest::sha3_224(),
+            Some(DigestAlgorithm::Sha3_256) => MessageDigest::sha3_256(),
+            Some(DigestAlgorithm::Sha3_384) => MessageDigest::sha3_384(),
+            Some(DigestAlgorithm::Sha3_512) => MessageDigest::sha3_512(),
+            None => anyhow::bail!(
+                "Algorithme d’empreinte non pris en charge ({})",
+                si.digest_algorithm
+            ),
+        };
+        let key = signer.public_key().context("Clé publique du signataire")?;
+        let signature = si.signature.as_slice();
+        let ok = match si.signature_algorithm.oid.to_string().as_str() {
+            // Ed25519, Ed448 : pas d’empreinte préalable.
+            "1.3.101.112" | "1.3.101.113" => Verifier::new_without_digest(&key)
+                .context("init Verifier")?
+                .verify_oneshot(signature, signed_attrs),
+            oid => {
+                let mut verifier = Verifier::new(md, &key).context("init Verifier")?;
+                if oid == "1.2.840.113549.1.1.10" {
+                    // RSASSA-PSS : MGF1 sur l’empreinte du signataire, sel détecté.
+                    verifier.set_rsa_padding(Padding::PKCS1_PSS)?;
+                    verifier.set_rsa_mgf1_md(md)?;
+                    verifier.set_rsa_pss_saltlen(RsaPssSaltlen::MAXIMUM_LENGTH)?;
+                }
+                verifier
+                    .update(signed_attrs)
+                    .and_then(|_| verifier.verify(signature))
+            }
+        }
+        .map_err(|e| anyhow::anyhow!("Signature des attributs signés non valide: {e}"))?;
+        if !ok {
+            anyhow::bail!("Signature des attributs signés non valide");
+        }
+
+        let chain = chain_outcome(&signer, &others, trust, at, "Certificat du signataire");
+        let dn = x509_cn_or_first(signer.subject_name());
+        Ok(Signer {
+            subjects: vec![dn.clone()],
+            dn: Some(dn),
+            chain,
+        })
+    }
+
+    fn chain_outcome(
+        leaf: &X509Ref,
+        others: &[X509],
+        trust: &TrustStore,
+        at: ValidationTime,
+        what: &str,
+    ) -> Result<(), String> {
+        at.evaluate(|t| {
+            verify_chain(leaf, others, trust, t)
+                .then_some(())
+                .ok_or_else(|| format!("{what} non rattaché aux anchors fournis"))
+        })
+    }
+
+    /// Chaîne de `leaf` vers les anchors à l’instant `at`, avec `others` et
+    /// les intermédiaires connus comme certificats non approuvés.
+    fn verify_chain(leaf: &X509Ref, others: &[X509], trust: &TrustStore, at: SystemTime) -> bool {
+        let build = || -> anyhow::Result<bool> {
+            let store = anchor_store(trust.anchors_pem, at)?;
+            let mut chain = untrusted(trust.intermediates)?;
+            for c in others {
+                chain.push(c.clone())?;
+            }
+            let mut ctx = X509StoreContext::new()?;
+            Ok(ctx.init(&store, leaf, &chain, |c| c.verify_cert())?)
+        };
+        build().unwrap_or(false)
+    }
+}
+
+/// Signataire dont la valeur de signature a été vérifiée. Sa chaîne est
+/// évaluée à part : un échec la laisse indéterminée sans invalider la
+/// signature.
+#[derive(Debug, Clone)]
+struct Signer {
+    /// Sujets de la chaîne vérifiée, du signataire à l’ancrage (le seul
+    /// signataire si elle n’a pu être établie).
+    subjects: Vec<String>,
+    dn: Option<String>,
+    chain: std::result::Result<(), String>,
+}
+
+/// Instant auquel la chaîne d’un signataire est évaluée.
+#[derive(Debug, Clone, Copy)]
+pub enum ValidationTime {
+    /// Aucune date attestée ni déclarée : instant présent.
+    Now,
+    /// genTime d’un horodatage de signature vérifié.
+    Attested(SystemTime),
+    /// signingTime déclaré par le signataire, non attesté.
+    Claimed(SystemTime),
+}
+
+impl ValidationTime {
+    /// genTime de l’horodatage de signature `timestamp` s’il est pleinement
+    /// valide, à défaut signingTime du SignerInfo `si`.
+    fn of(si: &CmsSigner, timestamp: Option<&Report>) -> Self {
+        let attested = timestamp
+            .filter(|ts| ts.verdict == ReportVerdict::Valid)
+            .and_then(|ts| ts.timestamp_rfc3161.as_deref())
+            .and_then(|t| OffsetDateTime::parse(t, &Rfc3339).ok());
+        match (attested, si.signing_time()) {
+            (Some(t), _) => Self::Attested(t.into()),
+            (None, Some(t)) => Self::Claimed(t.into()),
+            (None, None) => Self::Now,
+        }
+    }
+
+    /// Instant retenu pour le SignerInfo `si`, son horodatage de signature
+    /// vérifié auprès de `trust`.
+    pub fn of_signer(si: &CmsSigner, trust: &TrustStore) -> Self {
+        Self::of(si, signature_timestamp(si, trust).as_ref())
+    }
+
+    /// Évalue `check` à l’instant retenu. Une date seulement déclarée ne
+    /// fait foi que si la chaîne vaut aussi à l’instant présent.
+    fn evaluate<T>(
+        self,
+        check: impl Fn(SystemTime) -> std::result::Result<T, String>,
+    ) -> std::result::Result<T, String> {
+        let date = |at: SystemTime| {
+            OffsetDateTime::from(at)
+                .format(&Rfc3339)
+                .unwrap_or_default()
+        };
+        match self {
+            Self::Now => check(SystemTime::now()),
+            Self::Attested(at) => {
+                check(at).map_err(|e| format!("{e} au {} (horodatage de signature)", date(at)))
+            }
+            Self::Claimed(at) => {
+                let value =
+                    check(at).map_err(|e| format!("{e} au {} (signingTime déclaré)", date(at)))?;
+                check(SystemTime::now()).map(|_| value).map_err(|e| {
+                    format!(
+                        "{e} ; valide au {} selon le signingTime déclaré, non attesté par un horodatage",
+                        date(at)
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Vérification native (RustCrypto) : valeur de signature du SignerInfo,
+/// messageDigest et chaîne jusqu’aux anchors. Même interface que
+/// `openssl_impl`, qui la recoupe quand le backend est compilé.
+mod native_impl {
+    use super::*;
+    use crate::cms::signature::{verify, verify_pkcs1_digest};
+    use crate::x509::chain::{verify_chain, TIME_STAMPING};
+    use der::asn1::ObjectIdentifier;
+    use der::Decode;
+    use x509_cert::Certificate;
+
+    /// Vérifie le SignerInfo `si` de la SignedData sur `data` (détachée) ou
+    /// son contenu encapsulé, et retourne (contenu signé, signataire).
+    pub fn verify_pkcs7(
+        sd: &CmsSignedData,
+        si: &CmsSigner,
+        data: Option<&[u8]>,
+        trust: &TrustStore,
+        at: ValidationTime,
+    ) -> Result<(Vec<u8>, Signer)> {
+        let signer = verify_content(sd, si, data, trust, None, at)?;
+        let content = data.or(sd.content.as_deref()).unwrap_or_default();
+        Ok((content.to_vec(), signer))
+    }
+
+    /// Vérifie la signature du SignerInfo `si` d’un TimeStampToken et la
+    /// chaîne de l’autorité d’horodatage (usage timeStamping) contre les
+    /// anchors.
+    pub fn verify_token(sd: &CmsSignedData, si: &CmsSigner, trust: &TrustStore) -> Result<Signer> {
+        verify_content(
+            sd,
+            si,
+            None,
+            trust,
+            Some(TIME_STAMPING),
+            ValidationTime::Now,
+        )
+    }
+
+    /// Vérifie une signature RSA PKCS#1 v1.5 sur l’empreinte SHA-1 `digest` et
+    /// la chaîne du premier certificat de `certs_der`.
+    pub fn verify_rsa_sha1(
+        sig_value: &[u8],
+        certs_der: &[Vec<u8>],
+        digest: &[u8],
+        trust: &TrustStore,
+        at: ValidationTime,
+    ) -> Result<Signer> {
+        let certs = certs_der
+            .iter()
+            .map(|der| Certificate::from_der(der))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Certificat /Cert invalide")?;
+        let (signer, others) = certs.split_first().context("Aucun certificat dans /Cert")?;
+        let spki = &signer.tbs_certificate.subject_public_key_info;
+        if !verify_pkcs1_digest(spki, DigestAlgorithm::Sha1, digest, sig_value)? {
+            anyhow::bail!("Signature PKCS#1 non valide pour le certificat /Cert");
+        }
+        Ok(chain(signer, others, trust, None, at))
+    }
+
+    /// Vérifie la signature du SignerInfo `si` sur `signed_attrs` (ses
+    /// attributs signés, ou le contenu en leur absence) par le certificat du
+    /// signataire, puis sa chaîne.
+    pub fn verify_signed_attributes(
+        sd: &CmsSignedData,
+        si: &CmsSigner,
+        signed_attrs: &[u8],
+        trust: &TrustStore,
+        at: ValidationTime,
+    ) -> Result<Signer> {
+        signer_signature(sd, si, signed_attrs, trust, None, at)
+    }
+
+    /// messageDigest comparé au contenu, puis signature des attributs signés
+    /// (ou du contenu lui-même en leur absence).
+    fn verify_content(
+        sd: &CmsSignedData,
+        si: &CmsSigner,
+        data: Option<&[u8]>,
+        trust: &TrustStore,
+        purpose: Option<ObjectIdentifier>,
+        at: ValidationTime,
+    ) -> Result<Signer> {
+        let content = data
+            .or(sd.content.as_deref())
+            .context("Contenu signé absent")?;
+        let Some(signed_attrs) = si.signed_attrs_der.as_deref() else {
+            return signer_signature(sd, si, content, trust, purpose, at);
+        };
+        let algorithm = DigestAlgorithm::from_oid(&si.digest_algorithm).ok_or_else(|| {
+            Indeterminate(format!(
+                "Algorithme d’empreinte non pris en charge ({})",
+                oid_name(&si.digest_algorithm)
+            ))
+        })?;
+        let expected = si
+            .message_digest()
+            .context("Attribut messageDigest absent")?;
+        if algorithm.digest(content) != expected {
+            anyhow::bail!("messageDigest différent de l’empreinte des données signées");
+        }
+        signer_signature(sd, si, signed_attrs, trust, purpose, at)
+    }
+
+    fn signer_signature(
+        sd: &CmsSignedData,
+        si: &CmsSigner,
+        message: &[u8],
+        trust: &TrustStore,
+        purpose: Option<ObjectIdentifier>,
+        at: ValidationTime,
+    ) -> Result<Signer> {
+        let signer = sd
+            .certificate_of(si)
+            .context("Certificat du signataire absent")?;
+        let digest = DigestAlgorithm::from_oid(&si.digest_algorithm);
+        let spki = &signer.tbs_certificate.subject_public_key_info;
+        if !verify(
+            spki,
+            &si.signature_algorithm,
+            digest,
+            message,
+            &si.signature,
+        )? {
+            anyhow::bail!("Signature du SignerInfo non valide");
+        }
+        Ok(chain(signer, &sd.certificates, trust, purpose, at))
+    }
+
+    /// Chaîne de `leaf` jusqu’aux anchors ; son échec est reporté dans
+    /// `Signer::chain` sans invalider la signature.
+    fn chain(
+        leaf: &Certificate,
+        others: &[Certificate],
+        trust: &TrustStore,
+        purpose: Option<ObjectIdentifier>,
+        at: ValidationTime,
+    ) -> Signer {
+        let anchors = anchor_certificates(trust.anchors_pem);
+        let mut pool = others.to_vec();
+        pool.extend(
+            trust
+                .intermediates
+                .iter()
+                .filter_map(|der| Certificate::from_der(der).ok()),
+        );
+        let verified = at.evaluate(|t| {
+            verify_chain(leaf, &pool, &anchors, purpose, t).map_err(|e| format!("{e:#}"))
+        });
+        let (subjects, chain) = match verified {
+            Ok(chain) => (
+                chain
+                    .iter()
+                    .map(|c| display_name(&c.tbs_certificate.subject))
+                    .collect(),
+                Ok(()),
+            ),
+            Err(e) => (vec![display_name(&leaf.tbs_certificate.subject)], Err(e)),
+        };
+        Signer {
+            dn: subjects.first().cloned(),
+            subjects,
+            chain,
+        }
     }
 }
 
@@ -98,8 +582,8 @@ pub fn verify_cms_entrypoint(
     sig_path: &str,
     data_path: Option<&str>,
     anchors_pem: &[String],
-    _crl: &[String],
-    _ocsp: &[String],
+    crl: &[String],
+    ocsp: &[String],
     _online: bool,
     limits: &Limits,
 ) -> Result<Report> {
@@ -109,120 +593,854 @@ pub fn verify_cms_entrypoint(
     } else {
         None
     };
+    let material = ValidationMaterial::from_files(crl, ocsp, limits)?;
+    verify_cms_bytes(&sig, data.as_deref(), anchors_pem, &material)
+}
+
+/// Profondeur maximale d’un P7M imbriqué (.p7m.p7m…).
+const MAX_LAYERS: usize = 8;
+
+/// Vérifie une signature CMS/P7S (DER, Base64 ou PEM) déjà en mémoire, avec
+/// révocation hors ligne à partir de `material`.
+///
+/// Un P7M dont le contenu encapsulé est lui-même une signature est déroulé :
+/// chaque couche est vérifiée et reportée dans `layers`, et `document_sha256`
+/// porte sur le document de la couche la plus interne.
+pub fn verify_cms_bytes(
+    sig: &[u8],
+    data: Option<&[u8]>,
+    anchors_pem: &[String],
+    material: &ValidationMaterial,
+) -> Result<Report> {
+    let sig_der = signature_der(sig)?;
+    let (mut r, mut content) = verify_layer(&sig_der, data, anchors_pem, material)?;
+    let mut layers = Vec::new();
+    while let Some((inner_der, encoding)) = content.as_deref().and_then(nested_signed_data) {
+        if layers.is_empty() {
+            layers.push(CmsLayer {
+                index: 0,
+                encoding: encoding_of(sig).into(),
+                report: r.clone(),
+            });
+        }
+        if layers.len() == MAX_LAYERS {
+            anyhow::bail!("P7M imbriqué au-delà de {MAX_LAYERS} couches");
+        }
+        let (report, inner) = verify_layer(&inner_der, None, anchors_pem, material)?;
+        layers.push(CmsLayer {
+            index: layers.len(),
+            encoding: encoding.into(),
+            report,
+        });
+        content = inner;
+    }
+    if let Some(innermost) = layers.last() {
+        r.document_sha256 = innermost.report.document_sha256.clone();
+        r.layers = layers;
+        aggregate_layers(&mut r);
+        final_verdict(&mut r);
+    }
+    Ok(r)
+}
+
+/// Vérifie une couche CMS et sa révocation hors ligne (CRL de la SignedData,
+/// puis CRL/OCSP fournis en fichiers) ; retourne aussi son contenu encapsulé.
+fn verify_layer(
+    sig_der: &[u8],
+    data: Option<&[u8]>,
+    anchors_pem: &[String],
+    material: &ValidationMaterial,
+) -> Result<(Report, Option<Vec<u8>>)> {
+    let trust = TrustStore {
+        anchors_pem,
+        intermediates: &[],
+    };
+    let mut r = verify_cms_der(sig_der, data, &trust)?;
+    let Ok(sd) = CmsSignedData::from_der(sig_der) else {
+        return Ok((r, None));
+    };
+    let anchors = anchor_certificates(anchors_pem);
+    let embedded = sd.validation_material();
+    let assess = |r: &mut Report, si: &CmsSigner| {
+        if let Some(signer) = sd.certificate_of(si) {
+            r.revocation = assess_chain(
+                ValidationTime::of_signer(si, &trust),
+                signer,
+                &sd.certificates,
+                &anchors,
+                Some(&embedded),
+                material,
+            )
+            .revocation;
+            final_verdict(r);
+        }
+    };
+    if r.signers.is_empty() {
+        if let Some(si) = sd.signer() {
+            assess(&mut r, si);
+        }
+    } else {
+        for s in &mut r.signers {
+            assess(&mut s.report, &sd.signers[s.index]);
+        }
+        aggregate_signers(&mut r);
+        final_verdict(&mut r);
+    }
+    // Le contenu encapsulé n’est déroulé que pour un P7M, pas lorsque les
+    // données signées sont fournies à part.
+    Ok((r, sd.content.filter(|_| data.is_none())))
+}
+
+/// Signature CMS encapsulée dans `content` (DER/BER, Base64 ou PEM) et son
+/// encodage, si le contenu en est une.
+fn nested_signed_data(content: &[u8]) -> Option<(Vec<u8>, &'static str)> {
+    let der = signature_der(content).ok()?;
+    CmsSignedData::from_der(&der).ok()?;
+    Some((der, encoding_of(content)))
+}
+
+fn encoding_of(sig: &[u8]) -> &'static str {
+    if sig.starts_with(b"-----BEGIN") {
+        "PEM"
+    } else if base64_payload(sig).is_some() {
+        "Base64"
+    } else {
+        "DER"
+    }
+}
+
+/// Écrit dans `out` le contenu encapsulé du P7M `sig_path`, déroulé jusqu’à
+/// la couche la plus interne s’il est imbriqué ; retourne sa taille.
+pub fn extract_content(sig_path: &str, out: &str, limits: &Limits) -> Result<usize> {
+    let sig = read_file_bounded(sig_path, limits)?;
+    let mut sd = CmsSignedData::from_der(&signature_der(&sig)?)?;
+    for _ in 1..MAX_LAYERS {
+        match sd.content.as_deref().and_then(nested_signed_data) {
+            Some((inner, _)) => sd = CmsSignedData::from_der(&inner)?,
+            None => break,
+        }
+    }
+    let content = sd
+        .content
+        .context("Signature détachée : aucun contenu encapsulé à extraire")?;
+    std::fs::write(out, &content).with_context(|| format!("Écriture de {out}"))?;
+    Ok(content.len())
+}
+
+/// SignedData DER d’un fichier P7S/P7M en DER (ou BER), Base64 ou PEM.
+fn signature_der(sig: &[u8]) -> Result<Vec<u8>> {
+    if sig.starts_with(b"-----BEGIN") {
+        let s = std::str::from_utf8(sig).context("P7S PEM non UTF-8")?;
+        return extract_pem_block(s, "PKCS7");
+    }
+    Ok(base64_payload(sig).unwrap_or_else(|| sig.to_vec()))
+}
+
+/// Octets décodés d’un texte Base64, éventuellement découpé en lignes.
+fn base64_payload(text: &[u8]) -> Option<Vec<u8>> {
+    let compact: Vec<u8> = text
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    B64.decode(compact).ok()
+}
+
+/// Ancrages de confiance et certificats intermédiaires disponibles (DSS…)
+/// pour la construction de chaîne.
+#[derive(Debug, Clone, Copy)]
+pub struct TrustStore<'a> {
+    pub anchors_pem: &'a [String],
+    pub intermediates: &'a [Vec<u8>],
+}
+
+/// Vérifie une SignedData DER déjà en mémoire.
+///
+/// `data` porte le contenu signé d’une signature détachée (fichier `--data`,
+/// ou octets couverts par le ByteRange pour PAdES) ; à défaut, le contenu
+/// encapsulé du P7M est vérifié.
+pub fn verify_cms_der(sig_der: &[u8], data: Option<&[u8]>, trust: &TrustStore) -> Result<Report> {
+    let mut r = Report {
+        input_kind: "CMS".to_string(),
+        document_sha256: data.map(|d| hex::encode(Sha256::digest(d))),
+        ..Default::default()
+    };
+    let sd = match CmsSignedData::from_der(sig_der) {
+        Ok(sd) => sd,
+        Err(e) => {
+            reject(&mut r, format!("Structure CMS illisible: {e:#}"));
+            final_verdict(&mut r);
+            return Ok(r);
+        }
+    };
+    describe(&mut r, &sd);
+
+    let (signature, integrity, ltv) = match (data, sd.content.as_deref()) {
+        (Some(_), _) => (
+            "PKCS#7 détaché valide",
+            "MessageDigest/Data vérifiés",
+            LTV_DETACHED,
+        ),
+        (None, Some(content)) => {
+            r.document_sha256 = Some(hex::encode(Sha256::digest(content)));
+            (
+                "P7M enveloppé valide",
+                "MessageDigest/contenu encapsulé vérifiés",
+                "Non applicable (CMS enveloppé / P7M)",
+            )
+        }
+        (None, None) => {
+            r.signature = Component {
+                status: ReportVerdict::Warning,
+                detail: "Signature détachée : fournir les données signées (--data)".into(),
+            };
+            final_verdict(&mut r);
+            return Ok(r);
+        }
+    };
+    let content = data.or(sd.content.as_deref()).unwrap_or_default();
+    let several = sd.signers.len() > 1;
+    verify_signers(&mut r, &sd, |r, si| {
+        let at = validation_time(r, si, trust);
+        let failure =
+            si.and_then(|si| signed_attributes_failure(&sd, si, |a| Some(a.digest(content))));
+        let signed_attrs = si.and_then(|si| si.signed_attrs_der.as_deref());
+        let (verified, signature) = match (si, &failure, signed_attrs) {
+            // Contenu non conforme : reste à établir l’authenticité des
+            // attributs signés eux-mêmes.
+            (Some(si), Some(_), Some(signed_attrs)) => (
+                verify_attributes_signature(&sd, si, signed_attrs, trust, at),
+                "Signature des attributs signés valide",
+            ),
+            // Plusieurs signataires : chaque SignerInfo est vérifié seul, sur
+            // ses attributs signés ou le contenu lui-même.
+            (Some(si), None, _) if several => (
+                verify_attributes_signature(&sd, si, signed_attrs.unwrap_or(content), trust, at),
+                signature,
+            ),
+            _ => {
+                let verified = si
+                    .context("SignerInfo absent")
+                    .and_then(|si| native_impl::verify_pkcs7(&sd, si, data, trust, at));
+                #[cfg(feature = "openssl-backend")]
+                let verified = cross_check_signer(
+                    verified,
+                    openssl_impl::verify_pkcs7(sig_der, data, trust, at),
+                );
+                (verified.map(|(_, signer)| signer), signature)
+            }
+        };
+        match verified {
+            Ok(signer) => accept(r, trust.anchors_pem, signer, signature, integrity, ltv),
+            Err(e) => refuse(r, "Échec vérif PKCS#7", e),
+        }
+        if let Some(failure) = failure {
+            r.integrity = failure;
+        }
+    });
+
+    final_verdict(&mut r);
+    Ok(r)
+}
 
+/// Vérifie par `verify` chaque SignerInfo de `sd` : dans `r` pour un
+/// signataire unique, sinon dans un rapport par SignerInfo reporté dans
+/// `r.signers`, dont les composants sont agrégés.
+fn verify_signers(
+    r: &mut Report,
+    sd: &CmsSignedData,
+    mut verify: impl FnMut(&mut Report, Option<&CmsSigner>),
+) {
+    if sd.signers.len() < 2 {
+        verify(r, sd.signer());
+        return;
+    }
+    for (index, si) in sd.signers.iter().enumerate() {
+        let mut report = Report {
+            input_kind: r.input_kind.clone(),
+            document_sha256: r.document_sha256.clone(),
+            ..Default::default()
+        };
+        describe_signer(&mut report, sd, Some(si));
+        verify(&mut report, Some(si));
+        final_verdict(&mut report);
+        r.signers.push(CmsSignerReport { index, report });
+    }
+    aggregate_signers(r);
+}
+
+/// Vérifie une SignedData détachée dont le contenu n’est connu que par ses
+/// empreintes (octets d’un ByteRange lus en flux) : l’attribut messageDigest
+/// est comparé à l’empreinte du contenu, puis la signature des attributs
+/// signés est vérifiée.
+pub fn verify_cms_digests(sig_der: &[u8], digests: &Digests, trust: &TrustStore) -> Result<Report> {
     let mut r = Report {
         input_kind: "CMS".to_string(),
+        document_sha256: digests.get(DigestAlgorithm::Sha256).map(hex::encode),
         ..Default::default()
     };
 
-    if let Some(ref dat) = data {
-        let mut h = Sha256::new();
-        h.update(dat);
-        r.document_sha256 = Some(hex::encode(h.finalize()));
+    let sd = CmsSignedData::from_der(sig_der)?;
+    describe(&mut r, &sd);
+    sd.signer().context("SignerInfo absent")?;
+    verify_signers(&mut r, &sd, |r, si| {
+        let Some(si) = si else { return };
+        let at = validation_time(r, Some(si), trust);
+        let Some(signed_attrs) = si.signed_attrs_der.as_deref() else {
+            // La signature porte sur le contenu lui-même, que la lecture en
+            // flux ne garde pas : indéterminé, non invalide.
+            reject(
+                r,
+                "Attributs signés (messageDigest) absents : signature non vérifiable sans relire le contenu".into(),
+            );
+            r.signature.status = ReportVerdict::Warning;
+            return;
+        };
+        let failure = signed_attributes_failure(&sd, si, |a| digests.get(a).map(<[u8]>::to_vec));
+        let signature = match failure {
+            Some(_) => "Signature des attributs signés valide",
+            None => "PKCS#7 détaché valide",
+        };
+        match verify_attributes_signature(&sd, si, signed_attrs, trust, at) {
+            Ok(signer) => accept(
+                r,
+                trust.anchors_pem,
+                signer,
+                signature,
+                "MessageDigest/Data vérifiés",
+                LTV_DETACHED,
+            ),
+            Err(e) => refuse(r, "Échec vérif PKCS#7", e),
+        }
+        if let Some(failure) = failure {
+            r.integrity = failure;
+        }
+    });
+
+    final_verdict(&mut r);
+    Ok(r)
+}
+
+/// Attributs signés confrontés au contenu (RFC 5652 §5.4, §11) : contentType
+/// égal à l’eContentType, messageDigest égal à l’empreinte du contenu sous
+/// l’algorithme du SignerInfo (`digest_of`). Retourne l’intégrité en défaut,
+/// `None` si les attributs sont conformes ou absents (la signature porte
+/// alors sur le contenu lui-même).
+fn signed_attributes_failure(
+    sd: &CmsSignedData,
+    si: &CmsSigner,
+    digest_of: impl FnOnce(DigestAlgorithm) -> Option<Vec<u8>>,
+) -> Option<Component> {
+    si.signed_attrs_der.as_ref()?;
+    let failure = |status, detail| Some(Component { status, detail });
+    match si.content_type() {
+        None => {
+            return failure(
+                ReportVerdict::Invalid,
+                "Attribut signé contentType absent".into(),
+            )
+        }
+        Some(signed) if signed != sd.content_type => {
+            return failure(
+                ReportVerdict::Invalid,
+                format!(
+                    "contentType signé ({}) différent de l’eContentType ({})",
+                    oid_name(&signed),
+                    oid_name(&sd.content_type)
+                ),
+            )
+        }
+        Some(_) => {}
     }
+    let name = oid_name(&si.digest_algorithm);
+    let Some(algorithm) = DigestAlgorithm::from_oid(&si.digest_algorithm) else {
+        return failure(
+            ReportVerdict::Warning,
+            format!("Algorithme d’empreinte non pris en charge ({name})"),
+        );
+    };
+    let Some(expected) = si.message_digest() else {
+        return failure(
+            ReportVerdict::Invalid,
+            "Attribut signé messageDigest absent".into(),
+        );
+    };
+    match digest_of(algorithm) {
+        Some(actual) if actual == expected => None,
+        Some(_) => failure(
+            ReportVerdict::Invalid,
+            format!("messageDigest différent de l’empreinte {name} des données signées"),
+        ),
+        None => failure(
+            ReportVerdict::Warning,
+            format!("Empreinte {name} des données non calculée"),
+        ),
+    }
+}
 
-    let sig_der = if sig.starts_with(b"-----BEGIN") {
-        let s = String::from_utf8(sig.clone()).context("P7S PEM non UTF-8")?;
-        extract_pem_block(&s, "PKCS7")?
-    } else {
-        match B64.decode(&sig) {
-            Ok(der) => der,
-            Err(_) => sig.clone(),
+/// Attributs signés du SignerInfo `si` non conformes au contenu encapsulé
+/// de `sd` : l’intégrité en défaut, et la signature des attributs eux-mêmes.
+/// `None` si les attributs sont conformes ou absents.
+fn encapsulated_attributes_failure(
+    sd: &CmsSignedData,
+    si: &CmsSigner,
+    trust: &TrustStore,
+    at: ValidationTime,
+) -> Option<(Component, Result<Signer>)> {
+    let signed_attrs = si.signed_attrs_der.as_deref()?;
+    let content = sd.content.as_deref();
+    let failure = signed_attributes_failure(sd, si, |a| content.map(|c| a.digest(c)))?;
+    Some((
+        failure,
+        verify_attributes_signature(sd, si, signed_attrs, trust, at),
+    ))
+}
+
+/// Signature du SignerInfo `si` sur ses attributs signés (ou le contenu en
+/// leur absence) et chaîne de son signataire, sans relire le contenu.
+fn verify_attributes_signature(
+    sd: &CmsSignedData,
+    si: &CmsSigner,
+    signed_attrs: &[u8],
+    trust: &TrustStore,
+    at: ValidationTime,
+) -> Result<Signer> {
+    let verified = native_impl::verify_signed_attributes(sd, si, signed_attrs, trust, at);
+    #[cfg(feature = "openssl-backend")]
+    let verified = cross_check_signer(
+        verified,
+        openssl_impl::verify_signed_attributes(sd, si, signed_attrs, trust, at),
+    );
+    verified
+}
+
+/// Vérifie une SignedData encapsulant l’empreinte SHA-1 des données signées
+/// (PDF /SubFilter /adbe.pkcs7.sha1).
+pub fn verify_cms_sha1_digest(
+    sig_der: &[u8],
+    digests: &Digests,
+    trust: &TrustStore,
+) -> Result<Report> {
+    let mut r = Report {
+        input_kind: "CMS".to_string(),
+        document_sha256: digests.get(DigestAlgorithm::Sha256).map(hex::encode),
+        ..Default::default()
+    };
+    let digest = digests
+        .get(DigestAlgorithm::Sha1)
+        .context("Empreinte SHA-1 des données non calculée")?;
+    let sd = CmsSignedData::from_der(sig_der)?;
+    describe(&mut r, &sd);
+    sd.signer().context("SignerInfo absent")?;
+    let ltv = "Non applicable (empreinte SHA-1 encapsulée)";
+    let several = sd.signers.len() > 1;
+    verify_signers(&mut r, &sd, |r, si| {
+        let Some(si) = si else { return };
+        let at = validation_time(r, Some(si), trust);
+        if let Some((failure, verified)) = encapsulated_attributes_failure(&sd, si, trust, at) {
+            match verified {
+                Ok(signer) => accept(
+                    r,
+                    trust.anchors_pem,
+                    signer,
+                    "Signature des attributs signés valide",
+                    "",
+                    ltv,
+                ),
+                Err(e) => refuse(r, "Échec vérif PKCS#7", e),
+            }
+            r.integrity = failure;
+            return;
+        }
+
+        let content = sd.content.as_deref().unwrap_or_default();
+        let verified = if several {
+            // Chaque SignerInfo est vérifié seul, sur ses attributs signés ou
+            // le contenu encapsulé lui-même.
+            let signed = si.signed_attrs_der.as_deref().unwrap_or(content);
+            verify_attributes_signature(&sd, si, signed, trust, at)
+        } else {
+            let verified = native_impl::verify_pkcs7(&sd, si, None, trust, at);
+            #[cfg(feature = "openssl-backend")]
+            let verified = cross_check_signer(
+                verified,
+                openssl_impl::verify_pkcs7(sig_der, None, trust, at),
+            );
+            verified.map(|(_, signer)| signer)
+        };
+        match verified {
+            Ok(signer) if encapsulated_sha1(content) == digest => accept(
+                r,
+                trust.anchors_pem,
+                signer,
+                "PKCS#7 (empreinte SHA-1 encapsulée) valide",
+                "Empreinte SHA-1 encapsulée conforme aux données",
+                ltv,
+            ),
+            Ok(_) => reject(
+                r,
+                "Empreinte SHA-1 encapsulée différente de celle des données signées".into(),
+            ),
+            Err(e) => refuse(r, "Échec vérif PKCS#7", e),
         }
+    });
+
+    final_verdict(&mut r);
+    Ok(r)
+}
+
+/// Vérifie une signature RSA PKCS#1 v1.5 / SHA-1 brute des données signées,
+/// émise par le premier certificat de `certs_der` (PDF /SubFilter
+/// /adbe.x509.rsa_sha1).
+pub fn verify_pkcs1_sha1(
+    sig_value: &[u8],
+    certs_der: &[Vec<u8>],
+    digests: &Digests,
+    trust: &TrustStore,
+) -> Result<Report> {
+    let mut r = Report {
+        input_kind: "PKCS1".to_string(),
+        document_sha256: digests.get(DigestAlgorithm::Sha256).map(hex::encode),
+        ..Default::default()
     };
+    let digest = digests
+        .get(DigestAlgorithm::Sha1)
+        .context("Empreinte SHA-1 des données non calculée")?;
 
-    #[cfg(not(feature = "openssl-backend"))]
-    {
-        let _ = &sig_der;
-        let _ = anchors_pem;
+    // Sans SignerInfo, ni horodatage ni signingTime : chaîne évaluée à
+    // l’instant présent.
+    let at = ValidationTime::Now;
+    let verified = native_impl::verify_rsa_sha1(sig_value, certs_der, digest, trust, at);
+    #[cfg(feature = "openssl-backend")]
+    let verified = cross_check_signer(
+        verified,
+        openssl_impl::verify_rsa_sha1(sig_value, certs_der, digest, trust, at),
+    );
+    match verified {
+        Ok(signer) => accept(
+            &mut r,
+            trust.anchors_pem,
+            signer,
+            "Signature RSA PKCS#1 (SHA-1) valide",
+            "Empreinte SHA-1 des données vérifiée",
+            "Non applicable (signature PKCS#1)",
+        ),
+        Err(e) => refuse(&mut r, "Échec vérif PKCS#1", e),
     }
 
-    // --- Branche OpenSSL ----------------------------------------------------
-    #[cfg(feature = "openssl-backend")]
-    {
-        if let Some(ref dat) = data {
-            match openssl_impl::verify_detached(&sig_der, dat, anchors_pem) {
-                Ok((chain_dns, signer_dn)) => {
-                    r.signature = Component {
-                        status: ReportVerdict::Valid,
-                        detail: "PKCS#7 détaché valide".into(),
-                    };
-                    r.integrity = Component {
-                        status: ReportVerdict::Valid,
-                        detail: "MessageDigest/Data vérifiés".into(),
-                    };
-                    if !anchors_pem.is_empty() {
-                        r.chain = Component {
-                            status: ReportVerdict::Valid,
-                            detail: "Chaîne vérifiée contre les anchors fournis".into(),
-                        };
-                    } else {
-                        r.chain = Component {
-                            status: ReportVerdict::Warning,
-                            detail: "Aucun anchor fourni (--trust), chaîne non attestée".into(),
-                        };
+    final_verdict(&mut r);
+    Ok(r)
+}
+
+/// Vérifie un jeton d’horodatage RFC 3161 portant sur des données connues
+/// par leurs empreintes (horodatage de document PDF /SubFilter /ETSI.RFC3161)
+/// et reporte son genTime.
+pub fn verify_timestamp_token(
+    token_der: &[u8],
+    digests: &Digests,
+    trust: &TrustStore,
+) -> Result<Report> {
+    let mut r = Report {
+        input_kind: "TSP".to_string(),
+        document_sha256: digests.get(DigestAlgorithm::Sha256).map(hex::encode),
+        ..Default::default()
+    };
+
+    let parsed = TimeStampToken::from_der(token_der)
+        .and_then(|token| Ok((token, CmsSignedData::from_der(token_der)?)));
+    let (token, sd) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            reject(&mut r, format!("Jeton d’horodatage illisible: {e:#}"));
+            final_verdict(&mut r);
+            return Ok(r);
+        }
+    };
+    describe(&mut r, &sd);
+    r.timestamp_rfc3161 = Some(token.gen_time.clone());
+    r.signer_dn = token.signer_dn.clone();
+    r.certificate_chain = token.certificates.clone();
+
+    match token.imprint_matches(digests) {
+        None => reject(
+            &mut r,
+            format!(
+                "Algorithme d’empreinte du jeton non pris en charge ({})",
+                token.hash_algorithm
+            ),
+        ),
+        Some(false) => reject(
+            &mut r,
+            "Empreinte du jeton différente de celle des données horodatées".into(),
+        ),
+        Some(true) => {
+            let stamped = format!(
+                "horodatage du {} (politique {}, n° {})",
+                token.gen_time, token.policy, token.serial
+            );
+            let ltv = "Non applicable (jeton d’horodatage)";
+            let several = sd.signers.len() > 1;
+            verify_signers(&mut r, &sd, |r, si| {
+                let Some(si) = si else {
+                    reject(
+                        r,
+                        "Échec vérif jeton d’horodatage: SignerInfo absent".into(),
+                    );
+                    return;
+                };
+                let at = ValidationTime::Now;
+                if let Some((failure, verified)) =
+                    encapsulated_attributes_failure(&sd, si, trust, at)
+                {
+                    match verified {
+                        Ok(signer) => accept(
+                            r,
+                            trust.anchors_pem,
+                            signer,
+                            "Signature des attributs signés du jeton valide",
+                            "",
+                            ltv,
+                        ),
+                        Err(e) => refuse(r, "Échec vérif jeton d’horodatage", e),
                     }
-                    r.signer_dn = signer_dn;
-                    r.certificate_chain = chain_dns;
-
-                    r.revocation = Component {
-                        status: ReportVerdict::Warning,
-                        detail: "Non évaluée (offline par défaut)".into(),
-                    };
-                    r.ltv = Component {
-                        status: ReportVerdict::Warning,
-                        detail: "Non applicable (CMS détaché)".into(),
-                    };
+                    r.integrity = failure;
+                    return;
                 }
-                Err(e) => {
-                    r.signature = Component {
-                        status: ReportVerdict::Invalid,
-                        detail: format!("Échec vérif PKCS#7: {e}"),
-                    };
-                    r.chain = Component {
-                        status: ReportVerdict::Warning,
-                        detail: "Chaîne non évaluée".into(),
-                    };
+
+                let verified = native_impl::verify_token(&sd, si, trust);
+                #[cfg(feature = "openssl-backend")]
+                let verified = match several {
+                    // OpenSSL vérifie tous les SignerInfo d’un coup.
+                    true => verified,
+                    false => {
+                        cross_check_signer(verified, openssl_impl::verify_token(token_der, trust))
+                    }
+                };
+                match verified {
+                    Ok(signer) => {
+                        // Signataire unique : celui que décrit le jeton.
+                        let signer = match several {
+                            true => signer,
+                            false => Signer {
+                                subjects: token.certificates.clone(),
+                                dn: token.signer_dn.clone(),
+                                chain: signer.chain,
+                            },
+                        };
+                        accept(
+                            r,
+                            trust.anchors_pem,
+                            signer,
+                            &format!("Jeton d’horodatage valide : {stamped}"),
+                            "Empreinte du jeton conforme aux données",
+                            ltv,
+                        )
+                    }
+                    Err(e) => refuse(r, "Échec vérif jeton d’horodatage", e),
                 }
+            });
+        }
+    }
+
+    final_verdict(&mut r);
+    Ok(r)
+}
+
+/// Horodatage de signature (signatureTimeStampToken, RFC 3161 annexe A) du
+/// SignerInfo `si`, vérifié comme un jeton portant sur sa valeur de
+/// signature. `None` s’il est absent.
+pub fn signature_timestamp(si: &CmsSigner, trust: &TrustStore) -> Option<Report> {
+    let token_der = si
+        .unsigned_attribute(ID_AA_SIGNATURE_TIMESTAMP)?
+        .to_der()
+        .ok()?;
+    let algorithm = TimeStampToken::from_der(&token_der)
+        .ok()
+        .and_then(|t| t.digest_algorithm());
+    let mut hasher = MultiHasher::new(algorithm.as_slice());
+    hasher.update(&si.signature);
+    verify_timestamp_token(&token_der, &hasher.finish(), trust).ok()
+}
+
+/// Instant d’évaluation de la chaîne du signataire `si` ; le genTime d’un
+/// horodatage de signature valide est reporté.
+fn validation_time(r: &mut Report, si: Option<&CmsSigner>, trust: &TrustStore) -> ValidationTime {
+    let Some(si) = si else {
+        return ValidationTime::Now;
+    };
+    let timestamp = signature_timestamp(si, trust);
+    let at = ValidationTime::of(si, timestamp.as_ref());
+    if let ValidationTime::Attested(_) = at {
+        r.timestamp_rfc3161 = timestamp.and_then(|ts| ts.timestamp_rfc3161);
+    }
+    at
+}
+
+/// Renseigne le rapport à partir de la SignedData décodée : algorithmes,
+/// signataire, certificats embarqués, signingTime et structure.
+fn describe(r: &mut Report, sd: &CmsSignedData) {
+    describe_signer(r, sd, sd.signer());
+    r.certificate_chain = sd
+        .certificates
+        .iter()
+        .map(|c| display_name(&c.tbs_certificate.subject))
+        .collect();
+    r.cms = Some(sd.describe());
+}
+
+/// Algorithmes, signataire et signingTime du SignerInfo `signer`.
+fn describe_signer(r: &mut Report, sd: &CmsSignedData, signer: Option<&CmsSigner>) {
+    r.algorithms = signer
+        .map(|s| {
+            vec![
+                oid_name(&s.digest_algorithm),
+                oid_name(&s.signature_algorithm.oid),
+            ]
+        })
+        .unwrap_or_default();
+    r.signer_dn = signer
+        .and_then(|si| sd.certificate_of(si))
+        .map(|c| display_name(&c.tbs_certificate.subject));
+    r.signing_time = signer
+        .and_then(CmsSigner::signing_time)
+        .and_then(|t| t.format(&Rfc3339).ok());
+}
+
+/// Empreinte SHA-1 encapsulée (adbe.pkcs7.sha1), parfois réencapsulée dans
+/// une OCTET STRING DER.
+fn encapsulated_sha1(content: &[u8]) -> &[u8] {
+    match content {
+        [0x04, 0x14, inner @ ..] if inner.len() == 20 => inner,
+        other => other,
+    }
+}
+
+/// Recoupe la vérification native par OpenSSL : un désaccord (limite d’une
+/// version d’OpenSSL, défaut d’une des piles) rend le résultat indéterminé ;
+/// un algorithme hors du périmètre natif s’en remet à OpenSSL.
+#[cfg(feature = "openssl-backend")]
+fn cross_check<T>(native: Result<T>, openssl: Result<T>) -> Result<T> {
+    match (native, openssl) {
+        (Ok(value), Ok(_)) => Ok(value),
+        (Err(e), openssl) if e.is::<Indeterminate>() => openssl,
+        (Err(e), Err(_)) => Err(e),
+        (Ok(_), Err(e)) => Err(Indeterminate(format!(
+            "signature acceptée nativement mais refusée par OpenSSL: {e:#}"
+        ))
+        .into()),
+        (Err(e), Ok(_)) => Err(Indeterminate(format!(
+            "signature acceptée par OpenSSL mais refusée nativement: {e:#}"
+        ))
+        .into()),
+    }
+}
+
+/// Valeur de signature du `signer` non mise en cause par OpenSSL, puis chaîne
+/// recoupée : une chaîne que seul OpenSSL refuse reste non établie.
+#[cfg(feature = "openssl-backend")]
+fn cross_check_signer<T: Signed>(native: Result<T>, openssl: Result<T>) -> Result<T> {
+    match (native, openssl) {
+        (Ok(mut native), Ok(mut openssl)) => {
+            if let (Ok(()), Err(e)) = (&native.signer().chain, &openssl.signer().chain) {
+                native.signer().chain = Err(format!(
+                    "chaîne acceptée nativement mais refusée par OpenSSL: {e}"
+                ));
             }
-        } else {
-            r.signature = Component {
-                status: ReportVerdict::Warning,
-                detail: "P7M enveloppé non implémenté dans MVP".into(),
-            };
+            Ok(native)
         }
-        final_verdict(&mut r);
-        Ok(r)
+        (native, openssl) => cross_check(native, openssl),
     }
+}
 
-    // --- Branche SANS OpenSSL -----------------------------------------------
-    #[cfg(not(feature = "openssl-backend"))]
-    {
-        let _ = &sig_der; // éviter unused
-        let _ = anchors_pem;
+/// Résultat de vérification portant un [`Signer`].
+#[cfg(feature = "openssl-backend")]
+trait Signed {
+    fn signer(&mut self) -> &mut Signer;
+}
 
-        r.signature = Component {
-        status: ReportVerdict::Warning,
-        detail: "Pile native CMS non activée : utilisez --features openssl-backend pour une vérification cryptographique complète.".into(),
+#[cfg(feature = "openssl-backend")]
+impl Signed for Signer {
+    fn signer(&mut self) -> &mut Signer {
+        self
+    }
+}
+
+#[cfg(feature = "openssl-backend")]
+impl<C> Signed for (C, Signer) {
+    fn signer(&mut self) -> &mut Signer {
+        &mut self.1
+    }
+}
+
+/// Composant LTV d’une signature CMS détachée.
+const LTV_DETACHED: &str = "Non applicable (CMS détaché)";
+
+/// Signature et intégrité établies ; la chaîne du `signer` est reportée à
+/// part, WARNING si elle n’a pu être établie. `ltv` qualifie la forme de
+/// signature, la LTV n’étant évaluée que pour PAdES.
+fn accept(
+    r: &mut Report,
+    anchors_pem: &[String],
+    signer: Signer,
+    signature: &str,
+    integrity: &str,
+    ltv: &str,
+) {
+    r.signature = Component {
+        status: ReportVerdict::Valid,
+        detail: signature.into(),
     };
-        r.chain = Component {
+    r.integrity = Component {
+        status: ReportVerdict::Valid,
+        detail: integrity.into(),
+    };
+    r.chain = match signer.chain {
+        _ if anchors_pem.is_empty() => Component {
             status: ReportVerdict::Warning,
-            detail: "Validation de chaîne limitée sans backend X.509 avancé.".into(),
-        };
-        r.revocation = Component {
+            detail: "Aucun anchor fourni (--trust), chaîne non attestée".into(),
+        },
+        Err(e) => Component {
+            status: ReportVerdict::Warning,
+            detail: format!("Chaîne non établie : {e}"),
+        },
+        Ok(()) => Component {
+            status: ReportVerdict::Valid,
+            detail: "Chaîne vérifiée contre les anchors fournis".into(),
+        },
+    };
+    r.signer_dn = signer.dn;
+    r.certificate_chain = signer.subjects;
+
+    r.revocation = Component {
         status: ReportVerdict::Warning,
-        detail: "Révocation offline non évaluée (MVP). Fournir CRL/OCSP et activer backend ultérieurement.".into(),
+        detail: "Non évaluée (offline par défaut)".into(),
     };
-        r.ltv = Component {
-            status: ReportVerdict::Warning,
-            detail: "".into(),
-        };
+    r.ltv = Component {
+        status: ReportVerdict::Warning,
+        detail: ltv.into(),
+    };
+}
 
-        final_verdict(&mut r);
-        Ok(r)
+/// Échec de vérification : INVALID, ou WARNING quand la vérification n’a
+/// pu conclure.
+fn refuse(r: &mut Report, context: &str, e: anyhow::Error) {
+    reject(r, format!("{context}: {e:#}"));
+    if e.is::<Indeterminate>() {
+        r.signature.status = ReportVerdict::Warning;
     }
 }
 
+fn reject(r: &mut Report, detail: String) {
+    r.signature = Component {
+        status: ReportVerdict::Invalid,
+        detail,
+    };
+    r.chain = Component {
+        status: ReportVerdict::Warning,
+        detail: "Chaîne non évaluée".into(),
+    };
+}
+
 fn extract_pem_block(pem: &str, _label: &str) -> Result<Vec<u8>> {
     let start = pem
         .find("-----BEGIN")
@@ -2,17 +2,31 @@ use anyhow::{bail, Context, Result};
 use std::fs;
 use std::path::PathBuf;
 
+/// Garde-fous DoS : taille max des fichiers acceptés, et budget mémoire au-delà
+/// duquel un fichier n’est plus chargé en entier (les PDF sont alors lus en flux).
 #[derive(Clone, Copy, Debug)]
 pub struct Limits {
     pub max_bytes: u64,
+    pub max_memory: u64,
 }
 
 impl Limits {
-    pub fn from_mib(mib: u64) -> Self {
+    pub fn from_mib(file_mib: u64, memory_mib: u64) -> Self {
         Self {
-            max_bytes: mib * 1024 * 1024,
+            max_bytes: file_mib.saturating_mul(1024 * 1024),
+            max_memory: memory_mib.saturating_mul(1024 * 1024),
         }
     }
+
+    /// Un fichier de `len` octets peut-il être chargé en mémoire ?
+    pub fn fits_in_memory(&self, len: u64) -> bool {
+        len <= self.max_memory
+    }
+
+    /// Budget mémoire en octets, pour la lecture en flux.
+    pub fn memory_budget(&self) -> usize {
+        usize::try_from(self.max_memory).unwrap_or(usize::MAX)
+    }
 }
 
 fn normalize(path: &str) -> Result<PathBuf> {
@@ -27,16 +41,40 @@ fn normalize(path: &str) -> Result<PathBuf> {
     Ok(abs)
 }
 
+/// Lit un fichier en entier : sa taille est bornée par la taille max et par
+/// le budget mémoire.
 pub fn read_file_bounded(path: &str, limits: &Limits) -> Result<Vec<u8>> {
     let p = normalize(path)?;
     let md = fs::metadata(&p).with_context(|| format!("Stat échouée: {}", p.display()))?;
     if md.len() > limits.max_bytes {
         bail!("Fichier trop volumineux ({} bytes > limite)", md.len());
     }
+    if !limits.fits_in_memory(md.len()) {
+        bail!(
+            "Fichier trop volumineux pour le budget mémoire ({} bytes > {} bytes)",
+            md.len(),
+            limits.max_memory
+        );
+    }
     let data = fs::read(&p).with_context(|| format!("Lecture échouée: {}", p.display()))?;
     Ok(data)
 }
 
+/// Ouvre un fichier sans le lire, après contrôle de la taille max seule : la
+/// lecture à la demande reste dans le budget mémoire quelle que soit la taille.
+pub fn open_file_bounded(path: &str, limits: &Limits) -> Result<fs::File> {
+    let p = normalize(path)?;
+    let file = fs::File::open(&p).with_context(|| format!("Ouverture échouée: {}", p.display()))?;
+    let len = file
+        .metadata()
+        .with_context(|| format!("Stat échouée: {}", p.display()))?
+        .len();
+    if len > limits.max_bytes {
+        bail!("Fichier trop volumineux ({len} bytes > limite)");
+    }
+    Ok(file)
+}
+
 pub fn read_all_pems(paths: &[String], limits: &Limits) -> Result<Vec<String>> {
     let mut res = Vec::new();
     for p in paths {
@@ -10,6 +10,7 @@ mod x509;
 use anyhow::{Context, Result};
 use clap::{ArgAction, Parser};
 use report::{ExitCode, ReportVerdict};
+use std::io::Write;
 use tracing::{debug, info};
 use tracing_subscriber::{fmt, EnvFilter};
 
@@ -36,6 +37,14 @@ struct Cli {
     #[arg(long = "trust", value_name = "PEM", num_args = 1.., action = ArgAction::Append)]
     trust: Vec<String>,
 
+    /// Écrire le contenu signé d’un P7M enveloppé dans FILE
+    #[arg(long, value_name = "FILE", requires = "sig", conflicts_with = "data")]
+    extract_content: Option<String>,
+
+    /// Écrire le contenu extrait même si le verdict est INVALID
+    #[arg(long, action = ArgAction::SetTrue, requires = "extract_content")]
+    force: bool,
+
     /// Fichiers CRL hors-ligne (optionnels)
     #[arg(long, value_name = "CRL", num_args = 0.., action = ArgAction::Append)]
     crl: Vec<String>,
@@ -48,6 +57,35 @@ struct Cli {
     #[arg(long, value_name = "FILE")]
     out: Option<String>,
 
+    /// Extraire la révision couverte par la signature n° N (ordre du rapport) au lieu de vérifier
+    #[arg(long, value_name = "N", requires_all = ["in", "out_pdf"])]
+    extract_revision: Option<usize>,
+
+    /// Fichier PDF de sortie pour --extract-revision
+    #[arg(long, value_name = "FILE", requires = "extract_revision")]
+    out_pdf: Option<String>,
+
+    /// Écart toléré (secondes) entre /M et l’heure signée ou l’horodatage
+    #[arg(long, value_name = "SECONDS", default_value_t = 300)]
+    time_tolerance: u32,
+
+    /// Effet des champs de signature vides sur le verdict (toujours listés dans le rapport)
+    #[arg(long, value_name = "POLICY", value_enum, default_value_t = pdf::pades::UnsignedFieldPolicy::Ignore)]
+    unsigned_fields: pdf::pades::UnsignedFieldPolicy,
+
+    /// Vérifier aussi les pièces jointes du PDF (PDF signés, P7S), récursivement
+    #[arg(long, action = ArgAction::SetTrue, requires = "in")]
+    embedded: bool,
+
+    /// Mot de passe d’un PDF chiffré (utilisateur ou propriétaire). Vide par défaut
+    #[arg(
+        long,
+        value_name = "PASSWORD",
+        default_value = "",
+        hide_default_value = true
+    )]
+    pdf_password: Password,
+
     /// Activer les requêtes réseau (OCSP/CRL). Désactivé par défaut.
     #[arg(long, action = ArgAction::SetTrue)]
     online: bool,
@@ -56,9 +94,33 @@ struct Cli {
     #[arg(long, value_name = "LEVEL")]
     log_level: Option<String>,
 
-    /// Taille max des fichiers en MiB (défense DoS)
-    #[arg(long, default_value_t = 50)]
+    /// Taille max des fichiers en MiB (défense DoS). Les fichiers lus en entier
+    /// (--sig, --data, --trust, --crl, --ocsp) sont en outre bornés par
+    /// --max-memory-mib
+    #[arg(long, default_value_t = 4096)]
     max_mib: u64,
+
+    /// Budget mémoire en MiB : un PDF plus gros est vérifié en flux depuis le disque
+    #[arg(long, value_name = "MIB", default_value_t = 50)]
+    max_memory_mib: u64,
+}
+
+/// Mot de passe, masqué dans les traces (`debug!(?cli)`).
+#[derive(Clone)]
+struct Password(String);
+
+impl std::str::FromStr for Password {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Password(s.to_owned()))
+    }
+}
+
+impl std::fmt::Debug for Password {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(if self.0.is_empty() { "\"\"" } else { "***" })
+    }
 }
 
 fn init_tracing(level: Option<String>) {
@@ -77,18 +139,53 @@ fn main() -> Result<()> {
     info!("notar-verify démarré (offline par défaut)");
     debug!(?cli);
 
-    let limits = infra::fs::Limits::from_mib(cli.max_mib);
+    let limits = infra::fs::Limits::from_mib(cli.max_mib, cli.max_memory_mib);
+    let options = pdf::pades::PdfOptions {
+        time_tolerance: time::Duration::seconds(i64::from(cli.time_tolerance)),
+        password: cli.pdf_password.0.clone(),
+        unsigned_fields: cli.unsigned_fields,
+        embedded: cli.embedded,
+    };
+
+    // Extraction de la version signée : aucun rapport n’est produit
+    if let (Some(n), Some(pdf_path), Some(out_pdf)) = (
+        cli.extract_revision,
+        cli.r#in.as_deref(),
+        cli.out_pdf.as_deref(),
+    ) {
+        let mut out = std::io::BufWriter::new(
+            std::fs::File::create(out_pdf).context("Écriture --out-pdf a échoué")?,
+        );
+        let written = pdf::pades::extract_signed_revision(pdf_path, n, &limits, &options, &mut out)
+            .and_then(|n| Ok(out.flush().map(|_| n)?));
+        let written = match written {
+            Ok(n) => n,
+            Err(e) => {
+                drop(out);
+                let _ = std::fs::remove_file(out_pdf);
+                return Err(e).context("Extraction de la révision signée a échoué");
+            }
+        };
+        info!(
+            "Révision signée n°{} écrite dans {} ({} octets)",
+            n, out_pdf, written
+        );
+        return Ok(());
+    }
 
     // Charger anchors (si fournis)
     let anchors = infra::fs::read_all_pems(&cli.trust, &limits)
         .context("Échec lecture des anchors (--trust)")?;
 
     // Dispatcher selon mode
+    let mut refused_extraction = None;
     let report = if let Some(pdf_path) = cli.r#in.as_deref() {
-        pdf::pades::verify_pdf_pades(pdf_path, &anchors, &cli.crl, &cli.ocsp, cli.online, &limits)
-            .context("Vérification PAdES a échoué")?
+        pdf::pades::verify_pdf_pades(
+            pdf_path, &anchors, &cli.crl, &cli.ocsp, cli.online, &limits, &options,
+        )
+        .context("Vérification PAdES a échoué")?
     } else if let Some(sig_path) = cli.sig.as_deref() {
-        cms::verify::verify_cms_entrypoint(
+        let report = cms::verify::verify_cms_entrypoint(
             sig_path,
             cli.data.as_deref(),
             &anchors,
@@ -97,7 +194,21 @@ fn main() -> Result<()> {
             cli.online,
             &limits,
         )
-        .context("Vérification CMS a échoué")?
+        .context("Vérification CMS a échoué")?;
+        // Un contenu altéré n’est désenveloppé que sur demande explicite
+        if let Some(out) = cli.extract_content.as_deref() {
+            if report.verdict == ReportVerdict::Invalid && !cli.force {
+                refused_extraction = Some(out);
+            } else {
+                let written = cms::verify::extract_content(sig_path, out, &limits)
+                    .context("Extraction du contenu signé a échoué")?;
+                info!(
+                    "Contenu signé écrit dans {} ({} octets, verdict {:?})",
+                    out, written, report.verdict
+                );
+            }
+        }
+        report
     } else {
         anyhow::bail!(
             "Spécifiez --in <pdf> ou --sig <p7s|p7m> (avec --data si détachée). Voir --help."
@@ -113,6 +224,13 @@ fn main() -> Result<()> {
         info!("Rapport JSON écrit dans {}", out);
     }
 
+    if let Some(out) = refused_extraction {
+        anyhow::bail!(
+            "Contenu signé non écrit dans {out} : verdict {:?} (--force pour l’écrire malgré tout)",
+            report.verdict
+        );
+    }
+
     // Codes de sortie
     std::process::exit(match report.verdict {
         ReportVerdict::Valid => ExitCode::Valid as i32,
@@ -0,0 +1,145 @@
+use crate::cms::signed_data::CmsSignedData;
+use crate::cms::verify::{signature_timestamp, TrustStore};
+use crate::pdf::subfilter::SubFilter;
+use crate::report::{PadesConformance, PadesLevel, ReportVerdict, SignatureReport};
+use der::asn1::ObjectIdentifier;
+
+/// id-aa-signingCertificateV2 (RFC 5035).
+const ID_AA_SIGNING_CERTIFICATE_V2: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.2.47");
+
+/// Classe chaque signature (hors horodatages de document) selon les niveaux
+/// PAdES baseline. `contents[i]` est le /Contents de `sigs[i]` ; `dss_offset`
+/// situe le DSS en vigueur dans le fichier ; les horodatages de signature
+/// sont vérifiés contre `trust`.
+pub fn classify_baseline(
+    sigs: &mut [SignatureReport],
+    contents: &[Vec<u8>],
+    dss_offset: Option<usize>,
+    trust: &TrustStore,
+) {
+    // Couverture des horodatages de document non invalides.
+    let timestamps: Vec<usize> = sigs
+        .iter()
+        .filter(|s| is_document_timestamp(s))
+        .filter(|s| {
+            s.integrity.status != ReportVerdict::Invalid
+                && s.signature.status != ReportVerdict::Invalid
+        })
+        .filter_map(covered_end)
+        .collect();
+
+    for (sig, contents) in sigs.iter_mut().zip(contents) {
+        if !is_document_timestamp(sig) {
+            sig.pades = Some(assess_baseline(
+                sig,
+                contents,
+                &timestamps,
+                dss_offset,
+                trust,
+            ));
+        }
+    }
+}
+
+fn assess_baseline(
+    sig: &SignatureReport,
+    contents: &[u8],
+    timestamps: &[usize],
+    dss_offset: Option<usize>,
+    trust: &TrustStore,
+) -> PadesConformance {
+    let sd = CmsSignedData::from_der(contents).ok();
+    let si = sd.as_ref().and_then(CmsSignedData::signer);
+    let end = covered_end(sig).unwrap_or(usize::MAX);
+    let mut unmet: Vec<(PadesLevel, String)> = Vec::new();
+
+    // B-B : encodage CAdES, certificat du signataire protégé, signature intacte.
+    if sig.sub_filter.as_deref() != SubFilter::CadesDetached.name() {
+        unmet.push((
+            PadesLevel::BB,
+            format!(
+                "/SubFilter ETSI.CAdES.detached requis ({})",
+                sig.sub_filter
+                    .as_deref()
+                    .map_or("absent".to_string(), |n| format!("/{n}"))
+            ),
+        ));
+    }
+    if !si.is_some_and(|si| si.has_signed_attribute(ID_AA_SIGNING_CERTIFICATE_V2)) {
+        unmet.push((
+            PadesLevel::BB,
+            "attribut signé ESS signing-certificate-v2 absent".into(),
+        ));
+    }
+    if sig.signature.status == ReportVerdict::Invalid
+        || sig.integrity.status == ReportVerdict::Invalid
+    {
+        unmet.push((PadesLevel::BB, "signature ou intégrité invalide".into()));
+    }
+
+    // B-T : horodatage de la signature (jeton vérifié, empreinte de la
+    // valeur de signature), ou horodatage de document postérieur.
+    let stamped = si.and_then(|si| signature_timestamp(si, trust));
+    let stamp_failure = stamped.as_ref().and_then(|ts| {
+        [&ts.signature, &ts.integrity]
+            .into_iter()
+            .find(|c| c.status == ReportVerdict::Invalid)
+            .map(|c| c.detail.clone())
+    });
+    if !timestamps.iter().any(|&ts| ts > end) {
+        match (&stamped, stamp_failure) {
+            (None, _) => unmet.push((
+                PadesLevel::BT,
+                "ni horodatage de signature ni horodatage de document postérieur".into(),
+            )),
+            (Some(_), Some(failure)) => unmet.push((
+                PadesLevel::BT,
+                format!("horodatage de signature non valide ({failure})"),
+            )),
+            (Some(_), None) => {}
+        }
+    }
+
+    // B-LT : chaîne et révocation embarquées (DSS/VRI).
+    if sig.ltv.status != ReportVerdict::Valid {
+        unmet.push((PadesLevel::BLt, sig.ltv.detail.clone()));
+    }
+
+    // B-LTA : horodatage de document couvrant le DSS.
+    let dss_covered = dss_offset.is_some_and(|dss| timestamps.iter().any(|&ts| ts > dss));
+    if !dss_covered {
+        unmet.push((
+            PadesLevel::BLta,
+            "aucun horodatage de document ne couvre le DSS".into(),
+        ));
+    }
+
+    let first_unmet = unmet.iter().map(|(level, _)| *level).min();
+    let level = [
+        PadesLevel::BB,
+        PadesLevel::BT,
+        PadesLevel::BLt,
+        PadesLevel::BLta,
+    ]
+    .into_iter()
+    .take_while(|l| first_unmet.map_or(true, |unmet| *l < unmet))
+    .last();
+    PadesConformance {
+        level,
+        missing: unmet
+            .into_iter()
+            .map(|(level, what)| format!("{level} : {what}"))
+            .collect(),
+    }
+}
+
+fn is_document_timestamp(sig: &SignatureReport) -> bool {
+    sig.sub_filter.as_deref() == SubFilter::Rfc3161.name()
+}
+
+fn covered_end(sig: &SignatureReport) -> Option<usize> {
+    sig.byte_range
+        .last()
+        .map(|(off, len)| off.saturating_add(*len))
+}
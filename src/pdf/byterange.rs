@@ -0,0 +1,185 @@
+use crate::cms::digest::{DigestAlgorithm, Digests, MultiHasher};
+use crate::pdf::bytes::trim_end_ws;
+use crate::pdf::source::ByteSource;
+use anyhow::Result;
+use lopdf::Object;
+
+/// Octets examinés avant le trou /Contents et la fin de couverture.
+const LOOKBEHIND: usize = 64;
+
+/// Anomalie de couverture /ByteRange. Chaque variante est une raison distincte
+/// reportée dans le composant `integrity`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ByteRangeIssue {
+    #[error("ByteRange n’est pas un tableau")]
+    NotArray,
+    #[error("ByteRange : élément {0} non entier ou négatif")]
+    NotInteger(usize),
+    #[error("ByteRange : nombre impair d’éléments ({0})")]
+    OddLength(usize),
+    #[error("ByteRange : {0} plage(s) au lieu de 2")]
+    RangeCount(usize),
+    #[error("ByteRange ne commence pas à l’offset 0 (début à {0})")]
+    NotAtStart(usize),
+    #[error("ByteRange : plages non croissantes ou chevauchantes")]
+    Overlap,
+    #[error("ByteRange hors limites du fichier")]
+    OutOfBounds,
+    #[error("ByteRange : le trou [{0}, {1}) ne correspond pas exactement à /Contents <…>")]
+    GapMismatch(usize, usize),
+    #[error("ByteRange : la couverture se termine à {0}, pas à la fin d’une révision (%%EOF)")]
+    NotRevisionEnd(usize),
+}
+
+/// Lit un /ByteRange en paires (offset, longueur), sans tolérance : tout
+/// élément non entier ou négatif est une erreur.
+pub fn parse_byterange(obj: &Object) -> Result<Vec<(usize, usize)>, ByteRangeIssue> {
+    let Object::Array(arr) = obj else {
+        return Err(ByteRangeIssue::NotArray);
+    };
+    if arr.len() % 2 != 0 {
+        return Err(ByteRangeIssue::OddLength(arr.len()));
+    }
+    let mut values = Vec::with_capacity(arr.len());
+    for (i, o) in arr.iter().enumerate() {
+        let v = o
+            .as_i64()
+            .ok()
+            .and_then(|v| usize::try_from(v).ok())
+            .ok_or(ByteRangeIssue::NotInteger(i))?;
+        values.push(v);
+    }
+    Ok(values.chunks(2).map(|p| (p[0], p[1])).collect())
+}
+
+/// Contrôle strict de la couverture : début à 0, exactement deux plages
+/// croissantes, trou égal à la chaîne hex /Contents (délimiteurs compris),
+/// fin de couverture sur la fin d’une révision.
+pub fn validate_byterange(
+    pdf: &(impl ByteSource + ?Sized),
+    ranges: &[(usize, usize)],
+    contents: &[u8],
+) -> Result<Vec<ByteRangeIssue>> {
+    let mut issues = Vec::new();
+
+    if ranges
+        .iter()
+        .any(|(off, len)| off.checked_add(*len).map_or(true, |end| end > pdf.len()))
+    {
+        issues.push(ByteRangeIssue::OutOfBounds);
+    }
+    if let Some((start, _)) = ranges.first() {
+        if *start != 0 {
+            issues.push(ByteRangeIssue::NotAtStart(*start));
+        }
+    }
+    if ranges.len() != 2 {
+        issues.push(ByteRangeIssue::RangeCount(ranges.len()));
+    }
+    if ranges
+        .windows(2)
+        .any(|w| w[0].0.saturating_add(w[0].1) > w[1].0)
+    {
+        issues.push(ByteRangeIssue::Overlap);
+    }
+    if !issues.is_empty() {
+        // Les contrôles suivants supposent deux plages cohérentes.
+        return Ok(issues);
+    }
+
+    let gap = (ranges[0].0 + ranges[0].1, ranges[1].0);
+    if !gap_is_contents(pdf, gap, contents)? {
+        issues.push(ByteRangeIssue::GapMismatch(gap.0, gap.1));
+    }
+    let end = ranges[1].0 + ranges[1].1;
+    if !ends_on_eof(&pdf.read(end.saturating_sub(LOOKBEHIND), end)?) {
+        issues.push(ByteRangeIssue::NotRevisionEnd(end));
+    }
+    Ok(issues)
+}
+
+/// Recompose les octets couverts par le ByteRange.
+pub fn concat_ranges(pdf: &[u8], ranges: &[(usize, usize)]) -> Result<Vec<u8>, ByteRangeIssue> {
+    let mut signed = Vec::new();
+    for (off, len) in ranges {
+        let end = off.saturating_add(*len);
+        if end > pdf.len() {
+            return Err(ByteRangeIssue::OutOfBounds);
+        }
+        signed.extend_from_slice(&pdf[*off..end]);
+    }
+    Ok(signed)
+}
+
+/// Empreintes des octets couverts, lus plage par plage (sans les recomposer).
+pub fn digest_ranges(
+    pdf: &(impl ByteSource + ?Sized),
+    ranges: &[(usize, usize)],
+    algorithms: &[DigestAlgorithm],
+) -> Result<Digests> {
+    let mut hasher = MultiHasher::new(algorithms);
+    for (off, len) in ranges {
+        let end = off.saturating_add(*len);
+        if end > pdf.len() {
+            return Err(ByteRangeIssue::OutOfBounds.into());
+        }
+        pdf.for_each_chunk(*off, end, &mut |chunk| {
+            hasher.update(chunk);
+            true
+        })?;
+    }
+    Ok(hasher.finish())
+}
+
+/// Le trou doit être exactement `<hex>` précédé de la clé /Contents, et le
+/// hex décodé doit égaler la valeur /Contents du dictionnaire.
+fn gap_is_contents(
+    pdf: &(impl ByteSource + ?Sized),
+    (start, end): (usize, usize),
+    contents: &[u8],
+) -> Result<bool> {
+    if end < start + 2 {
+        return Ok(false);
+    }
+    let gap = pdf.read(start, end)?;
+    if gap.first() != Some(&b'<') || gap.last() != Some(&b'>') {
+        return Ok(false);
+    }
+    let head = pdf.read(start.saturating_sub(LOOKBEHIND), start)?;
+    if !trim_end_ws(&head).ends_with(b"/Contents") {
+        return Ok(false);
+    }
+    Ok(match decode_hex(&gap[1..gap.len() - 1]) {
+        Some(decoded) => decoded == contents,
+        None => false,
+    })
+}
+
+/// Décode une chaîne hexadécimale PDF (blancs ignorés, dernier quartet
+/// implicite à 0 si impair). `None` si un octet n’est pas hexadécimal.
+pub fn decode_hex(raw: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(raw.len() / 2);
+    let mut hi: Option<u8> = None;
+    for &b in raw {
+        if b.is_ascii_whitespace() {
+            continue;
+        }
+        let v = (b as char).to_digit(16)? as u8;
+        match hi.take() {
+            Some(h) => out.push(h << 4 | v),
+            None => hi = Some(v),
+        }
+    }
+    if let Some(h) = hi {
+        out.push(h << 4);
+    }
+    Some(out)
+}
+
+fn ends_on_eof(covered: &[u8]) -> bool {
+    let mut trimmed = covered;
+    while let [rest @ .., b'\r' | b'\n'] = trimmed {
+        trimmed = rest;
+    }
+    trimmed.ends_with(b"%%EOF")
+}
@@ -0,0 +1,20 @@
+//! Petits utilitaires de recherche sur les octets bruts du PDF.
+
+/// Première occurrence de `needle` dans `hay`.
+pub fn find(hay: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || hay.len() < needle.len() {
+        return None;
+    }
+    hay.windows(needle.len()).position(|w| w == needle)
+}
+
+/// Retire les blancs PDF de fin.
+pub fn trim_end_ws(mut s: &[u8]) -> &[u8] {
+    while let [rest @ .., last] = s {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        s = rest;
+    }
+    s
+}
@@ -0,0 +1,52 @@
+//! Occupation de /Contents : la CMS (ou le jeton) n’occupe que le début de la
+//! réserve, le reste doit être du bourrage nul. Des octets non nuls après
+//! l’objet DER ne sont couverts par rien et peuvent dissimuler des données.
+
+use crate::cms::ber::encoded_len;
+use crate::report::{Component, ContentsUsage, ReportVerdict};
+
+/// Mesure l’objet DER/BER en tête de `contents` et contrôle le bourrage.
+pub fn inspect_contents(contents: &[u8]) -> (ContentsUsage, Component) {
+    let reserved = contents.len();
+    let Some(used) = encoded_len(contents) else {
+        return (
+            ContentsUsage {
+                reserved,
+                used: None,
+                trailing_nonzero: 0,
+            },
+            Component {
+                status: ReportVerdict::Warning,
+                detail: format!(
+                    "Longueur de la structure signée illisible dans /Contents ({reserved} octets) : \
+                     bourrage non contrôlé"
+                ),
+            },
+        );
+    };
+    let trailing = &contents[used..];
+    let trailing_nonzero = trailing.iter().filter(|b| **b != 0).count();
+    let usage = ContentsUsage {
+        reserved,
+        used: Some(used),
+        trailing_nonzero,
+    };
+    let share = used * 100 / reserved.max(1);
+    let component = match trailing.iter().position(|b| *b != 0) {
+        Some(first) => Component {
+            status: ReportVerdict::Warning,
+            detail: format!(
+                "{trailing_nonzero} octet(s) non nul(s) après la structure signée dans /Contents \
+                 (à partir de l’octet {}) : données dissimulées possibles",
+                used + first
+            ),
+        },
+        None => Component {
+            status: ReportVerdict::Valid,
+            detail: format!(
+                "Structure signée de {used} octets sur {reserved} réservés ({share} %), bourrage nul"
+            ),
+        },
+    };
+    (usage, component)
+}
@@ -0,0 +1,475 @@
+use anyhow::{bail, Result};
+use lopdf::xref::XrefEntry;
+use lopdf::{Dictionary, Document, Object, ObjectId, ObjectStream, Reader};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::collections::BTreeSet;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CryptErr {
+    #[error("Mot de passe PDF incorrect (--pdf-password)")]
+    Password,
+    #[error("Chiffrement PDF non pris en charge : {0}")]
+    Unsupported(String),
+    #[error("Dictionnaire /Encrypt invalide : {0}")]
+    Encrypt(&'static str),
+}
+
+/// Bourrage des mots de passe (ISO 32000-2 §7.6.4.3.2, algorithme 2).
+const PAD: [u8; 32] = [
+    0x28, 0xBF, 0x4E, 0x5E, 0x4E, 0x75, 0x8A, 0x41, 0x64, 0x00, 0x4E, 0x56, 0xFF, 0xFA, 0x01, 0x08,
+    0x2E, 0x2E, 0x00, 0xB6, 0xD0, 0x68, 0x3E, 0x80, 0x2F, 0x0C, 0xA9, 0xFE, 0x64, 0x53, 0x69, 0x7A,
+];
+
+/// Méthode d’un filtre de chiffrement (/CFM).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cfm {
+    Identity,
+    Rc4,
+    AesV2,
+    AesV3,
+}
+
+/// Gestionnaire de sécurité standard ouvert avec un mot de passe valide.
+struct Handler {
+    key: Vec<u8>,
+    strings: Cfm,
+    streams: Cfm,
+    encrypt_metadata: bool,
+}
+
+/// Déchiffre en place les chaînes et flux d’un PDF protégé par le gestionnaire
+/// standard, avec le mot de passe utilisateur ou propriétaire `password`.
+///
+/// Le /Contents des dictionnaires de signature n’est jamais chiffré (ISO
+/// 32000-2 §7.6.2) et reste intact ; `bytes` (le fichier tel quel, haché sur
+/// le ByteRange) sert seulement à relire les flux d’objets chiffrés que lopdf
+/// n’a pas pu déplier au chargement.
+pub fn decrypt_document(doc: &mut Document, bytes: &[u8], password: &[u8]) -> Result<()> {
+    let Ok(encrypt_ref) = doc.trailer.get(b"Encrypt") else {
+        return Ok(());
+    };
+    let encrypt_id = encrypt_ref.as_reference().ok();
+    let encrypt = match encrypt_ref {
+        Object::Reference(id) => doc.get_dictionary(*id)?.clone(),
+        Object::Dictionary(d) => d.clone(),
+        _ => bail!(CryptErr::Encrypt("/Encrypt n’est pas un dictionnaire")),
+    };
+    let id0 = doc
+        .trailer
+        .get(b"ID")
+        .and_then(Object::as_array)
+        .ok()
+        .and_then(|ids| ids.first()?.as_str().ok())
+        .unwrap_or_default()
+        .to_vec();
+    let handler = Handler::open(&encrypt, &id0, password)?;
+
+    for (&id, obj) in doc.objects.iter_mut() {
+        if Some(id) != encrypt_id {
+            handler.decrypt_object(id, obj)?;
+        }
+    }
+
+    // Flux d’objets écartés par lopdf (contenu chiffré illisible au
+    // chargement) : relus depuis le fichier, déchiffrés puis dépliés. Les
+    // objets qu’ils portent ne sont pas chiffrés individuellement.
+    let containers: BTreeSet<u32> = doc
+        .reference_table
+        .entries
+        .values()
+        .filter_map(|e| match e {
+            XrefEntry::Compressed { container, .. } => Some(*container),
+            _ => None,
+        })
+        .collect();
+    let mut table = Document::new();
+    table.reference_table = doc.reference_table.clone();
+    let reader = Reader {
+        buffer: bytes,
+        document: table,
+    };
+    for container in containers {
+        let Some(&XrefEntry::Normal { generation, .. }) = doc.reference_table.get(container) else {
+            continue;
+        };
+        let id = (container, generation);
+        if doc.objects.contains_key(&id) {
+            continue;
+        }
+        let Ok(Object::Stream(mut stream)) = reader.get_object(id) else {
+            continue;
+        };
+        let plain = handler.decrypt(id, handler.streams, &stream.content)?;
+        stream.set_content(plain);
+        let Ok(objects) = ObjectStream::new(&mut stream.clone()) else {
+            continue;
+        };
+        for (oid, obj) in objects.objects {
+            let current = matches!(
+                doc.reference_table.get(oid.0),
+                Some(XrefEntry::Compressed { container: c, .. }) if *c == container
+            );
+            if current {
+                doc.objects.entry(oid).or_insert(obj);
+            }
+        }
+        doc.objects.insert(id, Object::Stream(stream));
+    }
+
+    doc.trailer.remove(b"Encrypt");
+    Ok(())
+}
+
+impl Handler {
+    fn open(encrypt: &Dictionary, id0: &[u8], password: &[u8]) -> Result<Handler> {
+        let int = |key: &[u8]| encrypt.get(key).and_then(Object::as_i64).ok();
+        let bytes = |key: &'static [u8]| -> Result<&[u8]> {
+            encrypt
+                .get(key)
+                .and_then(Object::as_str)
+                .map_err(|_| CryptErr::Encrypt("/O, /U, /OE ou /UE absent").into())
+        };
+        match encrypt.get(b"Filter").and_then(Object::as_name) {
+            Ok(b"Standard") => {}
+            Ok(other) => bail!(CryptErr::Unsupported(format!(
+                "gestionnaire de sécurité /{}",
+                String::from_utf8_lossy(other)
+            ))),
+            Err(_) => bail!(CryptErr::Encrypt("/Filter absent")),
+        }
+        let v = int(b"V").unwrap_or(0);
+        let r = int(b"R").ok_or(CryptErr::Encrypt("/R absent"))?;
+        let encrypt_metadata = encrypt
+            .get(b"EncryptMetadata")
+            .and_then(Object::as_bool)
+            .unwrap_or(true);
+
+        let (strings, streams) = match v {
+            1 | 2 => (Cfm::Rc4, Cfm::Rc4),
+            4 | 5 => (
+                crypt_filter(encrypt, b"StrF")?,
+                crypt_filter(encrypt, b"StmF")?,
+            ),
+            _ => bail!(CryptErr::Unsupported(format!("/V {v}"))),
+        };
+        let o = bytes(b"O")?;
+        let u = bytes(b"U")?;
+        let key = match r {
+            2..=4 => {
+                let len = match (r, int(b"Length")) {
+                    (2, _) => 5,
+                    (_, Some(bits)) if (40..=128).contains(&bits) && bits % 8 == 0 => {
+                        usize::try_from(bits / 8)?
+                    }
+                    (_, None) => 5,
+                    (_, Some(bits)) => bail!(CryptErr::Unsupported(format!("clé de {bits} bits"))),
+                };
+                let p = int(b"P").ok_or(CryptErr::Encrypt("/P absent"))?;
+                let params = Md5Params {
+                    r,
+                    len,
+                    o,
+                    u,
+                    p: p as u32,
+                    id0,
+                    encrypt_metadata,
+                };
+                params
+                    .user_key(&pad(password))
+                    .or_else(|| params.user_key(&params.user_password_from_owner(password)))
+                    .ok_or(CryptErr::Password)?
+            }
+            5 | 6 => {
+                let (oe, ue) = (bytes(b"OE")?, bytes(b"UE")?);
+                if o.len() < 48 || u.len() < 48 || oe.len() < 32 || ue.len() < 32 {
+                    bail!(CryptErr::Encrypt("/O, /U, /OE ou /UE tronqué"));
+                }
+                let password = &password[..password.len().min(127)];
+                let hash = |salt: &[u8], udata: &[u8]| -> Result<Vec<u8>> {
+                    if r == 5 {
+                        Ok(Sha256::new()
+                            .chain_update(password)
+                            .chain_update(salt)
+                            .chain_update(udata)
+                            .finalize()
+                            .to_vec())
+                    } else {
+                        hash_2b(password, salt, udata)
+                    }
+                };
+                if hash(&u[32..40], &[])? == u[..32] {
+                    backend::aes256_unwrap(&hash(&u[40..48], &[])?, &ue[..32])?
+                } else if hash(&o[32..40], &u[..48])? == o[..32] {
+                    backend::aes256_unwrap(&hash(&o[40..48], &u[..48])?, &oe[..32])?
+                } else {
+                    bail!(CryptErr::Password);
+                }
+            }
+            _ => bail!(CryptErr::Unsupported(format!("/R {r}"))),
+        };
+
+        Ok(Handler {
+            key,
+            strings,
+            streams,
+            encrypt_metadata,
+        })
+    }
+
+    fn decrypt_object(&self, id: ObjectId, obj: &mut Object) -> Result<()> {
+        match obj {
+            Object::String(s, _) => *s = self.decrypt(id, self.strings, s)?,
+            Object::Array(items) => {
+                for item in items {
+                    self.decrypt_object(id, item)?;
+                }
+            }
+            Object::Dictionary(dict) => self.decrypt_dict(id, dict)?,
+            Object::Stream(stream) => {
+                let kind = stream.dict.get(b"Type").and_then(Object::as_name).ok();
+                let clear = kind == Some(b"XRef".as_slice())
+                    || (kind == Some(b"Metadata".as_slice()) && !self.encrypt_metadata);
+                if !clear {
+                    let plain = self.decrypt(id, self.streams, &stream.content)?;
+                    stream.set_content(plain);
+                }
+                self.decrypt_dict(id, &mut stream.dict)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn decrypt_dict(&self, id: ObjectId, dict: &mut Dictionary) -> Result<()> {
+        let signature = dict.has(b"ByteRange") && dict.has(b"Contents");
+        for (key, value) in dict.iter_mut() {
+            if signature && key.as_slice() == b"Contents" {
+                continue;
+            }
+            self.decrypt_object(id, value)?;
+        }
+        Ok(())
+    }
+
+    fn decrypt(&self, id: ObjectId, cfm: Cfm, data: &[u8]) -> Result<Vec<u8>> {
+        match cfm {
+            Cfm::Identity => Ok(data.to_vec()),
+            Cfm::Rc4 => Ok(rc4(&self.object_key(id, false), data)),
+            Cfm::AesV2 => backend::aes_cbc_decrypt(&self.object_key(id, true), data),
+            Cfm::AesV3 => backend::aes_cbc_decrypt(&self.key, data),
+        }
+    }
+
+    /// Clé propre à l’objet (algorithme 1) : RC4 et AESV2 seulement.
+    fn object_key(&self, (num, gen): ObjectId, aes: bool) -> Vec<u8> {
+        let mut ctx = md5::Context::new();
+        ctx.consume(&self.key);
+        ctx.consume(&num.to_le_bytes()[..3]);
+        ctx.consume(gen.to_le_bytes());
+        if aes {
+            ctx.consume(b"sAlT");
+        }
+        ctx.compute()[..(self.key.len() + 5).min(16)].to_vec()
+    }
+}
+
+/// Méthode du filtre nommé par `/StrF` ou `/StmF` (V4/V5).
+fn crypt_filter(encrypt: &Dictionary, which: &[u8]) -> Result<Cfm> {
+    let name = match encrypt.get(which).and_then(Object::as_name) {
+        Ok(b"Identity") | Err(_) => return Ok(Cfm::Identity),
+        Ok(name) => name,
+    };
+    let cfm = encrypt
+        .get(b"CF")
+        .and_then(Object::as_dict)
+        .and_then(|cf| cf.get(name))
+        .and_then(Object::as_dict)
+        .and_then(|f| f.get(b"CFM"))
+        .and_then(Object::as_name);
+    match cfm {
+        Ok(b"V2") => Ok(Cfm::Rc4),
+        Ok(b"AESV2") => Ok(Cfm::AesV2),
+        Ok(b"AESV3") => Ok(Cfm::AesV3),
+        Ok(b"None") | Err(_) => Ok(Cfm::Identity),
+        Ok(other) => bail!(CryptErr::Unsupported(format!(
+            "filtre /CFM /{}",
+            String::from_utf8_lossy(other)
+        ))),
+    }
+}
+
+/// Paramètres des révisions 2 à 4 (clé dérivée par MD5).
+struct Md5Params<'a> {
+    r: i64,
+    len: usize,
+    o: &'a [u8],
+    u: &'a [u8],
+    p: u32,
+    id0: &'a [u8],
+    encrypt_metadata: bool,
+}
+
+impl Md5Params<'_> {
+    /// Clé de fichier (algorithme 2) si le mot de passe utilisateur bourré
+    /// `padded` est le bon (algorithmes 4 et 5).
+    fn user_key(&self, padded: &[u8; 32]) -> Option<Vec<u8>> {
+        let mut ctx = md5::Context::new();
+        ctx.consume(padded);
+        ctx.consume(self.o);
+        ctx.consume(self.p.to_le_bytes());
+        ctx.consume(self.id0);
+        if self.r >= 4 && !self.encrypt_metadata {
+            ctx.consume([0xFF; 4]);
+        }
+        let mut key = ctx.compute()[..self.len].to_vec();
+        if self.r >= 3 {
+            for _ in 0..50 {
+                key = md5::compute(&key)[..self.len].to_vec();
+            }
+        }
+
+        let expected = if self.r == 2 {
+            rc4(&key, &PAD)
+        } else {
+            let mut ctx = md5::Context::new();
+            ctx.consume(PAD);
+            ctx.consume(self.id0);
+            rc4_rounds(&key, &ctx.compute().0, 0..=19)
+        };
+        let n = if self.r == 2 { 32 } else { 16 };
+        (self.u.get(..n) == expected.get(..n)).then_some(key)
+    }
+
+    /// Mot de passe utilisateur bourré, retrouvé depuis /O avec le mot de
+    /// passe propriétaire (algorithme 7).
+    fn user_password_from_owner(&self, owner: &[u8]) -> [u8; 32] {
+        let mut digest = md5::compute(pad(owner)).0;
+        if self.r >= 3 {
+            for _ in 0..50 {
+                digest = md5::compute(digest).0;
+            }
+        }
+        let key = &digest[..self.len];
+        let user = if self.r == 2 {
+            rc4(key, self.o)
+        } else {
+            rc4_rounds(key, self.o, (0..=19).rev())
+        };
+        let mut padded = [0; 32];
+        let n = user.len().min(32);
+        padded[..n].copy_from_slice(&user[..n]);
+        padded
+    }
+}
+
+fn pad(password: &[u8]) -> [u8; 32] {
+    let n = password.len().min(32);
+    let mut padded = PAD;
+    padded[..n].copy_from_slice(&password[..n]);
+    padded[n..].copy_from_slice(&PAD[..32 - n]);
+    padded
+}
+
+/// RC4 successifs avec la clé XOR `i` pour chaque `i` de `rounds`.
+fn rc4_rounds(key: &[u8], data: &[u8], rounds: impl Iterator<Item = u8>) -> Vec<u8> {
+    let mut out = data.to_vec();
+    for i in rounds {
+        let k: Vec<u8> = key.iter().map(|b| b ^ i).collect();
+        out = rc4(&k, &out);
+    }
+    out
+}
+
+fn rc4(key: &[u8], data: &[u8]) -> Vec<u8> {
+    if key.is_empty() {
+        return data.to_vec();
+    }
+    let mut s: [u8; 256] = std::array::from_fn(|i| i as u8);
+    let mut j = 0u8;
+    for i in 0..256 {
+        j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+        s.swap(i, usize::from(j));
+    }
+    let (mut i, mut j) = (0u8, 0u8);
+    data.iter()
+        .map(|b| {
+            i = i.wrapping_add(1);
+            j = j.wrapping_add(s[usize::from(i)]);
+            s.swap(usize::from(i), usize::from(j));
+            b ^ s[usize::from(s[usize::from(i)].wrapping_add(s[usize::from(j)]))]
+        })
+        .collect()
+}
+
+/// Hachage de la révision 6 (ISO 32000-2 §7.6.4.3.4, algorithme 2.B).
+fn hash_2b(password: &[u8], salt: &[u8], udata: &[u8]) -> Result<Vec<u8>> {
+    let mut k = Sha256::new()
+        .chain_update(password)
+        .chain_update(salt)
+        .chain_update(udata)
+        .finalize()
+        .to_vec();
+    let mut round = 0u32;
+    loop {
+        let block: Vec<u8> = [password, &k, udata].concat();
+        let k1 = block.repeat(64);
+        let e = backend::aes128_cbc_encrypt(&k[..16], &k[16..32], &k1)?;
+        let sum: u32 = e[..16].iter().map(|b| u32::from(*b)).sum();
+        k = match sum % 3 {
+            0 => Sha256::digest(&e).to_vec(),
+            1 => Sha384::digest(&e).to_vec(),
+            _ => Sha512::digest(&e).to_vec(),
+        };
+        round += 1;
+        let last = u32::from(*e.last().unwrap_or(&0));
+        if round >= 64 && last + 32 <= round {
+            break;
+        }
+    }
+    k.truncate(32);
+    Ok(k)
+}
+
+/// AES-CBC (RustCrypto), comme RC4 sans dépendre d’OpenSSL.
+mod backend {
+    use aes::cipher::block_padding::{NoPadding, Pkcs7};
+    use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+    use aes::{Aes128, Aes256};
+    use anyhow::{anyhow, bail, Result};
+    use cbc::{Decryptor, Encryptor};
+
+    /// AES-CBC, vecteur d’initialisation en tête, bourrage PKCS#7.
+    pub fn aes_cbc_decrypt(key: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+        if data.len() < 32 || data.len() % 16 != 0 {
+            bail!("donnée chiffrée AES tronquée ({} octets)", data.len());
+        }
+        let (iv, body) = data.split_at(16);
+        let padding = |_| anyhow!("bourrage AES invalide");
+        match key.len() {
+            16 => Decryptor::<Aes128>::new_from_slices(key, iv)?
+                .decrypt_padded_vec_mut::<Pkcs7>(body)
+                .map_err(padding),
+            32 => Decryptor::<Aes256>::new_from_slices(key, iv)?
+                .decrypt_padded_vec_mut::<Pkcs7>(body)
+                .map_err(padding),
+            n => bail!("clé AES de {n} octets"),
+        }
+    }
+
+    /// Clé de fichier chiffrée par /UE ou /OE (AES-256, IV nul, sans bourrage).
+    pub fn aes256_unwrap(key: &[u8], wrapped: &[u8]) -> Result<Vec<u8>> {
+        Decryptor::<Aes256>::new_from_slices(key, &[0; 16])?
+            .decrypt_padded_vec_mut::<NoPadding>(wrapped)
+            .map_err(|_| anyhow!("/OE ou /UE non aligné sur le bloc AES"))
+    }
+
+    pub fn aes128_cbc_encrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Result<Vec<u8>> {
+        Ok(
+            Encryptor::<Aes128>::new_from_slices(key, iv)?
+                .encrypt_padded_vec_mut::<NoPadding>(data),
+        )
+    }
+}
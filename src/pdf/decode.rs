@@ -0,0 +1,99 @@
+//! Décodage borné des flux : un flux compressé n’est jamais inflaté au-delà
+//! du budget, quel que soit son taux de compression.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::ZlibDecoder;
+use lopdf::{Object, Stream};
+use std::io::{self, Read, Write};
+use weezl::{decode::Decoder, BitOrder};
+
+/// Contenu décodé de `stream` (/FlateDecode, /LZWDecode), refusé dès qu’il
+/// dépasse `max_bytes` : chaque filtre décode au travers d’une sortie plafonnée
+/// à `max_bytes + 1` octets.
+pub fn decode_capped(stream: &Stream, max_bytes: usize) -> Result<Vec<u8>> {
+    let filters = match stream.dict.get(b"Filter") {
+        Ok(_) => stream.filters().context("/Filter invalide")?,
+        Err(_) => Vec::new(),
+    };
+    let mut data = stream.content.clone();
+    for filter in &filters {
+        data = match filter.as_str() {
+            "FlateDecode" => inflate(&data, max_bytes),
+            "LZWDecode" => lzw(&data, stream, max_bytes),
+            other => bail!("filtre /{other} non pris en charge"),
+        };
+    }
+    if data.len() > max_bytes {
+        bail!("flux de plus de {max_bytes} octets une fois décodé");
+    }
+    let predictor = stream
+        .dict
+        .get(b"DecodeParms")
+        .and_then(Object::as_dict)
+        .and_then(|p| p.get(b"Predictor"))
+        .and_then(Object::as_i64)
+        .unwrap_or(1);
+    if predictor > 1 {
+        // Taille décodée bornée ci-dessus, qu’un prédicteur ne fait que
+        // réduire : lopdf peut relire le flux.
+        return stream
+            .decompressed_content()
+            .context("décodage du prédicteur");
+    }
+    Ok(data)
+}
+
+/// Inflate zlib d’au plus `max_bytes + 1` octets. Comme lopdf, un flux
+/// tronqué garde ce qui a pu être décodé.
+fn inflate(input: &[u8], max_bytes: usize) -> Vec<u8> {
+    let mut output = Vec::new();
+    let limit = u64::try_from(max_bytes)
+        .unwrap_or(u64::MAX)
+        .saturating_add(1);
+    let _ = ZlibDecoder::new(input).take(limit).read_to_end(&mut output);
+    output
+}
+
+/// LZW (EarlyChange 1 par défaut) d’au plus `max_bytes + 1` octets.
+fn lzw(input: &[u8], stream: &Stream, max_bytes: usize) -> Vec<u8> {
+    let early_change = stream
+        .dict
+        .get(b"DecodeParms")
+        .and_then(Object::as_dict)
+        .and_then(|p| p.get(b"EarlyChange"))
+        .and_then(Object::as_i64)
+        .map_or(true, |v| v != 0);
+    let mut decoder = if early_change {
+        Decoder::with_tiff_size_switch(BitOrder::Msb, 8)
+    } else {
+        Decoder::new(BitOrder::Msb, 8)
+    };
+    let mut sink = Capped {
+        output: Vec::new(),
+        limit: max_bytes.saturating_add(1),
+    };
+    let _ = decoder.into_stream(&mut sink).decode_all(input);
+    sink.output
+}
+
+/// Tampon refusant toute écriture au-delà de `limit` octets.
+struct Capped {
+    output: Vec<u8>,
+    limit: usize,
+}
+
+impl Write for Capped {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let room = self.limit - self.output.len();
+        if room == 0 {
+            return Err(io::Error::other("budget de décodage dépassé"));
+        }
+        let n = buf.len().min(room);
+        self.output.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
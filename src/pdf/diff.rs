@@ -0,0 +1,523 @@
+use crate::pdf::fields::form_fields;
+use crate::report::ModificationKind;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Borne du parcours du graphe d’objets (défense contre les PDF piégés).
+const MAX_REACHABLE: usize = 200_000;
+
+/// Clés à ne pas suivre lors d’un parcours descendant (remontent l’arbre).
+const UPWARD_KEYS: &[&[u8]] = &[b"Parent", b"P"];
+
+/// Objet ajouté, modifié ou supprimé entre deux révisions.
+#[derive(Debug, Clone)]
+pub struct ObjectChange {
+    pub id: ObjectId,
+    pub kind: ModificationKind,
+    pub field: Option<String>,
+}
+
+/// Compare le graphe d’objets de la révision signée (`old`) à celui de la
+/// révision finale (`new`) et classe chaque objet qui diffère.
+///
+/// Les rôles sont ceux de la révision signée ; la révision finale n’en
+/// attribue qu’aux objets qu’elle ajoute, pour qu’un objet signé ne puisse
+/// être requalifié (contenu de page désigné comme /Metadata, /Info ou DSS).
+pub fn diff_revisions(old: &Document, new: &Document) -> Vec<ObjectChange> {
+    let roles = Roles::build(old).merge(Roles::build(new).added_since(old));
+    let mut live = live_objects(old);
+    live.extend(live_objects(new));
+    let ids: BTreeSet<ObjectId> = old
+        .objects
+        .keys()
+        .chain(new.objects.keys())
+        .copied()
+        .collect();
+
+    ids.into_iter()
+        .filter_map(|id| {
+            let before = old.objects.get(&id);
+            let after = new.objects.get(&id);
+            if same_object(before, after)
+                || (!roles.has_role(id)
+                    && [before, after]
+                        .into_iter()
+                        .flatten()
+                        .all(|o| is_structure(id, o, &live)))
+            {
+                return None;
+            }
+            let (kind, field) = classify(id, before, after, old, new, &roles);
+            Some(ObjectChange { id, kind, field })
+        })
+        .collect()
+}
+
+pub fn same_object(a: Option<&Object>, b: Option<&Object>) -> bool {
+    match (a, b) {
+        // La position du flux dans le fichier n’est pas une modification.
+        (Some(Object::Stream(x)), Some(Object::Stream(y))) => {
+            x.dict == y.dict && x.content == y.content
+        }
+        (Some(x), Some(y)) => x == y,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Flux de structure du fichier (/XRef, /ObjStm) : réécrits à chaque révision
+/// incrémentale, ils ne portent pas de contenu. Les objets qu’un flux d’objets
+/// contient sont comparés un à un.
+///
+/// Le /Type d’un flux est déclaré par l’auteur de la révision : un flux n’est
+/// écarté que s’il n’est référencé par aucune révision (`live`) et a la forme
+/// d’un flux d’objets (/N, /First) ou de références croisées (/W, /Size).
+pub fn is_structure(id: ObjectId, obj: &Object, live: &HashSet<ObjectId>) -> bool {
+    let Object::Stream(s) = obj else {
+        return false;
+    };
+    // Graphe tronqué : l’absence de référence n’est plus une preuve.
+    if live.contains(&id) || live.len() >= MAX_REACHABLE {
+        return false;
+    }
+    let has = |keys: [&[u8]; 2]| keys.iter().all(|k| s.dict.has(k));
+    match s.dict.get(b"Type").and_then(Object::as_name) {
+        Ok(b"ObjStm") => has([b"N", b"First"]),
+        Ok(b"XRef") => has([b"W", b"Size"]),
+        _ => false,
+    }
+}
+
+/// Objets atteignables depuis le trailer (catalogue, /Info, /Encrypt…).
+pub fn live_objects(doc: &Document) -> HashSet<ObjectId> {
+    let mut out = HashSet::new();
+    reachable(doc, &Object::Dictionary(doc.trailer.clone()), &mut out);
+    out
+}
+
+/// Rôle des objets dans le graphe (révision signée, plus les objets ajoutés
+/// par la révision finale).
+#[derive(Default)]
+struct Roles {
+    catalog: HashSet<ObjectId>,
+    acroform: HashSet<ObjectId>,
+    metadata: HashSet<ObjectId>,
+    dss: HashSet<ObjectId>,
+    /// Nœuds AcroForm : nom complet, champ de signature ?
+    fields: HashMap<ObjectId, (Option<String>, bool)>,
+    pages: HashSet<ObjectId>,
+    annots_arrays: HashSet<ObjectId>,
+    page_content: HashSet<ObjectId>,
+    /// Objets d’apparence (/AP) → annotation propriétaire.
+    appearances: HashMap<ObjectId, ObjectId>,
+}
+
+impl Roles {
+    fn build(doc: &Document) -> Roles {
+        let mut r = Roles::default();
+        if let Ok(root) = doc.trailer.get(b"Root").and_then(Object::as_reference) {
+            r.catalog.insert(root);
+        }
+        if let Ok(info) = doc.trailer.get(b"Info").and_then(Object::as_reference) {
+            r.metadata.insert(info);
+        }
+        if let Ok(cat) = doc.catalog() {
+            if let Ok(id) = cat.get(b"AcroForm").and_then(Object::as_reference) {
+                r.acroform.insert(id);
+            }
+            if let Ok(id) = cat.get(b"Metadata").and_then(Object::as_reference) {
+                r.metadata.insert(id);
+            }
+            if let Ok(dss) = cat.get(b"DSS") {
+                reachable(doc, dss, &mut r.dss);
+            }
+        }
+        for (name, node) in form_fields(doc) {
+            if let Some(id) = node.id {
+                r.fields
+                    .insert(id, (name, node.ft == Some(b"Sig".as_slice())));
+            }
+        }
+        for (id, obj) in &doc.objects {
+            let Ok(dict) = dict_of(obj) else {
+                continue;
+            };
+            match dict.get(b"Type").and_then(Object::as_name) {
+                Ok(b"Page") => {
+                    r.pages.insert(*id);
+                    for key in [b"Contents".as_slice(), b"Resources"] {
+                        if let Ok(o) = dict.get(key) {
+                            reachable(doc, o, &mut r.page_content);
+                        }
+                    }
+                    if let Ok(annots) = dict.get(b"Annots") {
+                        if let Ok(arr) = annots.as_reference() {
+                            r.annots_arrays.insert(arr);
+                        }
+                        for annot in doc
+                            .dereference(annots)
+                            .ok()
+                            .and_then(|(_, a)| a.as_array().ok())
+                            .into_iter()
+                            .flatten()
+                        {
+                            r.note_appearances(doc, annot);
+                        }
+                    }
+                }
+                Ok(b"Pages") => {
+                    r.pages.insert(*id);
+                    if let Ok(res) = dict.get(b"Resources") {
+                        reachable(doc, res, &mut r.page_content);
+                    }
+                }
+                _ => {}
+            }
+        }
+        r
+    }
+
+    fn note_appearances(&mut self, doc: &Document, annot: &Object) {
+        let Ok(owner) = annot.as_reference() else {
+            return;
+        };
+        let Ok(ap) = doc.get_dictionary(owner).and_then(|d| d.get(b"AP")) else {
+            return;
+        };
+        let mut ids = HashSet::new();
+        reachable(doc, ap, &mut ids);
+        for id in ids {
+            self.appearances.entry(id).or_insert(owner);
+        }
+    }
+
+    /// L’objet a-t-il un rôle dans le graphe ?
+    fn has_role(&self, id: ObjectId) -> bool {
+        [
+            &self.catalog,
+            &self.acroform,
+            &self.metadata,
+            &self.dss,
+            &self.pages,
+            &self.annots_arrays,
+            &self.page_content,
+        ]
+        .iter()
+        .any(|set| set.contains(&id))
+            || self.fields.contains_key(&id)
+            || self.appearances.contains_key(&id)
+    }
+
+    /// Ne garde que les rôles des objets absents de la révision `old`.
+    fn added_since(mut self, old: &Document) -> Roles {
+        let added = |id: &ObjectId| !old.objects.contains_key(id);
+        for set in [
+            &mut self.catalog,
+            &mut self.acroform,
+            &mut self.metadata,
+            &mut self.dss,
+            &mut self.pages,
+            &mut self.annots_arrays,
+            &mut self.page_content,
+        ] {
+            set.retain(added);
+        }
+        self.fields.retain(|id, _| added(id));
+        self.appearances.retain(|id, _| added(id));
+        self
+    }
+
+    fn merge(mut self, other: Roles) -> Roles {
+        self.catalog.extend(other.catalog);
+        self.acroform.extend(other.acroform);
+        self.metadata.extend(other.metadata);
+        self.dss.extend(other.dss);
+        for (id, f) in other.fields {
+            self.fields.entry(id).or_insert(f);
+        }
+        self.pages.extend(other.pages);
+        self.annots_arrays.extend(other.annots_arrays);
+        self.page_content.extend(other.page_content);
+        for (id, owner) in other.appearances {
+            self.appearances.entry(id).or_insert(owner);
+        }
+        self
+    }
+}
+
+fn classify(
+    id: ObjectId,
+    before: Option<&Object>,
+    after: Option<&Object>,
+    old: &Document,
+    new: &Document,
+    roles: &Roles,
+) -> (ModificationKind, Option<String>) {
+    use ModificationKind::*;
+
+    let old_dict = before.and_then(|o| dict_of(o).ok());
+    let new_dict = after.and_then(|o| dict_of(o).ok());
+    let typ = new_dict
+        .or(old_dict)
+        .and_then(|d| d.get(b"Type").and_then(Object::as_name).ok());
+
+    // Ce qui est dessiné passe avant tout autre rôle : un objet de page ne
+    // devient pas DSS ou métadonnées parce qu’une révision l’y rattache.
+    if roles.pages.contains(&id) {
+        let keys = changed_keys(old_dict, new_dict);
+        if before.is_some() && keys.iter().all(|k| *k == b"Annots") {
+            let (before, after) = (annots_of(old_dict, old), annots_of(new_dict, new));
+            return (annots_change(&before, &after, old, new, roles), None);
+        }
+        return (PageContent, None);
+    }
+    if roles.page_content.contains(&id) {
+        return (PageContent, None);
+    }
+    if roles.dss.contains(&id) {
+        return (Dss, None);
+    }
+    match typ {
+        // Un dictionnaire de signature existant ne doit jamais être réécrit.
+        Some(b"Sig") if before.is_none() => return (Signature, None),
+        Some(b"DocTimeStamp") if before.is_none() => return (DocTimestamp, None),
+        Some(b"Sig" | b"DocTimeStamp") => return (Other, None),
+        _ => {}
+    }
+    if roles.catalog.contains(&id) {
+        return (catalog_change(old_dict, new_dict, old, new, roles), None);
+    }
+    if roles.acroform.contains(&id) {
+        return (acroform_change(old_dict, new_dict, old, new, roles), None);
+    }
+    if roles.metadata.contains(&id) {
+        return (Metadata, None);
+    }
+    if let Some((name, is_sig)) = roles.fields.get(&id) {
+        let kind = if *is_sig {
+            let had_value = old_dict.is_some_and(|d| d.has(b"V"));
+            if had_value && changed_keys(old_dict, new_dict).contains(&b"V".as_slice()) {
+                Other
+            } else {
+                Signature
+            }
+        } else if before.is_some()
+            && changed_keys(old_dict, new_dict)
+                .iter()
+                .all(|k| matches!(*k, b"V" | b"AS" | b"AP"))
+        {
+            FormFill
+        } else {
+            Other
+        };
+        return (kind, name.clone());
+    }
+    if roles.annots_arrays.contains(&id) {
+        let list = |o: Option<&Object>| -> Vec<ObjectId> {
+            o.and_then(|o| o.as_array().ok())
+                .map(|a| a.iter().filter_map(|x| x.as_reference().ok()).collect())
+                .unwrap_or_default()
+        };
+        return (
+            annots_change(&list(before), &list(after), old, new, roles),
+            None,
+        );
+    }
+    if let Some(owner) = roles.appearances.get(&id) {
+        return annotation_kind(*owner, old, new, roles);
+    }
+    if typ == Some(b"Annot") {
+        return annotation_kind(id, old, new, roles);
+    }
+    (Other, None)
+}
+
+/// Nature d’une annotation : widget de signature, widget de champ, ou autre.
+fn annotation_kind(
+    id: ObjectId,
+    old: &Document,
+    new: &Document,
+    roles: &Roles,
+) -> (ModificationKind, Option<String>) {
+    match roles.fields.get(&id) {
+        Some((name, true)) => (ModificationKind::Signature, name.clone()),
+        Some((name, false)) => (ModificationKind::FormFill, name.clone()),
+        None => {
+            let dict = new.get_dictionary(id).or_else(|_| old.get_dictionary(id));
+            let widget = dict
+                .and_then(|d| d.get(b"Subtype"))
+                .and_then(Object::as_name)
+                .is_ok_and(|s| s == b"Widget");
+            // Un widget sans champ connu équivaut à un nouveau champ.
+            let kind = if widget {
+                ModificationKind::Other
+            } else {
+                ModificationKind::Annotation
+            };
+            (kind, None)
+        }
+    }
+}
+
+fn annots_change(
+    before: &[ObjectId],
+    after: &[ObjectId],
+    old: &Document,
+    new: &Document,
+    roles: &Roles,
+) -> ModificationKind {
+    let added = after.iter().filter(|id| !before.contains(id));
+    let removed = before.iter().filter(|id| !after.contains(id));
+    added
+        .chain(removed)
+        .map(|id| annotation_kind(*id, old, new, roles).0)
+        .fold(ModificationKind::Signature, strictest)
+}
+
+fn catalog_change(
+    old_dict: Option<&Dictionary>,
+    new_dict: Option<&Dictionary>,
+    old: &Document,
+    new: &Document,
+    roles: &Roles,
+) -> ModificationKind {
+    use ModificationKind::*;
+    if old_dict.is_none() || new_dict.is_none() {
+        return Catalog;
+    }
+    changed_keys(old_dict, new_dict)
+        .into_iter()
+        .map(|k| match k {
+            b"DSS" => Dss,
+            b"Metadata" => Metadata,
+            b"AcroForm" => match (inline_acroform(old_dict), inline_acroform(new_dict)) {
+                (Some(a), Some(b)) => acroform_change(Some(a), Some(b), old, new, roles),
+                _ => Catalog,
+            },
+            _ => Catalog,
+        })
+        .fold(Dss, strictest)
+}
+
+fn inline_acroform(catalog: Option<&Dictionary>) -> Option<&Dictionary> {
+    catalog?.get(b"AcroForm").and_then(Object::as_dict).ok()
+}
+
+fn acroform_change(
+    old_dict: Option<&Dictionary>,
+    new_dict: Option<&Dictionary>,
+    old: &Document,
+    new: &Document,
+    roles: &Roles,
+) -> ModificationKind {
+    use ModificationKind::*;
+    if old_dict.is_none() || new_dict.is_none() {
+        return Catalog;
+    }
+    changed_keys(old_dict, new_dict)
+        .into_iter()
+        .map(|k| match k {
+            b"SigFlags" => Signature,
+            b"NeedAppearances" | b"DR" | b"DA" => FormFill,
+            b"Fields" => {
+                let before = refs_of(old_dict, b"Fields", old);
+                let after = refs_of(new_dict, b"Fields", new);
+                let only_signatures = after
+                    .iter()
+                    .filter(|id| !before.contains(id))
+                    .chain(before.iter().filter(|id| !after.contains(id)))
+                    .all(|id| matches!(roles.fields.get(id), Some((_, true))));
+                if only_signatures {
+                    Signature
+                } else {
+                    Other
+                }
+            }
+            _ => Catalog,
+        })
+        .fold(Dss, strictest)
+}
+
+/// Garde la modification la plus exigeante en permissions DocMDP.
+fn strictest(a: ModificationKind, b: ModificationKind) -> ModificationKind {
+    fn rank(k: ModificationKind) -> u8 {
+        use ModificationKind::*;
+        match k {
+            Dss | DocTimestamp => 0,
+            Signature | FormFill | Metadata => 1,
+            Annotation => 2,
+            PageContent | Catalog | Other => 3,
+        }
+    }
+    if rank(b) > rank(a) {
+        b
+    } else {
+        a
+    }
+}
+
+fn changed_keys<'a>(old: Option<&'a Dictionary>, new: Option<&'a Dictionary>) -> Vec<&'a [u8]> {
+    let empty = Dictionary::new();
+    let mut keys: Vec<&[u8]> = Vec::new();
+    for (a, b) in [(old, new), (new, old)] {
+        let Some(a) = a else {
+            continue;
+        };
+        let b = b.unwrap_or(&empty);
+        for (k, v) in a.iter() {
+            if b.get(k).ok() != Some(v) && !keys.contains(&k.as_slice()) {
+                keys.push(k.as_slice());
+            }
+        }
+    }
+    keys
+}
+
+fn annots_of(dict: Option<&Dictionary>, doc: &Document) -> Vec<ObjectId> {
+    refs_of(dict, b"Annots", doc)
+}
+
+fn refs_of(dict: Option<&Dictionary>, key: &[u8], doc: &Document) -> Vec<ObjectId> {
+    dict.and_then(|d| d.get(key).ok())
+        .and_then(|o| doc.dereference(o).ok())
+        .and_then(|(_, o)| o.as_array().ok())
+        .map(|a| a.iter().filter_map(|x| x.as_reference().ok()).collect())
+        .unwrap_or_default()
+}
+
+pub fn dict_of(obj: &Object) -> lopdf::Result<&Dictionary> {
+    match obj {
+        Object::Stream(s) => Ok(&s.dict),
+        o => o.as_dict(),
+    }
+}
+
+/// Ajoute à `out` les objets indirects atteignables depuis `root`, sans
+/// remonter par /Parent ni /P.
+pub fn reachable(doc: &Document, root: &Object, out: &mut HashSet<ObjectId>) {
+    let mut stack = vec![root];
+    while let Some(obj) = stack.pop() {
+        if out.len() >= MAX_REACHABLE {
+            return;
+        }
+        match obj {
+            Object::Reference(id) if out.insert(*id) => {
+                if let Some(o) = doc.objects.get(id) {
+                    stack.push(o);
+                }
+            }
+            Object::Array(arr) => stack.extend(arr.iter()),
+            Object::Dictionary(d) => push_dict(d, &mut stack),
+            Object::Stream(s) => push_dict(&s.dict, &mut stack),
+            _ => {}
+        }
+    }
+}
+
+fn push_dict<'a>(d: &'a Dictionary, stack: &mut Vec<&'a Object>) {
+    for (k, v) in d.iter() {
+        if !UPWARD_KEYS.contains(&k.as_slice()) {
+            stack.push(v);
+        }
+    }
+}
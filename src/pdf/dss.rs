@@ -0,0 +1,124 @@
+use crate::revocation::ValidationMaterial;
+use lopdf::xref::XrefEntry;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+
+/// Document Security Store (ETSI EN 319 142-1 §5.4.2) : matériel de
+/// validation commun et entrées VRI indexées par signature.
+#[derive(Debug, Clone, Default)]
+pub struct Dss {
+    pub material: ValidationMaterial,
+    /// Clé : SHA-1 (hex majuscule) du /Contents de la signature.
+    pub vri: BTreeMap<String, ValidationMaterial>,
+    /// Objet portant le DSS (le catalogue s’il y figure en direct).
+    pub id: ObjectId,
+    /// Position dans le fichier de la définition en vigueur du DSS (ou du
+    /// catalogue qui le porte en direct), pour situer sa révision.
+    pub offset: Option<usize>,
+}
+
+impl Dss {
+    /// Matériel applicable à la signature de /Contents `contents` : DSS
+    /// commun plus entrée VRI si elle existe (clé VRI retournée).
+    pub fn material_for(&self, contents: &[u8]) -> (ValidationMaterial, Option<String>) {
+        let mut material = self.material.clone();
+        let key = vri_keys(contents)
+            .into_iter()
+            .find(|k| self.vri.contains_key(k));
+        if let Some(vri) = key.as_ref().and_then(|k| self.vri.get(k)) {
+            material.extend(vri);
+        }
+        (material, key)
+    }
+}
+
+/// /DSS du catalogue ; à défaut, premier dictionnaire /Type /DSS du fichier.
+pub fn read_dss(doc: &Document) -> Option<Dss> {
+    let root = doc.trailer.get(b"Root").and_then(Object::as_reference).ok();
+    let from_catalog = root
+        .and_then(|id| doc.get_dictionary(id).ok())
+        .and_then(|c| c.get(b"DSS").ok())
+        .and_then(|d| match d {
+            Object::Reference(id) => Some((*id, doc.get_dictionary(*id).ok()?)),
+            Object::Dictionary(d) => Some((root?, d)),
+            _ => None,
+        });
+    let (id, dict) = from_catalog.or_else(|| {
+        doc.objects.iter().find_map(|(id, o)| {
+            let d = o.as_dict().ok()?;
+            let is_dss = d
+                .get(b"Type")
+                .and_then(Object::as_name)
+                .is_ok_and(|t| t == b"DSS");
+            is_dss.then_some((*id, d))
+        })
+    })?;
+
+    let mut dss = Dss {
+        material: material(doc, dict, [b"Certs".as_slice(), b"CRLs", b"OCSPs"]),
+        id,
+        offset: object_offset(doc, id),
+        ..Default::default()
+    };
+    if let Ok((_, Object::Dictionary(vri))) = dict.get(b"VRI").and_then(|v| doc.dereference(v)) {
+        for (key, entry) in vri.iter() {
+            if let Ok((_, Object::Dictionary(entry))) = doc.dereference(entry) {
+                let key = String::from_utf8_lossy(key).to_ascii_uppercase();
+                dss.vri.insert(
+                    key,
+                    material(doc, entry, [b"Cert".as_slice(), b"CRL", b"OCSP"]),
+                );
+            }
+        }
+    }
+    Some(dss)
+}
+
+/// Clés VRI candidates : SHA-1 du /Contents complet, et du seul élément DER
+/// (certains producteurs hachent sans le bourrage de la réserve).
+pub fn vri_keys(contents: &[u8]) -> Vec<String> {
+    let mut keys = vec![hex::encode_upper(Sha1::digest(contents))];
+    if let Some(der) = crate::cms::signed_data::der_prefix(contents) {
+        if der.len() != contents.len() {
+            keys.push(hex::encode_upper(Sha1::digest(der)));
+        }
+    }
+    keys
+}
+
+/// Position d’un objet d’après la table des références (celle de son flux
+/// d’objets s’il est compressé).
+fn object_offset(doc: &Document, id: ObjectId) -> Option<usize> {
+    let offset = match doc.reference_table.get(id.0)? {
+        XrefEntry::Normal { offset, .. } => *offset,
+        XrefEntry::Compressed { container, .. } => match doc.reference_table.get(*container)? {
+            XrefEntry::Normal { offset, .. } => *offset,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    usize::try_from(offset).ok()
+}
+
+/// Flux référencés par les clés (certificats, CRL, OCSP) de `dict`.
+fn material(doc: &Document, dict: &Dictionary, keys: [&[u8]; 3]) -> ValidationMaterial {
+    let streams = |key: &[u8]| -> Vec<Vec<u8>> {
+        let Ok((_, Object::Array(items))) = dict.get(key).and_then(|a| doc.dereference(a)) else {
+            return Vec::new();
+        };
+        items
+            .iter()
+            .filter_map(|i| match doc.dereference(i).ok()?.1 {
+                Object::Stream(s) if s.dict.has(b"Filter") => s.decompressed_content().ok(),
+                Object::Stream(s) => Some(s.content.clone()),
+                _ => None,
+            })
+            .collect()
+    };
+    ValidationMaterial {
+        certs: streams(keys[0]),
+        crls: streams(keys[1]),
+        ocsps: streams(keys[2]),
+    }
+}
@@ -0,0 +1,100 @@
+//! Pièces jointes du document : arbre des noms /EmbeddedFiles du catalogue
+//! (ISO 32000-2 §7.7.4, §7.11.4), qui porte aussi les membres d’un portfolio.
+
+use crate::pdf::decode::decode_capped;
+use crate::pdf::lazy::is_unloaded;
+use crate::pdf::text::decode_text_string;
+use anyhow::{bail, Context, Result};
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::HashSet;
+
+/// Profondeur max de l’arbre des noms.
+const MAX_TREE_DEPTH: usize = 32;
+
+/// Pièce jointe : nom de fichier et contenu décodé (ou raison de l’échec).
+pub struct Attachment {
+    pub name: String,
+    pub data: Result<Vec<u8>>,
+}
+
+/// Pièces jointes dans l’ordre de l’arbre des noms. Un contenu plus grand que
+/// `max_bytes` une fois décodé est refusé.
+pub fn attachments(doc: &Document, max_bytes: usize) -> Vec<Attachment> {
+    let mut entries = Vec::new();
+    let root = doc
+        .catalog()
+        .and_then(|c| c.get(b"Names"))
+        .and_then(|n| doc.dereference(n))
+        .and_then(|(_, n)| n.as_dict())
+        .and_then(|n| n.get(b"EmbeddedFiles"));
+    if let Ok(root) = root {
+        walk(doc, root, 0, &mut HashSet::new(), &mut entries);
+    }
+    entries
+        .into_iter()
+        .map(|(key, spec)| Attachment {
+            name: file_name(doc, spec).unwrap_or(key),
+            data: contents(doc, spec, max_bytes),
+        })
+        .collect()
+}
+
+fn walk<'a>(
+    doc: &'a Document,
+    node: &'a Object,
+    depth: usize,
+    seen: &mut HashSet<ObjectId>,
+    out: &mut Vec<(String, &'a Dictionary)>,
+) {
+    if depth > MAX_TREE_DEPTH {
+        return;
+    }
+    let Ok((id, Object::Dictionary(node))) = doc.dereference(node) else {
+        return;
+    };
+    if id.is_some_and(|id| !seen.insert(id)) {
+        return;
+    }
+    if let Ok((_, Object::Array(names))) = node.get(b"Names").and_then(|n| doc.dereference(n)) {
+        for pair in names.chunks_exact(2) {
+            let key = pair[0].as_str().map(decode_text_string).unwrap_or_default();
+            if let Ok((_, Object::Dictionary(spec))) = doc.dereference(&pair[1]) {
+                out.push((key, spec));
+            }
+        }
+    }
+    if let Ok((_, Object::Array(kids))) = node.get(b"Kids").and_then(|k| doc.dereference(k)) {
+        for kid in kids {
+            walk(doc, kid, depth + 1, seen, out);
+        }
+    }
+}
+
+/// /UF (Unicode) ou, à défaut, /F de la spécification de fichier.
+fn file_name(doc: &Document, spec: &Dictionary) -> Option<String> {
+    [b"UF".as_slice(), b"F"].iter().find_map(|key| {
+        let (_, name) = doc.dereference(spec.get(key).ok()?).ok()?;
+        name.as_str().ok().map(decode_text_string)
+    })
+}
+
+/// Flux /EF du fichier, filtres appliqués.
+fn contents(doc: &Document, spec: &Dictionary, max_bytes: usize) -> Result<Vec<u8>> {
+    let ef = spec
+        .get(b"EF")
+        .and_then(|ef| doc.dereference(ef))
+        .and_then(|(_, ef)| ef.as_dict())
+        .context("Spécification de fichier sans /EF")?;
+    let stream = [b"UF".as_slice(), b"F"]
+        .iter()
+        .find_map(|key| match doc.dereference(ef.get(key).ok()?) {
+            Ok((_, Object::Stream(stream))) => Some(stream),
+            _ => None,
+        })
+        .context("/EF sans flux de fichier")?;
+    if is_unloaded(stream) {
+        bail!("pièce jointe non chargée par la lecture en flux (augmenter --max-memory-mib)");
+    }
+    decode_capped(stream, max_bytes)
+        .context("Décodage du flux de la pièce jointe sous le budget mémoire a échoué")
+}
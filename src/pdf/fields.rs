@@ -0,0 +1,215 @@
+use crate::pdf::perms::is_usage_rights;
+use crate::pdf::text::decode_text_string;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use std::collections::HashSet;
+
+/// Profondeur max de l’arbre AcroForm (défense contre les PDF piégés).
+const MAX_FIELD_DEPTH: usize = 32;
+
+/// Nœud de l’arbre AcroForm (champ ou widget enfant).
+#[derive(Debug, Clone, Copy)]
+pub struct FieldNode<'a> {
+    pub id: Option<ObjectId>,
+    pub dict: &'a Dictionary,
+    /// /FT propre ou hérité d’un ancêtre.
+    pub ft: Option<&'a [u8]>,
+}
+
+/// Champ de signature AcroForm portant une valeur /V.
+#[derive(Debug, Clone)]
+pub struct SigField {
+    /// Nom complet du champ (/T des ancêtres joints par des points).
+    pub name: Option<String>,
+    pub sig_dict: Dictionary,
+    /// Dictionnaire /Lock du champ (FieldMDP), s’il existe.
+    pub lock: Option<Dictionary>,
+    /// Annotations widget du champ : le champ lui-même s’il est fusionné avec
+    /// son widget, sinon ses /Kids de sous-type /Widget.
+    pub widgets: Vec<(Option<ObjectId>, Dictionary)>,
+}
+
+/// Champ de signature sans valeur /V, en attente de signature.
+#[derive(Debug, Clone)]
+pub struct PendingSigField {
+    pub name: Option<String>,
+    pub widgets: Vec<(Option<ObjectId>, Dictionary)>,
+}
+
+/// Énumère les champs de signature signés dans l’ordre du document
+/// (tableau /Fields de l’AcroForm, parcours en profondeur des /Kids).
+///
+/// Si l’AcroForm n’expose aucune signature, se rabat sur les dictionnaires
+/// de signature orphelins (/Type /Sig ou /DocTimeStamp) par numéro d’objet.
+/// Les signatures de droits d’usage (UR3) n’engagent aucun signataire et sont
+/// écartées.
+pub fn signature_fields(doc: &Document) -> Vec<SigField> {
+    let mut out = Vec::new();
+    for (name, node) in form_fields(doc) {
+        if node.ft != Some(b"Sig".as_slice()) {
+            continue;
+        }
+        let Ok(v) = node.dict.get(b"V") else {
+            continue;
+        };
+        if let Ok((_, Object::Dictionary(sig_dict))) = doc.dereference(v) {
+            if is_usage_rights(doc, sig_dict) {
+                continue;
+            }
+            let lock = node
+                .dict
+                .get(b"Lock")
+                .and_then(|l| doc.dereference(l))
+                .and_then(|(_, l)| l.as_dict())
+                .ok()
+                .cloned();
+            out.push(SigField {
+                name,
+                sig_dict: sig_dict.clone(),
+                lock,
+                widgets: widgets(doc, &node),
+            });
+        }
+    }
+    if out.is_empty() {
+        out = orphan_signatures(doc);
+    }
+    out
+}
+
+/// Énumère les champs de signature terminaux sans /V, dans l’ordre du document.
+/// Les widgets purs (enfants sans /T d’un champ) ne sont pas des champs.
+pub fn pending_signature_fields(doc: &Document) -> Vec<PendingSigField> {
+    form_fields(doc)
+        .into_iter()
+        .filter(|(_, node)| {
+            node.ft == Some(b"Sig".as_slice())
+                && !node.dict.has(b"V")
+                && (node.dict.has(b"T") || !node.dict.has(b"Parent"))
+                && !has_child_fields(doc, node.dict)
+        })
+        .map(|(name, node)| PendingSigField {
+            name,
+            widgets: widgets(doc, &node),
+        })
+        .collect()
+}
+
+fn has_child_fields(doc: &Document, dict: &Dictionary) -> bool {
+    match dict.get(b"Kids").and_then(|k| doc.dereference(k)) {
+        Ok((_, Object::Array(kids))) => kids.iter().any(
+            |kid| matches!(doc.dereference(kid), Ok((_, Object::Dictionary(kid))) if kid.has(b"T")),
+        ),
+        _ => false,
+    }
+}
+
+/// Tous les nœuds de l’AcroForm avec leur nom complet, dans l’ordre du document.
+pub fn form_fields(doc: &Document) -> Vec<(Option<String>, FieldNode<'_>)> {
+    let mut out = Vec::new();
+    if let Some(fields) = acroform_fields(doc) {
+        let mut seen = HashSet::new();
+        for f in fields {
+            walk(doc, f, None, None, 0, &mut seen, &mut out);
+        }
+    }
+    out
+}
+
+fn widgets(doc: &Document, node: &FieldNode) -> Vec<(Option<ObjectId>, Dictionary)> {
+    let mut out = Vec::new();
+    if is_widget(node.dict) {
+        out.push((node.id, node.dict.clone()));
+    }
+    if let Ok((_, Object::Array(kids))) = node.dict.get(b"Kids").and_then(|k| doc.dereference(k)) {
+        for kid in kids {
+            if let Ok((id, Object::Dictionary(kid))) = doc.dereference(kid) {
+                if is_widget(kid) {
+                    out.push((id, kid.clone()));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn is_widget(dict: &Dictionary) -> bool {
+    dict.get(b"Subtype")
+        .and_then(Object::as_name)
+        .is_ok_and(|s| s == b"Widget")
+}
+
+fn acroform_fields(doc: &Document) -> Option<&Vec<Object>> {
+    let acroform = doc.catalog().ok()?.get(b"AcroForm").ok()?;
+    let (_, acroform) = doc.dereference(acroform).ok()?;
+    let fields = acroform.as_dict().ok()?.get(b"Fields").ok()?;
+    doc.dereference(fields).ok()?.1.as_array().ok()
+}
+
+fn walk<'a>(
+    doc: &'a Document,
+    obj: &'a Object,
+    parent_name: Option<&str>,
+    inherited_ft: Option<&'a [u8]>,
+    depth: usize,
+    seen: &mut HashSet<ObjectId>,
+    out: &mut Vec<(Option<String>, FieldNode<'a>)>,
+) {
+    if depth > MAX_FIELD_DEPTH {
+        return;
+    }
+    let Ok((id, obj)) = doc.dereference(obj) else {
+        return;
+    };
+    if let Some(id) = id {
+        if !seen.insert(id) {
+            return;
+        }
+    }
+    let Ok(dict) = obj.as_dict() else {
+        return;
+    };
+
+    let partial = dict
+        .get(b"T")
+        .and_then(Object::as_str)
+        .ok()
+        .map(decode_text_string);
+    let name = match (parent_name, partial) {
+        (Some(p), Some(t)) => Some(format!("{p}.{t}")),
+        (None, Some(t)) => Some(t),
+        (p, None) => p.map(str::to_owned),
+    };
+    let ft = dict
+        .get(b"FT")
+        .and_then(Object::as_name)
+        .ok()
+        .or(inherited_ft);
+    out.push((name.clone(), FieldNode { id, dict, ft }));
+
+    if let Ok(kids) = dict.get(b"Kids") {
+        if let Ok((_, Object::Array(kids))) = doc.dereference(kids) {
+            for kid in kids {
+                walk(doc, kid, name.as_deref(), ft, depth + 1, seen, out);
+            }
+        }
+    }
+}
+
+fn orphan_signatures(doc: &Document) -> Vec<SigField> {
+    doc.objects
+        .values()
+        .filter_map(|obj| {
+            let dict = obj.as_dict().ok()?;
+            let typ = dict.get(b"Type").and_then(Object::as_name).ok()?;
+            if typ != b"Sig" && typ != b"DocTimeStamp" || is_usage_rights(doc, dict) {
+                return None;
+            }
+            Some(SigField {
+                name: None,
+                sig_dict: dict.clone(),
+                lock: None,
+                widgets: Vec::new(),
+            })
+        })
+        .collect()
+}
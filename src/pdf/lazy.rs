@@ -0,0 +1,424 @@
+//! Lecture en flux d’un PDF trop volumineux pour le budget mémoire : la table
+//! des références est suivie depuis `startxref`, puis chaque objet est lu à sa
+//! position. Les gros flux (images numérisées…) ne sont pas chargés.
+
+use crate::pdf::source::ByteSource;
+use crate::pdf::syntax::{Parser, SyntaxErr};
+use anyhow::{anyhow, bail, Context, Result};
+use lopdf::xref::XrefEntry;
+use lopdf::{Dictionary, Document, Object, ObjectId, ObjectStream, Stream};
+use std::collections::{BTreeMap, HashSet};
+
+/// Taille max d’un flux chargé ; au-delà, son contenu est remplacé par un
+/// repère (position et longueur), stable d’une révision à l’autre tant que
+/// l’objet n’est pas réécrit.
+const INLINE_STREAM: usize = 1 << 20;
+/// Début du contenu de remplacement d’un flux non chargé.
+const UNLOADED: &str = "%notar-verify: flux non chargé";
+/// Fenêtre de lecture initiale d’un objet, doublée tant qu’il est tronqué.
+const WINDOW: usize = 16 << 10;
+/// Octets examinés avant `%%EOF` pour trouver `startxref`.
+const TAIL: usize = 1024;
+
+/// Emplacement d’un objet d’après la table des références.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Free,
+    Offset { offset: usize, generation: u16 },
+    Compressed { container: u32, index: u16 },
+}
+
+/// Révision d’un PDF lue objet par objet, dans la limite d’un budget mémoire.
+pub struct LazyPdf<'a> {
+    src: &'a dyn ByteSource,
+    /// Fin de la révision (position juste après `%%EOF`).
+    end: usize,
+    slots: BTreeMap<u32, Slot>,
+    trailer: Dictionary,
+    budget: usize,
+    used: usize,
+}
+
+/// Le contenu de `stream` a-t-il été laissé sur le disque ?
+pub fn is_unloaded(stream: &Stream) -> bool {
+    stream.content.starts_with(UNLOADED.as_bytes())
+}
+
+impl<'a> LazyPdf<'a> {
+    /// Ouvre la révision qui se termine à `eof` : `startxref` précédant ce
+    /// `%%EOF`, puis sections de références chaînées par /Prev.
+    pub fn open(src: &'a dyn ByteSource, eof: usize, budget: usize) -> Result<Self> {
+        let mut pdf = LazyPdf {
+            src,
+            end: eof.min(src.len()),
+            slots: BTreeMap::new(),
+            trailer: Dictionary::new(),
+            budget,
+            used: 0,
+        };
+        let mut next = Some(pdf.startxref()?);
+        let mut seen = HashSet::new();
+        while let Some(offset) = next.take() {
+            if !seen.insert(offset) {
+                bail!("Table des références : cycle /Prev à l’offset {offset}");
+            }
+            let (section, trailer) = pdf
+                .xref_section(offset)
+                .with_context(|| format!("Table des références illisible à l’offset {offset}"))?;
+            // Les sections les plus récentes sont lues en premier et priment.
+            for (num, slot) in section {
+                pdf.slots.entry(num).or_insert(slot);
+            }
+            next = trailer
+                .get(b"Prev")
+                .and_then(Object::as_i64)
+                .ok()
+                .and_then(|p| usize::try_from(p).ok());
+            for (key, value) in trailer.iter() {
+                if !pdf.trailer.has(key) && key.as_slice() != b"Prev" {
+                    pdf.trailer.set(key.clone(), value.clone());
+                }
+            }
+        }
+        Ok(pdf)
+    }
+
+    /// Document partiel : tous les objets de la révision, sans le contenu des
+    /// gros flux. Les dictionnaires de signature, champs, DSS… y sont complets.
+    pub fn document(&mut self) -> Result<Document> {
+        let mut doc = Document::new();
+        doc.trailer = self.trailer.clone();
+
+        let slots: Vec<(u32, Slot)> = self.slots.iter().map(|(n, s)| (*n, *s)).collect();
+        let mut containers = BTreeMap::<u32, Vec<u32>>::new();
+        for (num, slot) in &slots {
+            if let Slot::Compressed { container, .. } = slot {
+                containers.entry(*container).or_default().push(*num);
+            }
+        }
+        for (num, slot) in slots {
+            match slot {
+                Slot::Free => {}
+                Slot::Offset { offset, generation } => {
+                    // Les flux d’objets sont chargés en entier pour être dépliés.
+                    let full = containers.contains_key(&num);
+                    let (id, obj) = match self.object_at(offset, full) {
+                        Ok(found) => found,
+                        Err(e) if e.is::<Budget>() => return Err(e),
+                        // Objet illisible : ignoré, comme au chargement en mémoire.
+                        Err(_) => continue,
+                    };
+                    if id != (num, generation) {
+                        continue;
+                    }
+                    if let Ok(offset) = u32::try_from(offset) {
+                        doc.reference_table
+                            .insert(num, XrefEntry::Normal { offset, generation });
+                    }
+                    doc.objects.insert(id, obj);
+                }
+                Slot::Compressed { container, index } => {
+                    doc.reference_table
+                        .insert(num, XrefEntry::Compressed { container, index });
+                }
+            }
+        }
+
+        for (container, members) in containers {
+            let Some(Object::Stream(stream)) = self
+                .slots
+                .get(&container)
+                .and_then(|s| match s {
+                    Slot::Offset { generation, .. } => doc.objects.get(&(container, *generation)),
+                    _ => None,
+                })
+                .cloned()
+            else {
+                continue;
+            };
+            for (id, obj) in self.unpack(stream)? {
+                if members.contains(&id.0) {
+                    doc.objects.entry(id).or_insert(obj);
+                }
+            }
+        }
+        doc.max_id = doc.objects.keys().map(|id| id.0).max().unwrap_or(0);
+        Ok(doc)
+    }
+
+    /// Position d’un objet (celle de son flux d’objets s’il est compressé).
+    pub fn object_offset(&self, num: u32) -> Option<usize> {
+        match self.slots.get(&num)? {
+            Slot::Offset { offset, .. } => Some(*offset),
+            Slot::Compressed { container, .. } => match self.slots.get(container)? {
+                Slot::Offset { offset, .. } => Some(*offset),
+                _ => None,
+            },
+            Slot::Free => None,
+        }
+    }
+
+    fn startxref(&self) -> Result<usize> {
+        let tail = self.src.read(self.end.saturating_sub(TAIL), self.end)?;
+        let at = tail
+            .windows(9)
+            .rposition(|w| w == b"startxref")
+            .context("startxref introuvable")?;
+        let mut parser = Parser::new(&tail, at + 9);
+        parser
+            .unsigned()
+            .ok()
+            .and_then(|o| usize::try_from(o).ok())
+            .context("startxref illisible")
+    }
+
+    /// Section de références à `offset` : table classique (avec /XRefStm
+    /// éventuel pour les fichiers hybrides) ou flux de références.
+    fn xref_section(&mut self, offset: usize) -> Result<(BTreeMap<u32, Slot>, Dictionary)> {
+        let head = self.src.read(offset, offset + 4)?;
+        if head.as_ref() != b"xref" {
+            let (_, obj) = self.object_at(offset, true)?;
+            let Object::Stream(stream) = obj else {
+                bail!("ni table xref ni flux /XRef");
+            };
+            let section = self.xref_stream(&stream)?;
+            return Ok((section, stream.dict));
+        }
+
+        let (mut section, trailer) = self.xref_table(offset + 4)?;
+        if let Ok(stm) = trailer.get(b"XRefStm").and_then(Object::as_i64) {
+            let stm = usize::try_from(stm).context("/XRefStm invalide")?;
+            if let (_, Object::Stream(stream)) = self.object_at(stm, true)? {
+                // Les objets compressés figurent comme libres dans la table.
+                for (num, slot) in self.xref_stream(&stream)? {
+                    let entry = section.entry(num).or_insert(Slot::Free);
+                    if *entry == Slot::Free {
+                        *entry = slot;
+                    }
+                }
+            }
+        }
+        Ok((section, trailer))
+    }
+
+    fn xref_table(&mut self, mut pos: usize) -> Result<(BTreeMap<u32, Slot>, Dictionary)> {
+        let mut section = BTreeMap::new();
+        loop {
+            let window = self.src.read(pos, pos + WINDOW)?;
+            let mut parser = Parser::new(&window, 0);
+            if parser.keyword(b"trailer").map_err(syntax)? {
+                let start = pos + parser.pos;
+                let Object::Dictionary(trailer) = self.parse_at(start, |p| p.object())?.0 else {
+                    bail!("trailer n’est pas un dictionnaire");
+                };
+                return Ok((section, trailer));
+            }
+            let first = parser.unsigned().map_err(syntax)?;
+            let count = parser.unsigned().map_err(syntax)?;
+            let first = u32::try_from(first).context("xref : numéro d’objet invalide")?;
+            let count = usize::try_from(count)?;
+            self.charge(count.saturating_mul(20))?;
+
+            // Entrées de 20 octets `oooooooooo ggggg n\r\n`, après la ligne d’en-tête.
+            let mut entries = pos + parser.pos;
+            let eol = self.src.read(entries, entries + 16)?;
+            entries += eol.iter().take_while(|b| b.is_ascii_whitespace()).count();
+            let table = self.src.read(entries, entries + count * 20)?;
+            if table.len() < count * 20 {
+                bail!("xref : sous-section tronquée");
+            }
+            for (i, entry) in table.chunks(20).enumerate() {
+                let field = |r: std::ops::Range<usize>| -> Option<u64> {
+                    std::str::from_utf8(&entry[r]).ok()?.trim().parse().ok()
+                };
+                let (Some(offset), Some(generation)) = (field(0..10), field(11..16)) else {
+                    bail!("xref : entrée illisible");
+                };
+                let num = first
+                    .checked_add(u32::try_from(i)?)
+                    .context("xref : numéro d’objet invalide")?;
+                let slot = match entry[17] {
+                    b'n' => Slot::Offset {
+                        offset: usize::try_from(offset)?,
+                        generation: u16::try_from(generation).unwrap_or(u16::MAX),
+                    },
+                    _ => Slot::Free,
+                };
+                section.insert(num, slot);
+            }
+            pos = entries + count * 20;
+        }
+    }
+
+    /// Entrées d’un flux de références (ISO 32000-2 §7.5.8).
+    fn xref_stream(&mut self, stream: &Stream) -> Result<BTreeMap<u32, Slot>> {
+        let data = stream
+            .decompressed_content()
+            .map_err(|e| anyhow!("flux /XRef illisible ({e})"))?;
+        self.charge(data.len())?;
+        let widths: Vec<usize> = stream
+            .dict
+            .get(b"W")
+            .and_then(Object::as_array)
+            .map_err(|_| anyhow!("flux /XRef sans /W"))?
+            .iter()
+            .map(|w| w.as_i64().ok().and_then(|w| usize::try_from(w).ok()))
+            .collect::<Option<_>>()
+            .filter(|w: &Vec<usize>| w.len() == 3 && w.iter().all(|n| *n <= 8))
+            .context("flux /XRef : /W invalide")?;
+        let size = stream
+            .dict
+            .get(b"Size")
+            .and_then(Object::as_i64)
+            .map_err(|_| anyhow!("flux /XRef sans /Size"))?;
+        let index: Vec<i64> = match stream.dict.get(b"Index").and_then(Object::as_array) {
+            Ok(items) => items.iter().filter_map(|i| i.as_i64().ok()).collect(),
+            Err(_) => vec![0, size],
+        };
+
+        let row = widths.iter().sum::<usize>();
+        if row == 0 {
+            bail!("flux /XRef : /W nul");
+        }
+        let mut rows = data.chunks_exact(row);
+        let mut section = BTreeMap::new();
+        for pair in index.chunks_exact(2) {
+            let first = u32::try_from(pair[0]).context("flux /XRef : /Index invalide")?;
+            for i in 0..u32::try_from(pair[1]).context("flux /XRef : /Index invalide")? {
+                let Some(row) = rows.next() else {
+                    return Ok(section);
+                };
+                let mut fields = [0u64; 3];
+                let mut at = 0;
+                for (field, width) in fields.iter_mut().zip(&widths) {
+                    *field = row[at..at + width]
+                        .iter()
+                        .fold(0, |acc, b| acc << 8 | u64::from(*b));
+                    at += width;
+                }
+                // Type absent (largeur 0) : 1 par défaut.
+                let kind = if widths[0] == 0 { 1 } else { fields[0] };
+                let slot = match kind {
+                    1 => Slot::Offset {
+                        offset: usize::try_from(fields[1])?,
+                        generation: u16::try_from(fields[2]).unwrap_or(u16::MAX),
+                    },
+                    2 => Slot::Compressed {
+                        container: u32::try_from(fields[1])?,
+                        index: u16::try_from(fields[2]).unwrap_or(u16::MAX),
+                    },
+                    _ => Slot::Free,
+                };
+                section.insert(first.saturating_add(i), slot);
+            }
+        }
+        Ok(section)
+    }
+
+    /// Objet indirect à `offset`. Le contenu d’un flux n’est lu que s’il est
+    /// petit ou si `full` est demandé (flux de références, flux d’objets).
+    fn object_at(&mut self, offset: usize, full: bool) -> Result<(ObjectId, Object)> {
+        let ((id, dict_or_value, stream_at), consumed) = self.parse_at(offset, |p| {
+            let id = p.indirect_header()?;
+            let value = p.object()?;
+            let stream = match value {
+                Object::Dictionary(_) if p.stream_start()? => Some(p.pos),
+                _ => None,
+            };
+            Ok((id, value, stream))
+        })?;
+        self.charge(consumed)?;
+
+        let (Some(start), Object::Dictionary(dict)) = (stream_at, &dict_or_value) else {
+            return Ok((id, dict_or_value));
+        };
+        let start = offset + start;
+        let length = match dict.get(b"Length") {
+            Ok(Object::Integer(n)) => *n,
+            Ok(Object::Reference(r)) => match self.slots.get(&r.0).copied() {
+                Some(Slot::Offset { offset, .. }) => self.object_at(offset, false)?.1.as_i64()?,
+                _ => bail!("/Length du flux {} {} R introuvable", id.0, id.1),
+            },
+            _ => bail!("/Length du flux {} {} R absent", id.0, id.1),
+        };
+        let length = usize::try_from(length).context("/Length négatif")?;
+        if start.saturating_add(length) > self.end {
+            bail!("flux {} {} R hors de la révision", id.0, id.1);
+        }
+        let content = if full || length <= INLINE_STREAM {
+            self.charge(length)?;
+            self.src.read(start, start + length)?.into_owned()
+        } else {
+            format!("{UNLOADED} ({length} octets à l’offset {start})").into_bytes()
+        };
+        let stream = Stream {
+            dict: dict.clone(),
+            content,
+            allows_compression: false,
+            start_position: Some(start),
+        };
+        Ok((id, Object::Stream(stream)))
+    }
+
+    /// Analyse à `offset`, la fenêtre grandissant tant que l’objet est
+    /// tronqué. Retourne aussi le nombre d’octets consommés.
+    fn parse_at<T>(
+        &mut self,
+        offset: usize,
+        f: impl Fn(&mut Parser) -> Result<T, SyntaxErr>,
+    ) -> Result<(T, usize)> {
+        let mut window = WINDOW;
+        loop {
+            let buf = self
+                .src
+                .read(offset, offset.saturating_add(window).min(self.end))?;
+            let mut parser = Parser::new(&buf, 0);
+            match f(&mut parser) {
+                Ok(value) => return Ok((value, parser.pos)),
+                Err(SyntaxErr::Truncated) if offset + buf.len() < self.end => {
+                    window = window.saturating_mul(2);
+                    if window > self.budget.saturating_sub(self.used) {
+                        return Err(Budget(self.budget).into());
+                    }
+                }
+                Err(e) => return Err(syntax(e)).context(format!("objet à l’offset {offset}")),
+            }
+        }
+    }
+
+    /// Objets d’un flux d’objets (décompressé et déplié par lopdf).
+    fn unpack(&mut self, mut stream: Stream) -> Result<BTreeMap<ObjectId, Object>> {
+        if stream.dict.has(b"Filter") {
+            let plain = stream
+                .decompressed_content()
+                .map_err(|e| anyhow!("flux d’objets illisible ({e})"))?;
+            self.charge(plain.len())?;
+            stream.dict.remove(b"Filter");
+            stream.dict.remove(b"DecodeParms");
+            stream.content = plain;
+        }
+        Ok(ObjectStream::new(&mut stream)
+            .map(|s| s.objects)
+            .unwrap_or_default())
+    }
+
+    fn charge(&mut self, bytes: usize) -> Result<()> {
+        self.used = self.used.saturating_add(bytes);
+        if self.used > self.budget {
+            return Err(Budget(self.budget).into());
+        }
+        Ok(())
+    }
+}
+
+/// Dépassement du budget mémoire de la lecture en flux.
+#[derive(thiserror::Error, Debug)]
+#[error("Budget mémoire dépassé ({0} octets) lors de la lecture en flux du PDF")]
+struct Budget(usize);
+
+fn syntax(e: SyntaxErr) -> anyhow::Error {
+    match e {
+        SyntaxErr::Truncated => anyhow!("syntaxe PDF tronquée"),
+        SyntaxErr::Invalid => anyhow!("syntaxe PDF invalide"),
+    }
+}
@@ -0,0 +1,162 @@
+use crate::pdf::diff::ObjectChange;
+use crate::pdf::fields::SigField;
+use crate::pdf::text::decode_text_string;
+use crate::report::{Modification, ModificationKind};
+use lopdf::{Dictionary, Document, Object};
+
+/// Permission appliquée en l’absence de signature de certification : les
+/// ajouts usuels (remplissage, signatures, annotations) restent admis, toute
+/// modification du contenu des pages ou du catalogue ne l’est pas.
+const DEFAULT_PERMISSION: u8 = 3;
+
+/// Verrou FieldMDP (/Action + /Fields).
+#[derive(Debug, Clone)]
+pub enum FieldLock {
+    All,
+    Include(Vec<String>),
+    Exclude(Vec<String>),
+}
+
+impl FieldLock {
+    fn from_params(doc: &Document, params: &Dictionary) -> Option<FieldLock> {
+        let names = || -> Vec<String> {
+            params
+                .get(b"Fields")
+                .and_then(|o| doc.dereference(o))
+                .and_then(|(_, o)| o.as_array())
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|n| n.as_str().ok())
+                        .map(decode_text_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        match params.get(b"Action").and_then(Object::as_name).ok()? {
+            b"All" => Some(FieldLock::All),
+            b"Include" => Some(FieldLock::Include(names())),
+            b"Exclude" => Some(FieldLock::Exclude(names())),
+            _ => None,
+        }
+    }
+
+    fn locks(&self, field: &str) -> bool {
+        match self {
+            FieldLock::All => true,
+            FieldLock::Include(names) => names.iter().any(|n| n == field),
+            FieldLock::Exclude(names) => !names.iter().any(|n| n == field),
+        }
+    }
+}
+
+/// Contraintes DocMDP/FieldMDP du document, indexées par révision signée.
+#[derive(Debug, Default)]
+pub struct MdpContext {
+    /// (révision, /P) de la signature de certification.
+    certification: Option<(usize, u8)>,
+    locks: Vec<(usize, FieldLock)>,
+}
+
+impl MdpContext {
+    /// `signed_revisions[i]` est la révision couverte par `fields[i]`.
+    pub fn new(doc: &Document, fields: &[SigField], signed_revisions: &[Option<usize>]) -> Self {
+        let mut ctx = MdpContext::default();
+        for (field, rev) in fields.iter().zip(signed_revisions) {
+            let Some(rev) = *rev else {
+                continue;
+            };
+            for (method, params) in transforms(doc, &field.sig_dict) {
+                match method {
+                    b"DocMDP" => {
+                        let p = params
+                            .and_then(|p| p.get(b"P").and_then(Object::as_i64).ok())
+                            .unwrap_or(2)
+                            .clamp(1, 3) as u8;
+                        // Seule la première certification (dans l’ordre des révisions) compte.
+                        if ctx.certification.map_or(true, |(r, _)| rev < r) {
+                            ctx.certification = Some((rev, p));
+                        }
+                    }
+                    b"FieldMDP" => {
+                        if let Some(lock) = params.and_then(|p| FieldLock::from_params(doc, p)) {
+                            ctx.locks.push((rev, lock));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(lock) = field
+                .lock
+                .as_ref()
+                .and_then(|l| FieldLock::from_params(doc, l))
+            {
+                ctx.locks.push((rev, lock));
+            }
+        }
+        ctx
+    }
+
+    /// Niveau DocMDP en vigueur pour les modifications postérieures à `revision`.
+    pub fn permission(&self, revision: usize) -> Option<u8> {
+        self.certification
+            .filter(|(r, _)| *r <= revision)
+            .map(|(_, p)| p)
+    }
+
+    /// Évalue les modifications faites après la révision `revision`.
+    pub fn evaluate(&self, revision: usize, changes: &[ObjectChange]) -> Vec<Modification> {
+        let p = self.permission(revision).unwrap_or(DEFAULT_PERMISSION);
+        changes
+            .iter()
+            .map(|c| {
+                let locked = c.field.as_deref().is_some_and(|f| {
+                    self.locks
+                        .iter()
+                        .any(|(r, lock)| *r <= revision && lock.locks(f))
+                });
+                Modification {
+                    object: format!("{} {} R", c.id.0, c.id.1),
+                    kind: c.kind,
+                    field: c.field.clone(),
+                    allowed: allowed(c.kind, p, locked),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Modifications admises selon ISO 32000-2 §12.8.2.2 ; le DSS et les
+/// horodatages de document restent permis quel que soit /P (PAdES LTV).
+fn allowed(kind: ModificationKind, p: u8, locked: bool) -> bool {
+    use ModificationKind::*;
+    match kind {
+        Dss | DocTimestamp => true,
+        Signature | Metadata => p >= 2,
+        FormFill => p >= 2 && !locked,
+        Annotation => p >= 3,
+        PageContent | Catalog | Other => false,
+    }
+}
+
+/// Entrées /Reference du dictionnaire de signature : (/TransformMethod, /TransformParams).
+pub fn transforms<'a>(
+    doc: &'a Document,
+    sig_dict: &'a Dictionary,
+) -> Vec<(&'a [u8], Option<&'a Dictionary>)> {
+    let Ok((_, Object::Array(refs))) = sig_dict.get(b"Reference").and_then(|r| doc.dereference(r))
+    else {
+        return Vec::new();
+    };
+    refs.iter()
+        .filter_map(|r| doc.dereference(r).ok()?.1.as_dict().ok())
+        .filter_map(|r| {
+            let method = r.get(b"TransformMethod").and_then(Object::as_name).ok()?;
+            let params = r
+                .get(b"TransformParams")
+                .and_then(|p| doc.dereference(p))
+                .and_then(|(_, p)| p.as_dict())
+                .ok();
+            Some((method, params))
+        })
+        .collect()
+}
@@ -0,0 +1,131 @@
+use crate::pdf::text::decode_text_string;
+use crate::report::{Component, ReportVerdict, SignatureMetadata, SignatureReport};
+use lopdf::{Dictionary, Document, Object};
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Duration, Month, OffsetDateTime, Time, UtcOffset};
+
+/// /M, /Name, /Reason, /Location et /ContactInfo du dictionnaire de signature.
+pub fn signature_metadata(doc: &Document, sig_dict: &Dictionary) -> SignatureMetadata {
+    let text = |key: &[u8]| raw_string(doc, sig_dict, key).map(decode_text_string);
+    SignatureMetadata {
+        claimed_time: raw_string(doc, sig_dict, b"M")
+            .and_then(parse_pdf_date)
+            .and_then(|t| t.format(&Rfc3339).ok()),
+        name: text(b"Name"),
+        reason: text(b"Reason"),
+        location: text(b"Location"),
+        contact_info: text(b"ContactInfo"),
+    }
+}
+
+/// Compare /M à l’heure signée (signingTime) et à l’horodatage de `sig` :
+/// un écart supérieur à `tolerance` est signalé (WARNING).
+pub fn check_claimed_time(
+    doc: &Document,
+    sig_dict: &Dictionary,
+    sig: &SignatureReport,
+    tolerance: Duration,
+) -> Component {
+    let Some(raw) = raw_string(doc, sig_dict, b"M") else {
+        return Component {
+            status: ReportVerdict::Valid,
+            detail: "/M absent : rien à comparer".into(),
+        };
+    };
+    let Some(claimed) = parse_pdf_date(raw) else {
+        return Component {
+            status: ReportVerdict::Warning,
+            detail: format!("/M illisible (« {} »)", String::from_utf8_lossy(raw)),
+        };
+    };
+
+    let references: Vec<(&str, OffsetDateTime)> = [
+        ("signingTime", sig.signing_time.as_deref()),
+        ("l’horodatage", sig.timestamp_rfc3161.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(what, t)| Some((what, OffsetDateTime::parse(t?, &Rfc3339).ok()?)))
+    .collect();
+    if references.is_empty() {
+        return Component {
+            status: ReportVerdict::Valid,
+            detail: "Aucune heure signée à comparer à /M".into(),
+        };
+    }
+
+    let gaps: Vec<String> = references
+        .iter()
+        .filter_map(|(what, t)| {
+            let gap = (claimed - *t).abs();
+            (gap > tolerance).then(|| format!("{} s avec {what}", gap.whole_seconds()))
+        })
+        .collect();
+    if gaps.is_empty() {
+        Component {
+            status: ReportVerdict::Valid,
+            detail: format!(
+                "/M cohérent avec {} (tolérance {} s)",
+                references
+                    .iter()
+                    .map(|(what, _)| *what)
+                    .collect::<Vec<_>>()
+                    .join(" et "),
+                tolerance.whole_seconds()
+            ),
+        }
+    } else {
+        Component {
+            status: ReportVerdict::Warning,
+            detail: format!(
+                "/M s’écarte de {} (tolérance {} s)",
+                gaps.join(", "),
+                tolerance.whole_seconds()
+            ),
+        }
+    }
+}
+
+/// Date PDF (ISO 32000-2 §7.9.4) : `D:YYYYMMDDHHmmSSOHH'mm'`, champs
+/// terminaux facultatifs ; sans décalage, l’heure est prise en UTC.
+pub fn parse_pdf_date(raw: &[u8]) -> Option<OffsetDateTime> {
+    let s = std::str::from_utf8(raw).ok()?.trim();
+    let s = s.strip_prefix("D:").unwrap_or(s);
+    let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+    if !(4..=14).contains(&digits) || digits % 2 != 0 {
+        return None;
+    }
+    let (num, tz) = s.split_at(digits);
+    let field = |at: usize, default: u8| -> Option<u8> {
+        num.get(at..at + 2)
+            .map_or(Some(default), |v| v.parse().ok())
+    };
+
+    let date = Date::from_calendar_date(
+        num[..4].parse().ok()?,
+        Month::try_from(field(4, 1)?).ok()?,
+        field(6, 1)?,
+    )
+    .ok()?;
+    let time = Time::from_hms(field(8, 0)?, field(10, 0)?, field(12, 0)?).ok()?;
+
+    let offset = match tz.as_bytes().first() {
+        None | Some(b'Z') => UtcOffset::UTC,
+        Some(sign @ (b'+' | b'-')) => {
+            let hm: String = tz[1..].chars().filter(char::is_ascii_digit).collect();
+            let hours: i8 = hm.get(..2)?.parse().ok()?;
+            let minutes: i8 = hm.get(2..4).map_or(Some(0), |m| m.parse().ok())?;
+            let sign = if *sign == b'-' { -1 } else { 1 };
+            UtcOffset::from_hms(sign * hours, sign * minutes, 0).ok()?
+        }
+        Some(_) => return None,
+    };
+    Some(date.with_time(time).assume_offset(offset))
+}
+
+fn raw_string<'a>(doc: &'a Document, dict: &'a Dictionary, key: &[u8]) -> Option<&'a [u8]> {
+    let (_, obj) = doc.dereference(dict.get(key).ok()?).ok()?;
+    match obj {
+        Object::String(s, _) => Some(s),
+        _ => None,
+    }
+}
@@ -1 +1,23 @@
+pub mod baseline;
+pub mod byterange;
+pub mod bytes;
+pub mod contents;
+pub mod crypt;
+pub mod decode;
+pub mod diff;
+pub mod dss;
+pub mod embedded;
+pub mod fields;
+pub mod lazy;
+pub mod mdp;
+pub mod metadata;
 pub mod pades;
+pub mod perms;
+pub mod revisions;
+pub mod scan;
+pub mod shadow;
+pub mod source;
+pub mod subfilter;
+pub mod syntax;
+pub mod text;
+pub mod widget;
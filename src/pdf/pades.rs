@@ -1,9 +1,46 @@
-use crate::cms::verify::verify_cms_entrypoint;
-use crate::infra::fs::{read_file_bounded, Limits};
-use crate::report::{final_verdict, Component, Report, ReportVerdict};
-use anyhow::{Context, Result};
-use lopdf::{Document, Object, ObjectId};
-use sha2::{Digest, Sha256};
+use crate::cms::digest::DigestAlgorithm;
+use crate::cms::signed_data::CmsSignedData;
+use crate::cms::tsp::TimeStampToken;
+use crate::cms::verify::{
+    verify_cms_bytes, verify_cms_der, verify_cms_digests, verify_cms_sha1_digest,
+    verify_pkcs1_sha1, verify_timestamp_token, TrustStore, ValidationTime,
+};
+use crate::infra::fs::{open_file_bounded, Limits};
+use crate::pdf::baseline::classify_baseline;
+use crate::pdf::byterange::{concat_ranges, digest_ranges, parse_byterange, validate_byterange};
+use crate::pdf::bytes::find;
+use crate::pdf::contents::inspect_contents;
+use crate::pdf::crypt::decrypt_document;
+use crate::pdf::diff::diff_revisions;
+use crate::pdf::dss::{read_dss, Dss};
+use crate::pdf::embedded::attachments;
+use crate::pdf::fields::{pending_signature_fields, signature_fields, SigField};
+use crate::pdf::lazy::LazyPdf;
+use crate::pdf::mdp::MdpContext;
+use crate::pdf::metadata::{check_claimed_time, signature_metadata};
+use crate::pdf::perms::{check_xfa, is_certification, usage_rights};
+use crate::pdf::revisions::{locate_signature, revision_at, split_revisions, Revision};
+use crate::pdf::scan::scan_signatures;
+use crate::pdf::shadow::detect_shadow;
+use crate::pdf::source::{ByteSource, FileSource};
+use crate::pdf::subfilter::{cert_entries, pkcs1_signature_value, SubFilter};
+use crate::pdf::widget::describe_widgets;
+use crate::report::{
+    aggregate_signatures, final_verdict, signature_verdict, Component, EmbeddedFile, PendingField,
+    Report, ReportVerdict, RevisionInfo, SignatureReport,
+};
+use crate::revocation::{assess_chain, ValidationMaterial};
+use crate::x509::chain::anchor_certificates;
+use anyhow::{bail, Context, Result};
+use der::Decode;
+use lopdf::{Document, Object};
+use std::io::Write;
+use time::Duration;
+use tracing::{info, warn};
+use x509_cert::Certificate;
+
+/// Imbrication max des pièces jointes vérifiées (portfolio dans un portfolio…).
+const MAX_EMBEDDED_DEPTH: usize = 4;
 
 #[derive(thiserror::Error, Debug)]
 pub enum PdfErr {
@@ -13,6 +50,187 @@ pub enum PdfErr {
     ByteRange,
     #[error("Contents manquant")]
     Contents,
+    #[error("Signature n°{0} introuvable ({1} signature(s) dans le document)")]
+    SignatureNumber(usize, usize),
+}
+
+/// Réglages de la vérification PDF.
+#[derive(Debug, Clone)]
+pub struct PdfOptions {
+    /// Écart toléré entre /M et l’heure signée ou l’horodatage.
+    pub time_tolerance: Duration,
+    /// Mot de passe (utilisateur ou propriétaire) des PDF chiffrés ; vide par
+    /// défaut, ce qui ouvre les documents sans mot de passe d’ouverture.
+    pub password: String,
+    /// Effet sur le verdict des champs de signature restés vides.
+    pub unsigned_fields: UnsignedFieldPolicy,
+    /// Vérifier aussi les pièces jointes (/EmbeddedFiles, portfolios).
+    pub embedded: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            time_tolerance: Duration::minutes(5),
+            password: String::new(),
+            unsigned_fields: UnsignedFieldPolicy::Ignore,
+            embedded: false,
+        }
+    }
+}
+
+/// Politique appliquée aux champs de signature sans /V : toujours listés dans
+/// le rapport, ils rendent au plus le verdict WARNING ou INVALID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum UnsignedFieldPolicy {
+    Ignore,
+    Warning,
+    Invalid,
+}
+
+impl UnsignedFieldPolicy {
+    fn status(self) -> ReportVerdict {
+        match self {
+            UnsignedFieldPolicy::Ignore => ReportVerdict::Valid,
+            UnsignedFieldPolicy::Warning => ReportVerdict::Warning,
+            UnsignedFieldPolicy::Invalid => ReportVerdict::Invalid,
+        }
+    }
+}
+
+/// Octets du PDF : image complète en mémoire, ou fichier lu à la demande.
+enum PdfBytes {
+    Memory(Vec<u8>),
+    File(FileSource),
+}
+
+/// PDF ouvert : image complète en mémoire s’il tient dans le budget, sinon lu
+/// en flux depuis le disque (document partiel, sans le contenu des gros flux).
+struct OpenPdf {
+    bytes: PdfBytes,
+    doc: Document,
+    revisions: Vec<Revision>,
+    dss: Option<Dss>,
+    /// Raison pour laquelle la structure (xref, objets) n’a pu être lue ; les
+    /// signatures sont alors repérées par balayage des octets et `doc` est vide.
+    degraded: Option<String>,
+}
+
+impl OpenPdf {
+    fn open(pdf_path: &str, limits: &Limits, options: &PdfOptions) -> Result<OpenPdf> {
+        let file = FileSource::new(open_file_bounded(pdf_path, limits)?)?;
+        if limits.fits_in_memory(file.len() as u64) {
+            return OpenPdf::from_memory(file.read(0, file.len())?.into_owned(), options);
+        }
+
+        info!(
+            "PDF de {} octets au-delà du budget mémoire : lecture en flux",
+            file.len()
+        );
+        let revisions = split_revisions(&file)?;
+        let parsed = revisions
+            .last()
+            .context("aucune révision terminée par startxref/%%EOF")
+            .and_then(|last| {
+                let mut lazy = LazyPdf::open(&file, last.eof, limits.memory_budget())?;
+                let doc = lazy.document()?;
+                // La table de lopdf ne porte que des offsets 32 bits : position relue.
+                let dss = read_dss(&doc).map(|mut dss| {
+                    dss.offset = lazy.object_offset(dss.id.0);
+                    dss
+                });
+                Ok((doc, dss))
+            });
+        let mut pdf = OpenPdf {
+            bytes: PdfBytes::File(file),
+            doc: Document::new(),
+            revisions,
+            dss: None,
+            degraded: None,
+        };
+        match parsed {
+            Ok((doc, _)) if doc.is_encrypted() => bail!(
+                "PDF chiffré : la lecture en flux ne déchiffre pas ; relancer avec --max-memory-mib {}",
+                (pdf.source().len() >> 20) + 1
+            ),
+            Ok((doc, dss)) => {
+                pdf.doc = doc;
+                pdf.dss = dss;
+            }
+            Err(e) => pdf.degrade(format!("Lecture en flux du PDF a échoué : {e:#}")),
+        }
+        Ok(pdf)
+    }
+
+    /// PDF déjà en mémoire (fichier lu en entier ou pièce jointe).
+    fn from_memory(bytes: Vec<u8>, options: &PdfOptions) -> Result<OpenPdf> {
+        let mut pdf = OpenPdf {
+            revisions: split_revisions(&bytes)?,
+            doc: Document::new(),
+            dss: None,
+            degraded: None,
+            bytes: PdfBytes::Memory(Vec::new()),
+        };
+        match Document::load_mem(&bytes) {
+            Ok(doc) => {
+                pdf.doc = decrypt_if_needed(doc, &bytes, options)?;
+                pdf.dss = read_dss(&pdf.doc);
+            }
+            Err(e) => pdf.degrade(format!("Chargement PDF a échoué : {e}")),
+        }
+        pdf.bytes = PdfBytes::Memory(bytes);
+        Ok(pdf)
+    }
+
+    fn degrade(&mut self, reason: String) {
+        warn!("{reason} ; signatures repérées par balayage des octets");
+        self.degraded = Some(reason);
+    }
+
+    /// Champs de signature signés : AcroForm, ou balayage des octets si la
+    /// structure est illisible.
+    fn signature_fields(&self) -> Result<Vec<SigField>> {
+        match self.degraded {
+            Some(_) => scan_signatures(self.source()),
+            None => Ok(signature_fields(&self.doc)),
+        }
+    }
+
+    /// Octets du fichier, sur lesquels portent les ByteRange.
+    fn source(&self) -> &dyn ByteSource {
+        match &self.bytes {
+            PdfBytes::Memory(bytes) => bytes,
+            PdfBytes::File(file) => file,
+        }
+    }
+
+    /// Image complète du fichier ; `None` en lecture en flux.
+    fn memory(&self) -> Option<&[u8]> {
+        match &self.bytes {
+            PdfBytes::Memory(bytes) => Some(bytes),
+            PdfBytes::File(_) => None,
+        }
+    }
+}
+
+/// Données communes à toutes les signatures d’un même PDF.
+struct PdfContext<'a> {
+    source: &'a dyn ByteSource,
+    /// Image complète du fichier ; `None` en lecture en flux.
+    memory: Option<&'a [u8]>,
+    /// Budget mémoire des révisions relues en flux.
+    budget: usize,
+    doc: &'a Document,
+    options: &'a PdfOptions,
+    revisions: Vec<Revision>,
+    mdp: MdpContext,
+    dss: Option<Dss>,
+    /// Structure illisible : modifications et shadow non évaluables.
+    degraded: bool,
+    anchors_pem: &'a [String],
+    anchors: Vec<Certificate>,
+    /// CRL/OCSP fournis en fichiers (`--crl`, `--ocsp`).
+    external: &'a ValidationMaterial,
 }
 
 pub fn verify_pdf_pades(
@@ -20,15 +238,326 @@ pub fn verify_pdf_pades(
     anchors_pem: &[String],
     crl: &[String],
     ocsp: &[String],
-    online: bool,
+    _online: bool,
+    limits: &Limits,
+    options: &PdfOptions,
+) -> Result<Report> {
+    let pdf = OpenPdf::open(pdf_path, limits, options)?;
+    let external = ValidationMaterial::from_files(crl, ocsp, limits)?;
+    verify_document(&pdf, anchors_pem, &external, limits, options, 0)
+}
+
+/// Vérifie toutes les signatures d’un PDF ouvert ; `depth` est le niveau
+/// d’imbrication d’une pièce jointe (0 pour le document lui-même).
+fn verify_document(
+    pdf: &OpenPdf,
+    anchors_pem: &[String],
+    external: &ValidationMaterial,
     limits: &Limits,
+    options: &PdfOptions,
+    depth: usize,
 ) -> Result<Report> {
-    let pdf_bytes = read_file_bounded(pdf_path, limits)?;
-    let doc = Document::load_mem(&pdf_bytes).context("Chargement PDF a échoué")?;
+    let doc = &pdf.doc;
+
+    let fields = pdf.signature_fields()?;
+    let pending: Vec<PendingField> = pending_signature_fields(doc)
+        .iter()
+        .map(|f| {
+            let widgets = describe_widgets(doc, &f.widgets);
+            PendingField {
+                field_name: f.name.clone(),
+                page: widgets.iter().find_map(|w| w.page),
+                widgets,
+            }
+        })
+        .collect();
+    let embedded = if options.embedded && depth < MAX_EMBEDDED_DEPTH {
+        verify_attachments(doc, anchors_pem, external, limits, options, depth)
+    } else {
+        Vec::new()
+    };
+    let rights = usage_rights(doc);
+    if fields.is_empty() && embedded.is_empty() {
+        let ur3 = if rights.is_some() {
+            " ; seule une signature de droits d’usage (UR3), qui n’engage aucun signataire"
+        } else {
+            ""
+        };
+        return Err(PdfErr::Signature).context(format!(
+            "Aucune signature PDF détectée ({} champ(s) de signature vide(s){ur3})",
+            pending.len()
+        ));
+    }
+
+    let signed_revisions: Vec<Option<usize>> = fields
+        .iter()
+        .map(|f| covered_end(&f.sig_dict).and_then(|end| revision_at(&pdf.revisions, end)))
+        .collect();
+    let source = pdf.source();
+    let ctx = PdfContext {
+        source,
+        memory: pdf.memory(),
+        budget: limits.memory_budget(),
+        doc,
+        options,
+        mdp: MdpContext::new(doc, &fields, &signed_revisions),
+        revisions: pdf.revisions.clone(),
+        dss: pdf.dss.clone(),
+        degraded: pdf.degraded.is_some(),
+        anchors_pem,
+        anchors: anchor_certificates(anchors_pem),
+        external,
+    };
+
+    let whole = digest_ranges(source, &[(0, source.len())], &[DigestAlgorithm::Sha256])?;
+    let mut report = Report {
+        input_kind: "PDF".into(),
+        document_sha256: whole.get(DigestAlgorithm::Sha256).map(hex::encode),
+        ..Default::default()
+    };
+
+    for (index, field) in fields.iter().enumerate() {
+        let mut sig = SignatureReport {
+            index,
+            field_name: field.name.clone(),
+            widgets: describe_widgets(doc, &field.widgets),
+            certification: is_certification(doc, &field.sig_dict),
+            ..Default::default()
+        };
+        if let Err(e) = verify_signature_field(field, &mut sig, &ctx) {
+            sig.integrity = Component {
+                status: ReportVerdict::Invalid,
+                detail: format!("{e:#}"),
+            };
+        }
+        signature_verdict(&mut sig);
+        report.signatures.push(sig);
+    }
 
-    let (_sig_obj_id, sig_dict) = find_signature_dict(&doc)
-        .map_err(|_| PdfErr::Signature)
-        .context("Aucune signature PDF détectée")?;
+    // Niveaux PAdES baseline : B-T et B-LTA dépendent des horodatages de
+    // document postérieurs, connus seulement une fois toutes les signatures vues.
+    let contents: Vec<Vec<u8>> = fields
+        .iter()
+        .map(|f| {
+            f.sig_dict
+                .get(b"Contents")
+                .ok()
+                .and_then(|c| extract_contents(c).ok())
+                .unwrap_or_default()
+        })
+        .collect();
+    classify_baseline(
+        &mut report.signatures,
+        &contents,
+        ctx.dss.as_ref().and_then(|d| d.offset),
+        &TrustStore {
+            anchors_pem: ctx.anchors_pem,
+            intermediates: &[],
+        },
+    );
+
+    report.unsigned_fields = Some(unsigned_fields(&pending, options.unsigned_fields));
+    report.structure = Some(match &pdf.degraded {
+        None => Component {
+            status: ReportVerdict::Valid,
+            detail: format!("Structure PDF lue ({} révision(s))", pdf.revisions.len()),
+        },
+        Some(reason) => Component {
+            status: ReportVerdict::Warning,
+            detail: format!(
+                "Analyse structurelle dégradée ({reason}) : {} signature(s) repérée(s) par \
+                 balayage des octets ; champs, DocMDP/FieldMDP, shadow et DSS non évalués",
+                fields.len()
+            ),
+        },
+    });
+    report.pending_fields = pending;
+    report.embedded = embedded;
+    report.usage_rights = rights;
+    report.xfa = check_xfa(doc, limits.memory_budget());
+
+    aggregate_signatures(&mut report);
+    if report.signatures.is_empty() {
+        // Portfolio non signé : seuls ses membres portent des signatures.
+        let unsigned = Component {
+            status: ReportVerdict::Warning,
+            detail: "Document non signé : seules les pièces jointes sont vérifiées".into(),
+        };
+        report.integrity = unsigned.clone();
+        report.signature = unsigned.clone();
+        report.chain = unsigned.clone();
+        report.revocation = unsigned.clone();
+        report.ltv = unsigned;
+    }
+    final_verdict(&mut report);
+    Ok(report)
+}
+
+/// Vérifie chaque pièce jointe : PDF (récursivement) ou signature CMS/P7S,
+/// détachée du fichier de même nom sans l’extension `.p7s`. Les autres
+/// fichiers et les PDF non signés sont seulement listés.
+fn verify_attachments(
+    doc: &Document,
+    anchors_pem: &[String],
+    external: &ValidationMaterial,
+    limits: &Limits,
+    options: &PdfOptions,
+    depth: usize,
+) -> Vec<EmbeddedFile> {
+    let files = attachments(doc, limits.memory_budget());
+    files
+        .iter()
+        .map(|file| {
+            let mut entry = EmbeddedFile {
+                name: file.name.clone(),
+                ..Default::default()
+            };
+            let data = match &file.data {
+                Ok(data) => data,
+                Err(e) => {
+                    entry.error = Some(format!("{e:#}"));
+                    return entry;
+                }
+            };
+            entry.size = data.len();
+            let result = if is_pdf(data) {
+                OpenPdf::from_memory(data.clone(), options).and_then(|pdf| {
+                    verify_document(&pdf, anchors_pem, external, limits, options, depth + 1)
+                })
+            } else if is_cms(&file.name, data) {
+                let detached = detached_name(&file.name).and_then(|name| {
+                    files
+                        .iter()
+                        .find(|f| f.name == name)
+                        .and_then(|f| f.data.as_deref().ok())
+                });
+                verify_cms_bytes(data, detached, anchors_pem, external)
+            } else {
+                return entry;
+            };
+            match result {
+                Ok(report) => entry.report = Some(report),
+                // PDF sans signature : listé, sans effet sur le verdict.
+                Err(e) if matches!(e.downcast_ref(), Some(PdfErr::Signature)) => {}
+                Err(e) => entry.error = Some(format!("{e:#}")),
+            }
+            entry
+        })
+        .collect()
+}
+
+fn is_pdf(data: &[u8]) -> bool {
+    find(&data[..data.len().min(1024)], b"%PDF-").is_some()
+}
+
+fn is_cms(name: &str, data: &[u8]) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.ends_with(".p7s") || name.ends_with(".p7m") || data.starts_with(b"-----BEGIN PKCS7")
+}
+
+/// `acte.pdf.p7s` signe `acte.pdf`.
+fn detached_name(name: &str) -> Option<&str> {
+    let stem = name.len().checked_sub(4)?;
+    (name.is_char_boundary(stem) && name[stem..].eq_ignore_ascii_case(".p7s"))
+        .then(|| &name[..stem])
+}
+
+/// Évalue les champs de signature vides selon `policy`.
+fn unsigned_fields(pending: &[PendingField], policy: UnsignedFieldPolicy) -> Component {
+    if pending.is_empty() {
+        return Component {
+            status: ReportVerdict::Valid,
+            detail: "Tous les champs de signature sont signés".into(),
+        };
+    }
+    let names: Vec<&str> = pending
+        .iter()
+        .map(|f| f.field_name.as_deref().unwrap_or("sans nom"))
+        .collect();
+    let mut detail = format!(
+        "{} champ(s) de signature vide(s) : {}",
+        pending.len(),
+        names.join(", ")
+    );
+    if policy == UnsignedFieldPolicy::Ignore {
+        detail.push_str(" (sans effet sur le verdict, voir --unsigned-fields)");
+    }
+    Component {
+        status: policy.status(),
+        detail,
+    }
+}
+
+/// Écrit dans `out` le document tel que signé par la signature n° `number` (à
+/// partir de 1, ordre AcroForm comme dans le rapport) : `[0, fin du dernier
+/// ByteRange)`, recopié par blocs. Retourne le nombre d’octets écrits.
+pub fn extract_signed_revision(
+    pdf_path: &str,
+    number: usize,
+    limits: &Limits,
+    options: &PdfOptions,
+    out: &mut dyn Write,
+) -> Result<usize> {
+    let pdf = OpenPdf::open(pdf_path, limits, options)?;
+    let source = pdf.source();
+
+    let fields = pdf.signature_fields()?;
+    let field = number
+        .checked_sub(1)
+        .and_then(|i| fields.get(i))
+        .ok_or(PdfErr::SignatureNumber(number, fields.len()))?;
+    let byte_range_obj = field
+        .sig_dict
+        .get(b"ByteRange")
+        .map_err(|_| PdfErr::ByteRange)?;
+    let br = parse_byterange(byte_range_obj).context("ByteRange invalide")?;
+    let end = br.last().map_or(0, |(off, len)| off.saturating_add(*len));
+    if end == 0 || end > source.len() {
+        return Err(PdfErr::ByteRange).context("Couverture ByteRange hors du fichier");
+    }
+
+    let contents = field
+        .sig_dict
+        .get(b"Contents")
+        .ok()
+        .and_then(|c| extract_contents(c).ok())
+        .unwrap_or_default();
+    for issue in validate_byterange(source, &br, &contents)? {
+        warn!("Signature n°{number} : {issue}");
+    }
+
+    let mut written = Ok(());
+    source.for_each_chunk(0, end, &mut |chunk| {
+        written = out.write_all(chunk);
+        written.is_ok()
+    })?;
+    written.context("Écriture de la révision signée a échoué")?;
+    Ok(end)
+}
+
+/// Charge un PDF (ou une révision) et déchiffre ses objets s’il est protégé ;
+/// `bytes` reste l’image brute du fichier sur laquelle portent les ByteRange.
+fn load_document(bytes: &[u8], options: &PdfOptions) -> Result<Document> {
+    let doc = Document::load_mem(bytes).context("Chargement PDF a échoué")?;
+    decrypt_if_needed(doc, bytes, options)
+}
+
+fn decrypt_if_needed(mut doc: Document, bytes: &[u8], options: &PdfOptions) -> Result<Document> {
+    if doc.is_encrypted() {
+        decrypt_document(&mut doc, bytes, options.password.as_bytes())
+            .context("Déchiffrement PDF a échoué")?;
+    }
+    Ok(doc)
+}
+
+/// Vérifie une signature (ByteRange, CMS, chaîne, révocation) et renseigne `sig`.
+/// Une erreur structurelle (ByteRange/Contents) est remontée à l’appelant.
+fn verify_signature_field(
+    field: &SigField,
+    sig: &mut SignatureReport,
+    ctx: &PdfContext,
+) -> Result<()> {
+    let sig_dict = &field.sig_dict;
 
     // API lopdf (Result<&Object, Error>)
     let byte_range_obj = sig_dict.get(b"ByteRange").map_err(|_| PdfErr::ByteRange)?;
@@ -36,77 +565,378 @@ pub fn verify_pdf_pades(
 
     let contents_obj = sig_dict.get(b"Contents").map_err(|_| PdfErr::Contents)?;
     let cms_blob = extract_contents(contents_obj).context("Contents invalide")?;
+    let (usage, padding) = inspect_contents(&cms_blob);
+    sig.contents = Some(usage);
+    sig.padding = padding;
 
-    // Intégrité: recomposer les segments ByteRange et hasher
-    let digest_doc = sha256_over_ranges(&pdf_bytes, &br)?;
-    let document_sha256 = hex::encode(digest_doc);
-
-    // Écriture temporaire de la signature CMS (simplifie l’entrypoint CMS commun)
-    let tmp_sig = tempfile::NamedTempFile::new().context("tmp sig")?;
-    std::fs::write(tmp_sig.path(), &cms_blob).context("Écriture tmp sig")?;
+    let issues = validate_byterange(ctx.source, &br, &cms_blob)?;
 
-    let mut report = verify_cms_entrypoint(
-        tmp_sig.path().to_string_lossy().as_ref(),
-        Some(pdf_path), // PAdES : sémantique “detached” via ByteRange
-        anchors_pem,
-        crl,
-        ocsp,
-        online,
-        limits,
+    // Intégrité : empreintes des segments ByteRange, lus plage par plage ; ce
+    // sont exactement ces octets (et non le fichier entier) que couvre la
+    // signature détachée.
+    let sub_filter = SubFilter::of(sig_dict);
+    let digests = digest_ranges(
+        ctx.source,
+        &br,
+        &required_digests(&sub_filter, &cms_blob, ctx.memory.is_none()),
     )?;
+    sig.byte_range_sha256 = digests.get(DigestAlgorithm::Sha256).map(hex::encode);
+    let covered_end = br.last().map_or(0, |(off, len)| off + len);
+    let revision = locate_signature(ctx.source, &ctx.revisions, covered_end)?;
 
-    report.input_kind = "PDF".into();
-    report.integrity = Component {
-        status: ReportVerdict::Valid,
-        detail: "ByteRange cohérent, hash recomposé OK".into(),
+    sig.metadata = signature_metadata(ctx.doc, sig_dict);
+
+    // Matériel de validation embarqué : DSS commun + entrée VRI de la signature.
+    let (embedded, vri) = match &ctx.dss {
+        Some(dss) => {
+            let (material, key) = dss.material_for(&cms_blob);
+            (Some(material), key)
+        }
+        None => (None, None),
+    };
+    sig.vri = vri;
+    let trust = TrustStore {
+        anchors_pem: ctx.anchors_pem,
+        intermediates: embedded.as_ref().map_or(&[], |m| &m.certs),
     };
-    report.document_sha256 = Some(document_sha256);
 
-    // LTV/DSS (MVP : détection)
-    if let Some(_dss) = find_dss(&doc) {
-        report.ltv = Component {
+    // Sémantique de vérification propre à chaque /SubFilter.
+    sig.sub_filter = sub_filter.name().map(str::to_owned);
+    let x509_certs = cert_entries(ctx.doc, sig_dict);
+    let crypto = match &sub_filter {
+        SubFilter::Pkcs7Detached | SubFilter::CadesDetached => match ctx.memory {
+            Some(bytes) => {
+                let signed = concat_ranges(bytes, &br)?;
+                Some(verify_cms_der(&cms_blob, Some(&signed), &trust)?)
+            }
+            // Lecture en flux : contenu connu par son empreinte seule.
+            None => Some(verify_cms_digests(&cms_blob, &digests, &trust)?),
+        },
+        SubFilter::Pkcs7Sha1 => Some(verify_cms_sha1_digest(&cms_blob, &digests, &trust)?),
+        SubFilter::X509RsaSha1 => {
+            let value = pkcs1_signature_value(&cms_blob)
+                .context("Contents adbe.x509.rsa_sha1 : OCTET STRING DER attendue")?;
+            Some(verify_pkcs1_sha1(value, &x509_certs, &digests, &trust)?)
+        }
+        SubFilter::Rfc3161 => Some(verify_timestamp_token(&cms_blob, &digests, &trust)?),
+        SubFilter::Unknown(name) => {
+            sig.signature = Component {
+                status: ReportVerdict::Warning,
+                detail: format!(
+                    "Indéterminé : SubFilter {} non pris en charge, signature non vérifiée",
+                    name.as_deref()
+                        .map_or("absent".to_string(), |n| format!("/{n}"))
+                ),
+            };
+            None
+        }
+    };
+    // Attributs signés non conformes aux octets couverts (messageDigest,
+    // contentType), ou non confrontés à une signature par ailleurs valide :
+    // défaut d’intégrité propre à la CMS.
+    let attributes = crypto
+        .as_ref()
+        .filter(|cms| match cms.integrity.status {
+            ReportVerdict::Invalid => true,
+            ReportVerdict::Warning => cms.signature.status == ReportVerdict::Valid,
+            ReportVerdict::Valid => false,
+        })
+        .map(|cms| cms.integrity.clone());
+    match crypto {
+        Some(cms) => {
+            sig.signer_dn = cms.signer_dn;
+            sig.certificate_chain = cms.certificate_chain;
+            sig.signing_time = cms.signing_time;
+            sig.cms = cms.cms;
+            sig.timestamp_rfc3161 = cms.timestamp_rfc3161;
+            sig.signature = cms.signature;
+            sig.chain = cms.chain;
+            assess_revocation(
+                ctx,
+                sig,
+                &sub_filter,
+                &cms_blob,
+                &x509_certs,
+                embedded.as_ref(),
+            );
+        }
+        None => {
+            let not_assessed = Component {
+                status: ReportVerdict::Warning,
+                detail: "Non évaluée (signature non vérifiée)".into(),
+            };
+            sig.chain = not_assessed.clone();
+            sig.revocation = not_assessed.clone();
+            sig.ltv = not_assessed;
+        }
+    }
+    sig.claimed_time = check_claimed_time(ctx.doc, sig_dict, sig, ctx.options.time_tolerance);
+    sig.docmdp = revision
+        .signed_revision
+        .and_then(|r| ctx.mdp.permission(r - 1));
+    let after = if revision.modified_after && ctx.degraded {
+        let unassessed = |detail: &str| Component {
             status: ReportVerdict::Warning,
-            detail: "DSS présent (exploitation CRL/OCSP embarqués à implémenter)".into(),
+            detail: detail.into(),
         };
+        sig.shadow = unassessed("Non évalué : structure PDF illisible");
+        Some(unassessed(
+            "Document modifié après signature ; structure PDF illisible, modifications non évaluées",
+        ))
+    } else if revision.modified_after {
+        assess_modifications(ctx, &revision, sig)
     } else {
-        report.ltv = Component {
-            status: ReportVerdict::Warning,
-            detail: "DSS absent".into(),
+        sig.shadow = Component {
+            status: ReportVerdict::Valid,
+            detail: "Aucune révision postérieure à la signature".into(),
+        };
+        None
+    };
+    sig.integrity = if let (true, Some(after)) = (issues.is_empty(), after) {
+        after
+    } else if issues.is_empty() {
+        Component {
+            status: ReportVerdict::Valid,
+            detail: "ByteRange cohérent, hash recomposé OK".into(),
+        }
+    } else {
+        Component {
+            status: ReportVerdict::Invalid,
+            detail: issues
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" ; "),
+        }
+    };
+    if let Some(attributes) = attributes {
+        sig.integrity.detail = match sig.integrity.status {
+            ReportVerdict::Valid => attributes.detail,
+            _ => format!("{} ; {}", attributes.detail, sig.integrity.detail),
         };
+        sig.integrity.status = sig.integrity.status.worst(attributes.status);
     }
+    sig.byte_range = br;
+    sig.revision = Some(revision);
+    Ok(())
+}
 
-    // (Horodatage CMS à compléter côté cms::verify)
-
-    final_verdict(&mut report);
-    Ok(report)
+/// Empreintes à calculer sur les octets couverts : SHA-256 pour le rapport,
+/// plus celle qu’exige la vérification du /SubFilter. Une CMS détachée n’a
+/// besoin de la sienne qu’en lecture en flux : sinon les octets couverts,
+/// chargés en mémoire, sont relus par la vérification CMS.
+fn required_digests(
+    sub_filter: &SubFilter,
+    contents: &[u8],
+    streamed: bool,
+) -> Vec<DigestAlgorithm> {
+    let mut algorithms = vec![DigestAlgorithm::Sha256];
+    let specific = match sub_filter {
+        SubFilter::Pkcs7Sha1 | SubFilter::X509RsaSha1 => vec![DigestAlgorithm::Sha1],
+        SubFilter::Rfc3161 => TimeStampToken::from_der(contents)
+            .ok()
+            .and_then(|t| t.digest_algorithm())
+            .into_iter()
+            .collect(),
+        // Une empreinte par algorithme des SignerInfo, tous vérifiés.
+        SubFilter::Pkcs7Detached | SubFilter::CadesDetached if streamed => {
+            CmsSignedData::from_der(contents)
+                .map(|sd| {
+                    sd.signers
+                        .iter()
+                        .filter_map(|si| DigestAlgorithm::from_oid(&si.digest_algorithm))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    };
+    algorithms.extend(specific);
+    algorithms
 }
 
-fn find_signature_dict(doc: &Document) -> Result<(ObjectId, lopdf::Dictionary)> {
-    for (id, obj) in &doc.objects {
-        if let Ok(dict) = obj.as_dict() {
-            if let Ok(Object::Dictionary(v)) = dict.get(b"V") {
-                return Ok((*id, v.clone()));
-            }
+/// Révocation et disponibilité LTV de la chaîne du signataire, à partir des
+/// certificats de la signature, du DSS et des CRL/OCSP fournis en fichiers.
+fn assess_revocation(
+    ctx: &PdfContext,
+    sig: &mut SignatureReport,
+    sub_filter: &SubFilter,
+    contents: &[u8],
+    x509_certs: &[Vec<u8>],
+    embedded: Option<&ValidationMaterial>,
+) {
+    let trust = TrustStore {
+        anchors_pem: ctx.anchors_pem,
+        intermediates: &[],
+    };
+    let (signer, mut pool, cms_material, at) = match sub_filter {
+        SubFilter::X509RsaSha1 => {
+            let certs: Vec<Certificate> = x509_certs
+                .iter()
+                .filter_map(|der| Certificate::from_der(der).ok())
+                .collect();
+            let signer = certs.first().cloned();
+            (
+                signer,
+                certs,
+                ValidationMaterial::default(),
+                ValidationTime::Now,
+            )
         }
+        _ => match CmsSignedData::from_der(contents) {
+            Ok(sd) => (
+                sd.signer_certificate().cloned(),
+                sd.certificates.clone(),
+                sd.validation_material(),
+                sd.signer().map_or(ValidationTime::Now, |si| {
+                    ValidationTime::of_signer(si, &trust)
+                }),
+            ),
+            Err(_) => (
+                None,
+                Vec::new(),
+                ValidationMaterial::default(),
+                ValidationTime::Now,
+            ),
+        },
+    };
+    let Some(signer) = signer else {
+        let missing = Component {
+            status: ReportVerdict::Warning,
+            detail: "Non évaluée (certificat du signataire introuvable)".into(),
+        };
+        sig.revocation = missing.clone();
+        sig.ltv = missing;
+        return;
+    };
+    pool.extend(
+        embedded
+            .iter()
+            .flat_map(|m| m.certs.iter())
+            .filter_map(|der| Certificate::from_der(der).ok()),
+    );
+
+    // CRL de la SignedData : embarquées au même titre que celles du DSS.
+    let embedded = match (embedded, cms_material.crls.is_empty()) {
+        (None, true) => None,
+        (dss, _) => {
+            let mut material = cms_material;
+            material.extend(dss.unwrap_or(&ValidationMaterial::default()));
+            Some(material)
+        }
+    };
+    let assessment = assess_chain(
+        at,
+        &signer,
+        &pool,
+        &ctx.anchors,
+        embedded.as_ref(),
+        ctx.external,
+    );
+    sig.revocation = assessment.revocation;
+    sig.ltv = assessment.ltv;
+    if sig.vri.is_some() && sig.ltv.status == ReportVerdict::Valid {
+        sig.ltv.detail.push_str(" (entrée VRI dédiée)");
     }
-    Err(anyhow::anyhow!("Aucune dict de signature"))
 }
 
-fn parse_byterange(obj: &Object) -> Result<Vec<(usize, usize)>> {
-    if let Object::Array(arr) = obj {
-        if arr.len() % 2 != 0 {
-            return Err(anyhow::anyhow!("ByteRange paire attendue"));
+/// Fin de la couverture ByteRange, si celui-ci est lisible.
+fn covered_end(sig_dict: &lopdf::Dictionary) -> Option<usize> {
+    let br = parse_byterange(sig_dict.get(b"ByteRange").ok()?).ok()?;
+    br.last().map(|(off, len)| off.saturating_add(*len))
+}
+
+/// Compare la révision signée à la révision finale : évalue chaque
+/// modification contre DocMDP/FieldMDP (composant d’intégrité retourné) et
+/// renseigne le dépistage shadow de `sig`.
+fn assess_modifications(
+    ctx: &PdfContext,
+    revision: &RevisionInfo,
+    sig: &mut SignatureReport,
+) -> Option<Component> {
+    let unassessed = |detail: String| Component {
+        status: ReportVerdict::Warning,
+        detail,
+    };
+    let Some(index) = revision.signed_revision.map(|r| r - 1) else {
+        sig.shadow = unassessed("Non évalué : révision signée introuvable".into());
+        return Some(unassessed(
+            "Document modifié après signature ; révision signée introuvable".into(),
+        ));
+    };
+    let signed = ctx.revisions[index];
+    let old = match ctx.memory {
+        Some(bytes) => load_document(&bytes[..signed.end], ctx.options),
+        None => LazyPdf::open(ctx.source, signed.eof, ctx.budget).and_then(|mut r| r.document()),
+    };
+    let old = match old {
+        Ok(doc) => doc,
+        Err(e) => {
+            sig.shadow = unassessed(format!("Non évalué : révision signée illisible ({e})"));
+            return Some(unassessed(format!(
+                "Document modifié après signature ; révision signée illisible ({e})"
+            )));
         }
-        let mut res = Vec::new();
-        let mut it = arr.iter();
-        while let (Some(a), Some(b)) = (it.next(), it.next()) {
-            let off = a.as_i64().unwrap_or(0) as usize;
-            let len = b.as_i64().unwrap_or(0) as usize;
-            res.push((off, len));
+    };
+
+    let changes = diff_revisions(&old, ctx.doc);
+    sig.modifications = ctx.mdp.evaluate(index, &changes);
+
+    sig.shadow_findings = detect_shadow(&old, ctx.doc, &changes);
+    sig.shadow = if sig.shadow_findings.is_empty() {
+        Component {
+            status: ReportVerdict::Valid,
+            detail: "Aucun motif d’attaque shadow détecté".into(),
+        }
+    } else {
+        let kinds: Vec<String> = sig
+            .shadow_findings
+            .iter()
+            .map(|f| format!("{:?}", f.kind))
+            .collect();
+        Component {
+            status: ReportVerdict::Invalid,
+            detail: format!("Motifs d’attaque shadow détectés : {}", kinds.join(", ")),
+        }
+    };
+
+    let refused: Vec<String> = sig
+        .modifications
+        .iter()
+        .filter(|m| !m.allowed)
+        .map(|m| format!("{:?} ({})", m.kind, m.object))
+        .collect();
+    if !refused.is_empty() {
+        let more = refused.len().saturating_sub(5);
+        let mut detail = format!(
+            "Modifications non autorisées après signature : {}",
+            refused[..refused.len().min(5)].join(", ")
+        );
+        if more > 0 {
+            detail.push_str(&format!(" (+{more})"));
         }
-        return Ok(res);
+        return Some(Component {
+            status: ReportVerdict::Invalid,
+            detail,
+        });
     }
-    Err(anyhow::anyhow!("ByteRange non-array"))
+    if changes.is_empty() {
+        return Some(Component {
+            status: ReportVerdict::Warning,
+            detail: format!(
+                "Octets ajoutés après signature sans modification d’objet identifiable ({} octets)",
+                revision.bytes_after
+            ),
+        });
+    }
+    Some(Component {
+        status: ReportVerdict::Valid,
+        detail: format!(
+            "ByteRange cohérent ; {} modification(s) ultérieure(s) autorisée(s) en {} révision(s)",
+            changes.len(),
+            revision.revisions_after
+        ),
+    })
 }
 
 fn extract_contents(obj: &Object) -> Result<Vec<u8>> {
@@ -117,30 +947,3 @@ fn extract_contents(obj: &Object) -> Result<Vec<u8>> {
         _ => Err(anyhow::anyhow!("Contents inattendu")),
     }
 }
-
-fn sha256_over_ranges(pdf: &[u8], ranges: &[(usize, usize)]) -> Result<Vec<u8>> {
-    let mut h = Sha256::new();
-    for (off, len) in ranges {
-        let end = off.saturating_add(*len);
-        if end > pdf.len() {
-            return Err(anyhow::anyhow!("ByteRange hors limites"));
-        }
-        h.update(&pdf[*off..end]);
-    }
-    Ok(h.finalize().to_vec())
-}
-
-fn find_dss(doc: &Document) -> Option<lopdf::Dictionary> {
-    for obj in doc.objects.values() {
-        if let Ok(dict) = obj.as_dict() {
-            if let Ok(typ) = dict.get(b"Type") {
-                if let Ok(name) = typ.as_name() {
-                    if name == b"DSS" {
-                        return Some(dict.clone());
-                    }
-                }
-            }
-        }
-    }
-    None
-}
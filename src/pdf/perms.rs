@@ -0,0 +1,133 @@
+//! Dictionnaire /Perms du catalogue (ISO 32000-2 §12.8.4) : signature de
+//! certification (/DocMDP) et signature de droits d’usage Adobe (/UR3) ; et
+//! formulaires XFA, dont le rendu dynamique échappe au ByteRange.
+
+use crate::pdf::bytes::find;
+use crate::pdf::decode::decode_capped;
+use crate::pdf::lazy::is_unloaded;
+use crate::pdf::mdp::transforms;
+use crate::pdf::metadata::parse_pdf_date;
+use crate::pdf::text::decode_text_string;
+use crate::report::{Component, ReportVerdict, UsageRights};
+use lopdf::{Dictionary, Document, Object};
+use time::format_description::well_known::Rfc3339;
+
+/// Rubriques de /TransformParams UR3 énumérant des droits.
+const RIGHTS: [&[u8]; 6] = [
+    b"Document",
+    b"Form",
+    b"Annots",
+    b"Signature",
+    b"EF",
+    b"FormEx",
+];
+
+/// Signature de droits d’usage (/Reference /UR3, ou /UR antérieur) : elle
+/// débloque des fonctions du lecteur et n’engage aucun signataire.
+pub fn is_usage_rights(doc: &Document, sig_dict: &Dictionary) -> bool {
+    transforms(doc, sig_dict)
+        .iter()
+        .any(|(method, _)| matches!(*method, b"UR3" | b"UR"))
+}
+
+/// La signature est-elle celle que désigne /Perms /DocMDP (certification) ?
+pub fn is_certification(doc: &Document, sig_dict: &Dictionary) -> bool {
+    perms_signature(doc, b"DocMDP")
+        .is_some_and(|cert| cert.get(b"ByteRange").ok() == sig_dict.get(b"ByteRange").ok())
+}
+
+/// Signature /Perms /UR3, décrite sans être vérifiée.
+pub fn usage_rights(doc: &Document) -> Option<UsageRights> {
+    let sig_dict = perms_signature(doc, b"UR3")?;
+    let text = |key: &[u8]| -> Option<String> {
+        let (_, value) = doc.dereference(sig_dict.get(key).ok()?).ok()?;
+        value.as_str().ok().map(decode_text_string)
+    };
+    let mut rights = Vec::new();
+    for (method, params) in transforms(doc, sig_dict) {
+        let (b"UR3" | b"UR", Some(params)) = (method, params) else {
+            continue;
+        };
+        for key in RIGHTS {
+            let Ok((_, Object::Array(names))) = params.get(key).and_then(|n| doc.dereference(n))
+            else {
+                continue;
+            };
+            rights.extend(names.iter().filter_map(|n| n.as_name().ok()).map(|n| {
+                format!(
+                    "{}/{}",
+                    String::from_utf8_lossy(key),
+                    String::from_utf8_lossy(n)
+                )
+            }));
+        }
+    }
+    Some(UsageRights {
+        name: text(b"Name"),
+        sub_filter: sig_dict
+            .get(b"SubFilter")
+            .and_then(Object::as_name)
+            .ok()
+            .map(|n| String::from_utf8_lossy(n).into_owned()),
+        claimed_time: sig_dict
+            .get(b"M")
+            .and_then(Object::as_str)
+            .ok()
+            .and_then(parse_pdf_date)
+            .and_then(|t| t.format(&Rfc3339).ok()),
+        rights,
+    })
+}
+
+/// Formulaire XFA (/AcroForm /XFA) : WARNING s’il est dynamique (catalogue
+/// /NeedsRendering ou `dynamicRender` requis dans la configuration XFA).
+/// Un paquet de plus de `max_bytes` une fois décodé n’est pas examiné.
+pub fn check_xfa(doc: &Document, max_bytes: usize) -> Option<Component> {
+    let catalog = doc.catalog().ok()?;
+    let (_, acroform) = doc.dereference(catalog.get(b"AcroForm").ok()?).ok()?;
+    let (_, xfa) = doc
+        .dereference(acroform.as_dict().ok()?.get(b"XFA").ok()?)
+        .ok()?;
+
+    let needs_rendering = catalog
+        .get(b"NeedsRendering")
+        .and_then(Object::as_bool)
+        .unwrap_or(false);
+    let packets: Vec<&Object> = match xfa {
+        // [nom₁ flux₁ nom₂ flux₂ …] : un paquet XDP par flux.
+        Object::Array(items) => items.iter().skip(1).step_by(2).collect(),
+        other => vec![other],
+    };
+    let dynamic_render = packets.into_iter().any(|p| {
+        let Ok((_, Object::Stream(stream))) = doc.dereference(p) else {
+            return false;
+        };
+        if is_unloaded(stream) {
+            return false;
+        }
+        decode_capped(stream, max_bytes)
+            .is_ok_and(|xml| find(&xml, b"<dynamicRender>required").is_some())
+    });
+
+    Some(if needs_rendering || dynamic_render {
+        Component {
+            status: ReportVerdict::Warning,
+            detail: "Formulaire XFA dynamique : le contenu affiché est produit par le modèle XFA \
+                     à l’ouverture, le ByteRange n’en garantit pas le rendu"
+                .into(),
+        }
+    } else {
+        Component {
+            status: ReportVerdict::Valid,
+            detail: "Formulaire XFA statique : l’apparence PDF signée fait foi".into(),
+        }
+    })
+}
+
+fn perms_signature<'a>(doc: &'a Document, key: &[u8]) -> Option<&'a Dictionary> {
+    let (_, perms) = doc
+        .dereference(doc.catalog().ok()?.get(b"Perms").ok()?)
+        .ok()?;
+    let (_, sig) = doc.dereference(perms.as_dict().ok()?.get(key).ok()?).ok()?;
+    sig.as_dict().ok()
+}
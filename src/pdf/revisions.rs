@@ -0,0 +1,109 @@
+use crate::pdf::bytes::{find, trim_end_ws};
+use crate::pdf::source::{ByteSource, CHUNK};
+use crate::report::RevisionInfo;
+use anyhow::Result;
+
+/// Octets examinés avant `%%EOF` pour y trouver `startxref <offset>`.
+const LOOKBEHIND: usize = 128;
+
+/// Révision incrémentale du fichier : elle couvre les octets `[0, end)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Revision {
+    /// Position juste après `%%EOF`.
+    pub eof: usize,
+    /// Fin de révision, fin de ligne suivant `%%EOF` comprise.
+    pub end: usize,
+}
+
+/// Découpe le fichier en révisions incrémentales. Seuls les `%%EOF` précédés de
+/// `startxref <offset>` comptent, pour ignorer ceux qui traînent dans un flux.
+/// Le fichier est parcouru par blocs.
+pub fn split_revisions(pdf: &(impl ByteSource + ?Sized)) -> Result<Vec<Revision>> {
+    let mut revisions = Vec::new();
+    let mut start = 0;
+    while start < pdf.len() {
+        // Recouvrement de 4 octets : un `%%EOF` à cheval est vu dans ce bloc.
+        let chunk = pdf.read(start, start.saturating_add(CHUNK + 4))?;
+        let mut from = 0;
+        while let Some(p) = find(&chunk[from..], b"%%EOF").map(|p| p + from) {
+            if p >= CHUNK {
+                break;
+            }
+            from = p + 5;
+            let pos = start + p;
+            if startxref_before(&pdf.read(pos.saturating_sub(LOOKBEHIND), pos)?).is_none() {
+                continue;
+            }
+            let eof = pos + 5;
+            let end = match &*pdf.read(eof, eof + 2)? {
+                [b'\r', b'\n', ..] => eof + 2,
+                [b'\r' | b'\n', ..] => eof + 1,
+                _ => eof,
+            };
+            revisions.push(Revision { eof, end });
+        }
+        start += CHUNK;
+    }
+    Ok(revisions)
+}
+
+/// Situe la couverture d’une signature (fin du ByteRange) parmi les révisions
+/// et décrit ce qui a été ajouté ensuite. Des octets non blancs après le
+/// dernier `%%EOF` comptent comme une révision incomplète.
+pub fn locate_signature(
+    pdf: &(impl ByteSource + ?Sized),
+    revisions: &[Revision],
+    covered_end: usize,
+) -> Result<RevisionInfo> {
+    let signed = revision_at(revisions, covered_end);
+    let modified_after = has_content(pdf, covered_end)?;
+
+    let last_end = revisions.last().map_or(0, |r| r.end);
+    let trailing_garbage = has_content(pdf, last_end)?;
+    let total_revisions = revisions.len() + usize::from(trailing_garbage);
+    let revisions_after = match signed {
+        Some(i) => total_revisions - (i + 1),
+        None => {
+            revisions.iter().filter(|r| r.eof > covered_end).count() + usize::from(trailing_garbage)
+        }
+    };
+
+    Ok(RevisionInfo {
+        signed_revision: signed.map(|i| i + 1),
+        total_revisions,
+        revisions_after,
+        modified_after,
+        bytes_after: pdf.len().saturating_sub(covered_end),
+    })
+}
+
+/// Des octets non blancs suivent-ils la position `from` ?
+fn has_content(pdf: &(impl ByteSource + ?Sized), from: usize) -> Result<bool> {
+    let mut found = false;
+    pdf.for_each_chunk(from, pdf.len(), &mut |chunk| {
+        found = chunk.iter().any(|b| !b.is_ascii_whitespace());
+        !found
+    })?;
+    Ok(found)
+}
+
+/// Index (0 = version initiale) de la révision se terminant à `covered_end`.
+pub fn revision_at(revisions: &[Revision], covered_end: usize) -> Option<usize> {
+    revisions
+        .iter()
+        .position(|r| r.eof <= covered_end && covered_end <= r.end)
+}
+
+/// Lit `startxref <n>` immédiatement avant `%%EOF` (blancs tolérés).
+fn startxref_before(head: &[u8]) -> Option<usize> {
+    let head = trim_end_ws(head);
+    let digits = head.iter().rev().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let (rest, num) = head.split_at(head.len() - digits);
+    if !trim_end_ws(rest).ends_with(b"startxref") {
+        return None;
+    }
+    std::str::from_utf8(num).ok()?.parse().ok()
+}
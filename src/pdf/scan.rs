@@ -0,0 +1,108 @@
+//! Repérage des signatures à même les octets, quand la structure du PDF (xref,
+//! trailer) est illisible : seule la partie cryptographique reste vérifiable.
+
+use crate::pdf::byterange::parse_byterange;
+use crate::pdf::bytes::find;
+use crate::pdf::fields::SigField;
+use crate::pdf::perms::is_usage_rights;
+use crate::pdf::source::{ByteSource, CHUNK};
+use crate::pdf::syntax::Parser;
+use anyhow::Result;
+use lopdf::{Document, Object};
+use tracing::debug;
+
+const KEY: &[u8] = b"/ByteRange";
+
+/// Octets remontés avant /ByteRange pour trouver le `<<` du dictionnaire.
+const LOOKBEHIND: usize = 16 << 10;
+
+/// Octets lus après la fin de /Contents pour clore le dictionnaire.
+const LOOKAHEAD: usize = 4 << 10;
+
+/// Étendue max d’un dictionnaire de signature (/Contents compris).
+const MAX_DICT: usize = 8 << 20;
+
+/// `<<` candidats essayés avant /ByteRange (dictionnaires imbriqués).
+const MAX_CANDIDATES: usize = 64;
+
+/// Dictionnaires de signature trouvés dans le fichier, dans l’ordre des
+/// octets : chaque `/ByteRange [...]` dont le trou tombe sur `/Contents <...>`
+/// du même dictionnaire, hors droits d’usage (UR3). Les champs AcroForm
+/// restent inconnus (sans nom).
+pub fn scan_signatures(pdf: &(impl ByteSource + ?Sized)) -> Result<Vec<SigField>> {
+    // Aucun objet indirect résoluble : seules les /Reference directes comptent.
+    let no_objects = Document::new();
+    let mut found: Vec<SigField> = Vec::new();
+    let mut start = 0;
+    while start < pdf.len() {
+        // Recouvrement : une clé à cheval sur deux blocs est vue dans le premier.
+        let chunk = pdf.read(start, start.saturating_add(CHUNK + KEY.len()))?;
+        let mut from = 0;
+        while let Some(p) = find(&chunk[from..], KEY).map(|p| p + from) {
+            if p >= CHUNK {
+                break;
+            }
+            from = p + KEY.len();
+            let Some(sig_dict) = signature_dict_at(pdf, start + p)? else {
+                continue;
+            };
+            if is_usage_rights(&no_objects, &sig_dict) {
+                continue;
+            }
+            if !found
+                .iter()
+                .any(|f| f.sig_dict.get(b"ByteRange").ok() == sig_dict.get(b"ByteRange").ok())
+            {
+                found.push(SigField {
+                    name: None,
+                    sig_dict,
+                    lock: None,
+                    widgets: Vec::new(),
+                });
+            }
+        }
+        start += CHUNK;
+    }
+    Ok(found)
+}
+
+/// Dictionnaire englobant la clé /ByteRange située à `key`, s’il porte aussi
+/// le /Contents désigné par le trou du ByteRange.
+fn signature_dict_at(
+    pdf: &(impl ByteSource + ?Sized),
+    key: usize,
+) -> Result<Option<lopdf::Dictionary>> {
+    let head = pdf.read(key, key + 256)?;
+    let Ok(array) = Parser::new(&head, KEY.len()).object() else {
+        return Ok(None);
+    };
+    let Ok(ranges) = parse_byterange(&array) else {
+        return Ok(None);
+    };
+    let [(off, len), (gap_end, _), ..] = ranges[..] else {
+        return Ok(None);
+    };
+    let gap_start = off.saturating_add(len);
+
+    let from = key.min(gap_start).saturating_sub(LOOKBEHIND);
+    let to = key.max(gap_end).saturating_add(LOOKAHEAD);
+    if to - from > MAX_DICT {
+        debug!("ByteRange à l’offset {key} : /Contents trop éloigné, ignoré");
+        return Ok(None);
+    }
+    let window = pdf.read(from, to)?;
+    let mut candidates = (0..key - from)
+        .rev()
+        .filter(|i| window[*i..].starts_with(b"<<"))
+        .take(MAX_CANDIDATES);
+    Ok(candidates.find_map(|at| {
+        let Ok(Object::Dictionary(dict)) = Parser::new(&window, at).object() else {
+            return None;
+        };
+        let contents_at = window
+            .get(gap_start.checked_sub(from)?..)?
+            .starts_with(b"<");
+        (dict.get(b"ByteRange").ok() == Some(&array) && dict.has(b"Contents") && contents_at)
+            .then_some(dict)
+    }))
+}
@@ -0,0 +1,174 @@
+use crate::pdf::diff::{dict_of, is_structure, live_objects, reachable, same_object, ObjectChange};
+use crate::report::{ShadowFinding, ShadowKind};
+use lopdf::{Document, Object, ObjectId};
+use std::collections::HashSet;
+
+/// Nombre max d’objets cités par constat.
+const MAX_LISTED: usize = 20;
+
+/// Bits /F d’annotation rendant l’annotation invisible (Hidden, NoView).
+const HIDDEN_FLAGS: i64 = 2 | 32;
+
+/// Recherche les motifs d’attaque « shadow » entre la révision signée (`old`)
+/// et la révision finale (`new`) : contenu caché puis révélé, polices ou flux
+/// de contenu remplacés par mise à jour incrémentale, réemploi d’identifiants.
+pub fn detect_shadow(
+    old: &Document,
+    new: &Document,
+    changes: &[ObjectChange],
+) -> Vec<ShadowFinding> {
+    let live_old = live_objects(old);
+    let live_new = live_objects(new);
+    let mut live = live_old.clone();
+    live.extend(live_new.iter().copied());
+    let drawn_old = drawn_objects(old);
+    let rendered_old = rendered_objects(old);
+
+    let mut hide = Vec::new();
+    let mut replace = Vec::new();
+    let mut hide_and_replace = Vec::new();
+    let mut reuse = Vec::new();
+
+    // Hide-and-replace : objets déjà présents (inchangés) mais inatteignables
+    // dans la révision signée, rendus visibles ensuite.
+    for (id, obj) in &old.objects {
+        if !live_old.contains(id)
+            && live_new.contains(id)
+            && same_object(Some(obj), new.objects.get(id))
+            && !is_structure(*id, obj, &live)
+        {
+            hide_and_replace.push(*id);
+        }
+    }
+
+    // Hide : recouvrement affiché à la signature puis retiré ou masqué.
+    for id in &drawn_old {
+        if !live_new.contains(id) {
+            hide.push(*id);
+        }
+    }
+    for (id, obj) in &old.objects {
+        if let (Some(before), Some(after)) =
+            (annot_flags(obj), new.objects.get(id).and_then(annot_flags))
+        {
+            if (before & HIDDEN_FLAGS) != (after & HIDDEN_FLAGS) {
+                hide.push(*id);
+            }
+        }
+    }
+
+    // Replace : police ou flux dessiné par une page de la révision signée,
+    // réécrit, quelle que soit la classe MDP attribuée à la modification.
+    for c in changes {
+        let (Some(before), Some(after)) = (old.objects.get(&c.id), new.objects.get(&c.id)) else {
+            continue;
+        };
+        // Un flux dessiné reste remplacé même retypé (/Type /ObjStm…).
+        if nature(before) != nature(after) {
+            reuse.push(c.id);
+        }
+        if is_font(before) || (rendered_old.contains(&c.id) && matches!(before, Object::Stream(_)))
+        {
+            replace.push(c.id);
+        }
+    }
+
+    [
+        (
+            ShadowKind::Hide,
+            hide,
+            "contenu affiché à la signature retiré ou masqué ensuite",
+        ),
+        (
+            ShadowKind::Replace,
+            replace,
+            "police ou flux de contenu redéfini après signature",
+        ),
+        (
+            ShadowKind::HideAndReplace,
+            hide_and_replace,
+            "objets présents mais invisibles à la signature, rendus visibles ensuite",
+        ),
+        (
+            ShadowKind::ObjectIdReuse,
+            reuse,
+            "identifiant d’objet réemployé pour un objet de nature différente",
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, ids, _)| !ids.is_empty())
+    .map(|(kind, mut ids, detail)| {
+        ids.sort();
+        ids.dedup();
+        let total = ids.len();
+        ShadowFinding {
+            kind,
+            objects: ids
+                .iter()
+                .take(MAX_LISTED)
+                .map(|(n, g)| format!("{n} {g} R"))
+                .collect(),
+            detail: format!("{detail} ({total} objet(s))"),
+        }
+    })
+    .collect()
+}
+
+/// Objets qui participent au rendu des pages : contenu, ressources, annotations.
+fn drawn_objects(doc: &Document) -> HashSet<ObjectId> {
+    page_objects(doc, &[b"Contents", b"Resources", b"Annots"])
+}
+
+/// Objets dessinés par les pages elles-mêmes : flux de contenu, XObjects,
+/// polices (hors apparences d’annotations).
+fn rendered_objects(doc: &Document) -> HashSet<ObjectId> {
+    page_objects(doc, &[b"Contents", b"Resources"])
+}
+
+fn page_objects(doc: &Document, keys: &[&[u8]]) -> HashSet<ObjectId> {
+    let mut out = HashSet::new();
+    for page_id in doc.get_pages().into_values() {
+        let Ok(page) = doc.get_dictionary(page_id) else {
+            continue;
+        };
+        for key in keys {
+            if let Ok(o) = page.get(key) {
+                reachable(doc, o, &mut out);
+            }
+        }
+    }
+    out
+}
+
+fn is_font(obj: &Object) -> bool {
+    dict_of(obj)
+        .and_then(|d| d.get(b"Type"))
+        .and_then(Object::as_name)
+        .is_ok_and(|t| matches!(t, b"Font" | b"FontDescriptor"))
+}
+
+fn annot_flags(obj: &Object) -> Option<i64> {
+    let d = dict_of(obj).ok()?;
+    if !d.has(b"Subtype") || !d.has(b"Rect") {
+        return None;
+    }
+    Some(d.get(b"F").and_then(Object::as_i64).unwrap_or(0))
+}
+
+/// Nature d’un objet : variante, /Type et /Subtype.
+fn nature(obj: &Object) -> (&'static str, Option<Vec<u8>>, Option<Vec<u8>>) {
+    let variant = match obj {
+        Object::Stream(_) => "stream",
+        Object::Dictionary(_) => "dict",
+        Object::Array(_) => "array",
+        _ => "scalar",
+    };
+    let name = |key: &[u8]| {
+        dict_of(obj)
+            .and_then(|d| d.get(key))
+            .and_then(Object::as_name)
+            .ok()
+            .map(<[u8]>::to_vec)
+    };
+    (variant, name(b"Type"), name(b"Subtype"))
+}
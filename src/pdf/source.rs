@@ -0,0 +1,94 @@
+//! Accès aux octets du PDF : image en mémoire, ou lecture à la demande sur
+//! disque quand le fichier dépasse le budget mémoire (mode flux).
+
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Taille des blocs lus lors des parcours séquentiels (empreintes, recherches).
+pub const CHUNK: usize = 1 << 20;
+
+/// Octets du fichier adressables par position.
+pub trait ByteSource {
+    fn len(&self) -> usize;
+
+    /// Octets `[start, end)`, bornés à la fin du fichier.
+    fn read(&self, start: usize, end: usize) -> Result<Cow<'_, [u8]>>;
+
+    /// Appelle `f` sur les blocs successifs de `[start, end)`.
+    fn for_each_chunk(
+        &self,
+        start: usize,
+        end: usize,
+        f: &mut dyn FnMut(&[u8]) -> bool,
+    ) -> Result<()> {
+        let mut pos = start;
+        while pos < end.min(self.len()) {
+            let next = end.min(pos.saturating_add(CHUNK));
+            if !f(&self.read(pos, next)?) {
+                break;
+            }
+            pos = next;
+        }
+        Ok(())
+    }
+}
+
+impl ByteSource for [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read(&self, start: usize, end: usize) -> Result<Cow<'_, [u8]>> {
+        let end = end.min(self.len());
+        Ok(Cow::Borrowed(
+            self.get(start.min(end)..end).unwrap_or_default(),
+        ))
+    }
+}
+
+impl ByteSource for Vec<u8> {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read(&self, start: usize, end: usize) -> Result<Cow<'_, [u8]>> {
+        self.as_slice().read(start, end)
+    }
+}
+
+/// Fichier lu à la demande ; seuls les blocs demandés sont en mémoire.
+#[derive(Debug)]
+pub struct FileSource {
+    file: RefCell<File>,
+    len: usize,
+}
+
+impl FileSource {
+    pub fn new(file: File) -> Result<Self> {
+        let len = file.metadata().context("Stat échouée")?.len();
+        Ok(Self {
+            file: RefCell::new(file),
+            len: usize::try_from(len).context("Fichier trop volumineux pour cette plateforme")?,
+        })
+    }
+}
+
+impl ByteSource for FileSource {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read(&self, start: usize, end: usize) -> Result<Cow<'_, [u8]>> {
+        let end = end.min(self.len);
+        let start = start.min(end);
+        let mut buf = vec![0; end - start];
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(start as u64))
+            .and_then(|_| file.read_exact(&mut buf))
+            .with_context(|| format!("Lecture des octets [{start}, {end}) a échoué"))?;
+        Ok(Cow::Owned(buf))
+    }
+}
@@ -0,0 +1,85 @@
+use lopdf::{Dictionary, Document, Object};
+
+/// Encodage de la signature déclaré par /SubFilter (ISO 32000-2 §12.8.3).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubFilter {
+    /// CMS détachée sur les octets du ByteRange.
+    Pkcs7Detached,
+    /// CMS détachée conforme CAdES (PAdES baseline).
+    CadesDetached,
+    /// CMS encapsulant l’empreinte SHA-1 des octets du ByteRange.
+    Pkcs7Sha1,
+    /// Signature RSA PKCS#1 brute sur SHA-1, certificats dans /Cert.
+    X509RsaSha1,
+    /// Jeton d’horodatage RFC 3161 (horodatage de document).
+    Rfc3161,
+    /// SubFilter absent (`None`) ou non reconnu.
+    Unknown(Option<String>),
+}
+
+impl SubFilter {
+    pub fn of(sig_dict: &Dictionary) -> SubFilter {
+        let Ok(name) = sig_dict.get(b"SubFilter").and_then(Object::as_name) else {
+            return SubFilter::Unknown(None);
+        };
+        match name {
+            b"adbe.pkcs7.detached" => SubFilter::Pkcs7Detached,
+            b"ETSI.CAdES.detached" => SubFilter::CadesDetached,
+            b"adbe.pkcs7.sha1" => SubFilter::Pkcs7Sha1,
+            b"adbe.x509.rsa_sha1" => SubFilter::X509RsaSha1,
+            b"ETSI.RFC3161" => SubFilter::Rfc3161,
+            other => SubFilter::Unknown(Some(String::from_utf8_lossy(other).into_owned())),
+        }
+    }
+
+    /// Nom tel qu’il figure dans le PDF.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            SubFilter::Pkcs7Detached => Some("adbe.pkcs7.detached"),
+            SubFilter::CadesDetached => Some("ETSI.CAdES.detached"),
+            SubFilter::Pkcs7Sha1 => Some("adbe.pkcs7.sha1"),
+            SubFilter::X509RsaSha1 => Some("adbe.x509.rsa_sha1"),
+            SubFilter::Rfc3161 => Some("ETSI.RFC3161"),
+            SubFilter::Unknown(name) => name.as_deref(),
+        }
+    }
+}
+
+/// Certificats DER de /Cert (chaîne ou tableau de chaînes, signataire en tête).
+pub fn cert_entries(doc: &Document, sig_dict: &Dictionary) -> Vec<Vec<u8>> {
+    let Ok((_, cert)) = sig_dict.get(b"Cert").and_then(|c| doc.dereference(c)) else {
+        return Vec::new();
+    };
+    match cert {
+        Object::String(der, _) => vec![der.clone()],
+        Object::Array(items) => items
+            .iter()
+            .filter_map(|i| doc.dereference(i).ok()?.1.as_str().ok())
+            .map(<[u8]>::to_vec)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Valeur de signature PKCS#1 de /Contents pour adbe.x509.rsa_sha1 : une
+/// OCTET STRING DER, suivie du bourrage de la réserve.
+pub fn pkcs1_signature_value(contents: &[u8]) -> Option<&[u8]> {
+    let (&tag, rest) = contents.split_first()?;
+    if tag != 0x04 {
+        return None;
+    }
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (usize::from(first), rest)
+    } else {
+        let n = usize::from(first & 0x7f);
+        if n == 0 || n > 4 || rest.len() < n {
+            return None;
+        }
+        let len = rest[..n]
+            .iter()
+            .fold(0usize, |acc, b| acc << 8 | usize::from(*b));
+        (len, &rest[n..])
+    };
+    rest.get(..len)
+}
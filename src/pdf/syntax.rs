@@ -0,0 +1,335 @@
+//! Syntaxe objet PDF (ISO 32000-2 §7.3) pour la lecture en flux, où les
+//! objets sont lus un à un depuis le disque au lieu d’un `Document` complet.
+
+use lopdf::{Dictionary, Object, ObjectId, StringFormat};
+
+/// Profondeur max des tableaux/dictionnaires imbriqués.
+const MAX_DEPTH: usize = 64;
+
+/// Échec d’analyse : fenêtre trop courte (l’objet continue au-delà) ou
+/// syntaxe invalide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntaxErr {
+    Truncated,
+    Invalid,
+}
+
+type Parsed<T> = Result<T, SyntaxErr>;
+
+/// Analyseur sur une fenêtre d’octets du fichier.
+pub struct Parser<'a> {
+    buf: &'a [u8],
+    pub pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(buf: &'a [u8], pos: usize) -> Self {
+        Self { buf, pos }
+    }
+
+    /// En-tête `n g obj` d’un objet indirect.
+    pub fn indirect_header(&mut self) -> Parsed<ObjectId> {
+        let num = self.unsigned()?;
+        let gen = self.unsigned()?;
+        if !self.keyword(b"obj")? {
+            return Err(SyntaxErr::Invalid);
+        }
+        Ok((
+            u32::try_from(num).map_err(|_| SyntaxErr::Invalid)?,
+            u16::try_from(gen).map_err(|_| SyntaxErr::Invalid)?,
+        ))
+    }
+
+    /// Consomme `kw` s’il suit (blancs et commentaires ignorés) et n’est pas
+    /// le début d’un mot plus long.
+    pub fn keyword(&mut self, kw: &[u8]) -> Parsed<bool> {
+        self.skip_ws()?;
+        let end = self.pos + kw.len();
+        if self.buf.len() < end {
+            return if kw.starts_with(&self.buf[self.pos..]) {
+                Err(SyntaxErr::Truncated)
+            } else {
+                Ok(false)
+            };
+        }
+        if &self.buf[self.pos..end] != kw || self.buf.get(end).is_some_and(|b| is_regular(*b)) {
+            return Ok(false);
+        }
+        self.pos = end;
+        Ok(true)
+    }
+
+    /// Entier non signé.
+    pub fn unsigned(&mut self) -> Parsed<u64> {
+        self.skip_ws()?;
+        let digits = self.buf[self.pos..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if self.pos + digits == self.buf.len() {
+            return Err(SyntaxErr::Truncated);
+        }
+        if digits == 0 {
+            return Err(SyntaxErr::Invalid);
+        }
+        let text = &self.buf[self.pos..self.pos + digits];
+        self.pos += digits;
+        std::str::from_utf8(text)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(SyntaxErr::Invalid)
+    }
+
+    /// Objet direct (référence `n g R` comprise).
+    pub fn object(&mut self) -> Parsed<Object> {
+        self.value(0)
+    }
+
+    /// Place le curseur sur le premier octet des données suivant le mot-clé
+    /// `stream` et sa fin de ligne ; `false` si aucun flux ne suit.
+    pub fn stream_start(&mut self) -> Parsed<bool> {
+        if !self.keyword(b"stream")? {
+            return Ok(false);
+        }
+        match self.buf.get(self.pos..self.pos + 2) {
+            Some([b'\r', b'\n']) => self.pos += 2,
+            Some([b'\n', _]) => self.pos += 1,
+            Some(_) => return Err(SyntaxErr::Invalid),
+            None => return Err(SyntaxErr::Truncated),
+        }
+        Ok(true)
+    }
+
+    fn value(&mut self, depth: usize) -> Parsed<Object> {
+        if depth > MAX_DEPTH {
+            return Err(SyntaxErr::Invalid);
+        }
+        self.skip_ws()?;
+        match self.peek()? {
+            b'/' => self.name().map(Object::Name),
+            b'(' => self.literal_string(),
+            b'<' if self.buf.get(self.pos + 1) == Some(&b'<') => {
+                self.dictionary(depth).map(Object::Dictionary)
+            }
+            b'<' if self.pos + 1 == self.buf.len() => Err(SyntaxErr::Truncated),
+            b'<' => self.hex_string(),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_ws()?;
+                    if self.peek()? == b']' {
+                        self.pos += 1;
+                        return Ok(Object::Array(items));
+                    }
+                    items.push(self.value(depth + 1)?);
+                }
+            }
+            b'+' | b'-' | b'.' | b'0'..=b'9' => self.number_or_reference(),
+            _ => {
+                let word = self.word()?;
+                match word {
+                    b"true" => Ok(Object::Boolean(true)),
+                    b"false" => Ok(Object::Boolean(false)),
+                    b"null" => Ok(Object::Null),
+                    _ => Err(SyntaxErr::Invalid),
+                }
+            }
+        }
+    }
+
+    fn dictionary(&mut self, depth: usize) -> Parsed<Dictionary> {
+        self.pos += 2;
+        let mut dict = Dictionary::new();
+        loop {
+            self.skip_ws()?;
+            match &self.buf[self.pos..] {
+                [b'>', b'>', ..] => {
+                    self.pos += 2;
+                    return Ok(dict);
+                }
+                [b'>'] => return Err(SyntaxErr::Truncated),
+                _ => {}
+            }
+            if self.peek()? != b'/' {
+                return Err(SyntaxErr::Invalid);
+            }
+            let key = self.name()?;
+            let value = self.value(depth + 1)?;
+            dict.set(key, value);
+        }
+    }
+
+    fn name(&mut self) -> Parsed<Vec<u8>> {
+        self.pos += 1;
+        let raw = self.regular_run()?;
+        let mut name = Vec::with_capacity(raw.len());
+        let mut i = 0;
+        while i < raw.len() {
+            let decoded = match raw.get(i..i + 3) {
+                Some([b'#', h, l]) => hex_value(*h).zip(hex_value(*l)).map(|(h, l)| h << 4 | l),
+                _ => None,
+            };
+            match decoded {
+                Some(b) => {
+                    name.push(b);
+                    i += 3;
+                }
+                None => {
+                    name.push(raw[i]);
+                    i += 1;
+                }
+            }
+        }
+        Ok(name)
+    }
+
+    fn literal_string(&mut self) -> Parsed<Object> {
+        self.pos += 1;
+        let mut out = Vec::new();
+        let mut nesting = 0usize;
+        loop {
+            let b = self.next()?;
+            match b {
+                b'(' => {
+                    nesting += 1;
+                    out.push(b);
+                }
+                b')' if nesting == 0 => break,
+                b')' => {
+                    nesting -= 1;
+                    out.push(b);
+                }
+                b'\\' => match self.next()? {
+                    b'n' => out.push(b'\n'),
+                    b'r' => out.push(b'\r'),
+                    b't' => out.push(b'\t'),
+                    b'b' => out.push(0x08),
+                    b'f' => out.push(0x0c),
+                    // Fin de ligne échappée : continuation.
+                    b'\r' => {
+                        if self.peek()? == b'\n' {
+                            self.pos += 1;
+                        }
+                    }
+                    b'\n' => {}
+                    d @ b'0'..=b'7' => {
+                        let mut value = u32::from(d - b'0');
+                        for _ in 0..2 {
+                            match self.peek()? {
+                                d @ b'0'..=b'7' => {
+                                    value = value * 8 + u32::from(d - b'0');
+                                    self.pos += 1;
+                                }
+                                _ => break,
+                            }
+                        }
+                        out.push(value as u8);
+                    }
+                    other => out.push(other),
+                },
+                other => out.push(other),
+            }
+        }
+        Ok(Object::String(out, StringFormat::Literal))
+    }
+
+    fn hex_string(&mut self) -> Parsed<Object> {
+        self.pos += 1;
+        let start = self.pos;
+        let len = self.buf[start..]
+            .iter()
+            .position(|b| *b == b'>')
+            .ok_or(SyntaxErr::Truncated)?;
+        self.pos = start + len + 1;
+        crate::pdf::byterange::decode_hex(&self.buf[start..start + len])
+            .map(|s| Object::String(s, StringFormat::Hexadecimal))
+            .ok_or(SyntaxErr::Invalid)
+    }
+
+    fn number_or_reference(&mut self) -> Parsed<Object> {
+        let word = self.word()?;
+        let text = std::str::from_utf8(word).map_err(|_| SyntaxErr::Invalid)?;
+        if let Ok(int) = text.parse::<i64>() {
+            // `n g R` : deux entiers suivis de R.
+            let save = self.pos;
+            if let (Ok(num), true) = (u32::try_from(int), word[0].is_ascii_digit()) {
+                match self.unsigned() {
+                    Ok(gen) if self.keyword(b"R")? => {
+                        let gen = u16::try_from(gen).map_err(|_| SyntaxErr::Invalid)?;
+                        return Ok(Object::Reference((num, gen)));
+                    }
+                    Err(SyntaxErr::Truncated) => return Err(SyntaxErr::Truncated),
+                    _ => {}
+                }
+            }
+            self.pos = save;
+            return Ok(Object::Integer(int));
+        }
+        text.parse::<f32>()
+            .map(Object::Real)
+            .map_err(|_| SyntaxErr::Invalid)
+    }
+
+    /// Suite d’octets réguliers (ni blanc ni délimiteur), non vide.
+    fn word(&mut self) -> Parsed<&'a [u8]> {
+        let word = self.regular_run()?;
+        if word.is_empty() {
+            return Err(SyntaxErr::Invalid);
+        }
+        Ok(word)
+    }
+
+    fn regular_run(&mut self) -> Parsed<&'a [u8]> {
+        let buf = self.buf;
+        let len = buf[self.pos..]
+            .iter()
+            .take_while(|b| is_regular(**b))
+            .count();
+        if self.pos + len == buf.len() {
+            return Err(SyntaxErr::Truncated);
+        }
+        let run = &buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(run)
+    }
+
+    fn skip_ws(&mut self) -> Parsed<()> {
+        loop {
+            match self.peek()? {
+                b if is_whitespace(b) => self.pos += 1,
+                b'%' => while !matches!(self.next()?, b'\r' | b'\n') {},
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn peek(&self) -> Parsed<u8> {
+        self.buf.get(self.pos).copied().ok_or(SyntaxErr::Truncated)
+    }
+
+    fn next(&mut self) -> Parsed<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        Ok(b)
+    }
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(
+        b,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+fn is_regular(b: u8) -> bool {
+    !is_whitespace(b) && !is_delimiter(b)
+}
+
+fn hex_value(b: u8) -> Option<u8> {
+    (b as char).to_digit(16).map(|v| v as u8)
+}
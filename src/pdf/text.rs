@@ -0,0 +1,63 @@
+/// Décode une chaîne texte PDF (ISO 32000-2 §7.9.2.2) : UTF-16BE ou UTF-8
+/// si elle commence par leur BOM, PDFDocEncoding sinon.
+pub fn decode_text_string(raw: &[u8]) -> String {
+    if let Some(utf16) = raw.strip_prefix(&[0xFE, 0xFF]) {
+        let units = utf16
+            .chunks(2)
+            .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]));
+        return char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+    }
+    if let Some(utf8) = raw.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8_lossy(utf8).into_owned();
+    }
+    raw.iter().map(|&b| pdf_doc_char(b)).collect()
+}
+
+/// PDFDocEncoding (ISO 32000-2 annexe D.2) : Latin-1, sauf 0x18–0x1F et
+/// 0x80–0xA0.
+fn pdf_doc_char(b: u8) -> char {
+    const LOW: [char; 8] = ['˘', 'ˇ', 'ˆ', '˙', '˝', '˛', '˚', '˜'];
+    const HIGH: [char; 33] = [
+        '•',
+        '†',
+        '‡',
+        '…',
+        '—',
+        '–',
+        'ƒ',
+        '⁄',
+        '‹',
+        '›',
+        '−',
+        '‰',
+        '„',
+        '“',
+        '”',
+        '‘',
+        '’',
+        '‚',
+        '™',
+        'ﬁ',
+        'ﬂ',
+        'Ł',
+        'Œ',
+        'Š',
+        'Ÿ',
+        'Ž',
+        'ı',
+        'ł',
+        'œ',
+        'š',
+        'ž',
+        char::REPLACEMENT_CHARACTER,
+        '€',
+    ];
+    match b {
+        0x18..=0x1F => LOW[usize::from(b - 0x18)],
+        0x80..=0xA0 => HIGH[usize::from(b - 0x80)],
+        0xAD => char::REPLACEMENT_CHARACTER,
+        _ => char::from(b),
+    }
+}
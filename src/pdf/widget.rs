@@ -0,0 +1,86 @@
+//! Placement des signatures : annotation widget, page, rectangle et
+//! apparence (ISO 32000-2 §12.5.6.19, §12.7.5.5).
+
+use crate::report::SignatureWidget;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+
+/// Drapeaux d’annotation Hidden (bit 2) et NoView (bit 6).
+const HIDDEN_FLAGS: i64 = 0b10 | 0b10_0000;
+
+/// Décrit chaque widget d’un champ : page (d’après les /Annots des pages, à
+/// défaut l’entrée /P), /Rect, visibilité et présence d’une apparence /N.
+pub fn describe_widgets(
+    doc: &Document,
+    widgets: &[(Option<ObjectId>, Dictionary)],
+) -> Vec<SignatureWidget> {
+    if widgets.is_empty() {
+        return Vec::new();
+    }
+    let pages = doc.get_pages();
+    widgets
+        .iter()
+        .map(|(id, dict)| {
+            let page = id
+                .and_then(|id| {
+                    pages
+                        .iter()
+                        .find(|(_, page)| page_annotations(doc, **page).contains(&id))
+                })
+                .or_else(|| {
+                    let p = dict.get(b"P").and_then(Object::as_reference).ok()?;
+                    pages.iter().find(|(_, page)| **page == p)
+                })
+                .map(|(number, _)| *number as usize);
+            let rect = rect(doc, dict);
+            let flags = dict.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+            let has_area = rect.is_some_and(|[x1, y1, x2, y2]| x1 != x2 && y1 != y2);
+            SignatureWidget {
+                object: id.map(|(n, g)| format!("{n} {g} R")),
+                page,
+                rect,
+                visible: page.is_some() && has_area && flags & HIDDEN_FLAGS == 0,
+                appearance: has_appearance(doc, dict),
+            }
+        })
+        .collect()
+}
+
+fn page_annotations(doc: &Document, page: ObjectId) -> Vec<ObjectId> {
+    doc.get_dictionary(page)
+        .and_then(|p| p.get(b"Annots"))
+        .and_then(|a| doc.dereference(a))
+        .and_then(|(_, a)| a.as_array())
+        .map(|a| a.iter().filter_map(|r| r.as_reference().ok()).collect())
+        .unwrap_or_default()
+}
+
+fn rect(doc: &Document, dict: &Dictionary) -> Option<[f32; 4]> {
+    let (_, rect) = doc.dereference(dict.get(b"Rect").ok()?).ok()?;
+    match rect.as_array().ok()?.as_slice() {
+        [a, b, c, d] => Some([
+            a.as_float().ok()?,
+            b.as_float().ok()?,
+            c.as_float().ok()?,
+            d.as_float().ok()?,
+        ]),
+        _ => None,
+    }
+}
+
+/// Apparence normale /AP /N : un flux, ou un dictionnaire d’états dont au
+/// moins un est un flux.
+fn has_appearance(doc: &Document, dict: &Dictionary) -> bool {
+    let normal = dict
+        .get(b"AP")
+        .and_then(|ap| doc.dereference(ap))
+        .and_then(|(_, ap)| ap.as_dict())
+        .and_then(|ap| ap.get(b"N"))
+        .and_then(|n| doc.dereference(n));
+    match normal {
+        Ok((_, Object::Stream(_))) => true,
+        Ok((_, Object::Dictionary(states))) => states
+            .iter()
+            .any(|(_, s)| matches!(doc.dereference(s), Ok((_, Object::Stream(_))))),
+        _ => false,
+    }
+}
@@ -9,7 +9,7 @@ pub enum ExitCode {
     Warning = 2,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ReportVerdict {
     Valid,
@@ -18,6 +18,25 @@ pub enum ReportVerdict {
     Warning,
 }
 
+impl ReportVerdict {
+    fn severity(self) -> u8 {
+        match self {
+            ReportVerdict::Valid => 0,
+            ReportVerdict::Warning => 1,
+            ReportVerdict::Invalid => 2,
+        }
+    }
+
+    /// Le plus sévère des deux statuts (Invalid > Warning > Valid).
+    pub fn worst(self, other: ReportVerdict) -> ReportVerdict {
+        if other.severity() > self.severity() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Component {
     pub status: ReportVerdict,
@@ -39,6 +58,335 @@ pub struct Report {
     pub ltv: Component,
     pub verdict: ReportVerdict,
     pub document_sha256: Option<String>,
+    /// Résultat par signature (PDF), dans l’ordre des champs AcroForm.
+    pub signatures: Vec<SignatureReport>,
+    /// Champs de signature vides (PDF), en attente de signature.
+    pub pending_fields: Vec<PendingField>,
+    /// Effet des champs vides selon la politique `--unsigned-fields` (PDF).
+    pub unsigned_fields: Option<Component>,
+    /// Lecture de la structure PDF ; WARNING si elle a échoué et que les
+    /// signatures ont été repérées par balayage des octets.
+    pub structure: Option<Component>,
+    /// Pièces jointes vérifiées avec `--embedded` (PDF signés, CMS/P7S).
+    pub embedded: Vec<EmbeddedFile>,
+    /// Signature de droits d’usage (/Perms /UR3), reportée sans être vérifiée.
+    pub usage_rights: Option<UsageRights>,
+    /// Formulaire XFA ; WARNING s’il est dynamique.
+    pub xfa: Option<Component>,
+    /// Structure CMS décodée (signature CMS/P7S).
+    pub cms: Option<CmsStructure>,
+    /// Vérification de chaque SignerInfo d’une SignedData à plusieurs
+    /// signataires ; vide pour un signataire unique.
+    pub signers: Vec<CmsSignerReport>,
+    /// Couches d’un P7M imbriqué (.p7m.p7m), de l’enveloppe externe au
+    /// document ; vide pour une signature à une seule couche.
+    pub layers: Vec<CmsLayer>,
+}
+
+/// Couche d’un P7M imbriqué : enveloppe CMS dont le contenu encapsulé est
+/// lui-même une signature.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CmsLayer {
+    /// Profondeur, 0 pour l’enveloppe externe.
+    pub index: usize,
+    /// Encodage de la couche dans son contenant : `DER`, `Base64` ou `PEM`.
+    pub encoding: String,
+    /// Vérification de la couche seule ; `document_sha256` y porte sur son
+    /// contenu encapsulé.
+    pub report: Report,
+}
+
+/// SignerInfo d’une SignedData à plusieurs signataires, vérifié seul.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CmsSignerReport {
+    /// Rang du SignerInfo dans la SignedData, à partir de 0.
+    pub index: usize,
+    pub report: Report,
+}
+
+/// SignedData telle que décodée (RFC 5652), indépendamment de sa vérification.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CmsStructure {
+    /// eContentType (`data`, `tstInfo`…).
+    pub content_type: String,
+    /// Contenu encapsulé (P7M) plutôt que signature détachée.
+    pub encapsulated: bool,
+    pub digest_algorithms: Vec<String>,
+    /// Nombre de certificats et de CRL embarqués.
+    pub certificates: usize,
+    pub crls: usize,
+    pub signers: Vec<CmsSignerInfo>,
+}
+
+/// SignerInfo : algorithmes et attributs présents.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CmsSignerInfo {
+    /// Sujet du certificat désigné par le sid, s’il est embarqué.
+    pub signer_dn: Option<String>,
+    pub digest_algorithm: String,
+    pub signature_algorithm: String,
+    pub signed_attributes: Vec<String>,
+    pub unsigned_attributes: Vec<String>,
+}
+
+/// Signature de droits d’usage Adobe (/Perms /UR3) : elle active des fonctions
+/// du lecteur (remplissage, annotations…) et n’engage aucun signataire.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct UsageRights {
+    pub name: Option<String>,
+    pub sub_filter: Option<String>,
+    /// /M, en RFC 3339.
+    pub claimed_time: Option<String>,
+    /// Droits accordés (`Form/FillIn`, `Annots/Create`…).
+    pub rights: Vec<String>,
+}
+
+/// Pièce jointe d’un PDF (/EmbeddedFiles ou membre de portfolio).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EmbeddedFile {
+    /// Nom de fichier (/UF, /F ou clé de l’arbre des noms).
+    pub name: String,
+    /// Taille décodée en octets.
+    pub size: usize,
+    /// Rapport de la pièce jointe, si elle porte une signature.
+    pub report: Option<Report>,
+    /// Vérification impossible (pièce illisible, signature malformée…).
+    pub error: Option<String>,
+}
+
+/// Champ de signature présent dans le formulaire mais jamais signé.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PendingField {
+    pub field_name: Option<String>,
+    /// Page du premier widget du champ (à partir de 1).
+    pub page: Option<usize>,
+    pub widgets: Vec<SignatureWidget>,
+}
+
+/// Résultat de vérification d’une signature individuelle.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SignatureReport {
+    pub index: usize,
+    pub field_name: Option<String>,
+    /// /SubFilter du dictionnaire de signature (ex. `ETSI.CAdES.detached`).
+    pub sub_filter: Option<String>,
+    pub signer_dn: Option<String>,
+    pub certificate_chain: Vec<String>,
+    pub signing_time: Option<String>,
+    pub timestamp_rfc3161: Option<String>,
+    /// Structure CMS décodée de /Contents.
+    pub cms: Option<CmsStructure>,
+    /// Métadonnées déclarées du dictionnaire de signature.
+    pub metadata: SignatureMetadata,
+    /// Annotations widget du champ (placement et apparence de la signature).
+    pub widgets: Vec<SignatureWidget>,
+    /// Cohérence de /M avec signingTime et l’horodatage.
+    pub claimed_time: Component,
+    pub byte_range: Vec<(usize, usize)>,
+    pub byte_range_sha256: Option<String>,
+    /// Taille de la structure signée face à la réserve /Contents.
+    pub contents: Option<ContentsUsage>,
+    /// Bourrage de /Contents après la structure signée (données dissimulées).
+    pub padding: Component,
+    pub revision: Option<RevisionInfo>,
+    /// Signature de certification désignée par /Perms /DocMDP.
+    pub certification: bool,
+    /// Niveau DocMDP /P en vigueur (signature de certification), s’il y en a une.
+    pub docmdp: Option<u8>,
+    /// Objets modifiés après la révision signée, classés et évalués (DocMDP/FieldMDP).
+    pub modifications: Vec<Modification>,
+    /// Dépistage des attaques « shadow », indépendant de la validité cryptographique.
+    pub shadow: Component,
+    pub shadow_findings: Vec<ShadowFinding>,
+    /// Clé de l’entrée VRI du DSS propre à cette signature, s’il y en a une.
+    pub vri: Option<String>,
+    /// Conformité PAdES baseline (absent pour un horodatage de document).
+    pub pades: Option<PadesConformance>,
+    pub revocation: Component,
+    pub integrity: Component,
+    pub signature: Component,
+    pub chain: Component,
+    pub ltv: Component,
+    pub verdict: ReportVerdict,
+}
+
+/// Métadonnées du dictionnaire de signature : couvertes par le ByteRange mais
+/// déclarées par le logiciel de signature, sans garantie d’exactitude.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SignatureMetadata {
+    /// /M, en RFC 3339.
+    pub claimed_time: Option<String>,
+    pub name: Option<String>,
+    pub reason: Option<String>,
+    pub location: Option<String>,
+    pub contact_info: Option<String>,
+}
+
+/// Annotation widget d’un champ de signature. L’apparence n’est qu’une image
+/// posée sur la page : rien ne la lie au signataire cryptographique.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SignatureWidget {
+    /// Objet PDF du widget (`"12 0 R"`), `None` s’il est direct.
+    pub object: Option<String>,
+    /// Numéro de page (à partir de 1).
+    pub page: Option<usize>,
+    /// /Rect `[x1 y1 x2 y2]` en points.
+    pub rect: Option<[f32; 4]>,
+    /// Sur une page, d’aire non nulle, sans drapeau Hidden ni NoView.
+    pub visible: bool,
+    /// Apparence normale (/AP /N) présente.
+    pub appearance: bool,
+}
+
+/// Occupation de la réserve /Contents (octets décodés).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ContentsUsage {
+    pub reserved: usize,
+    /// Longueur de la CMS ou du jeton, `None` si illisible.
+    pub used: Option<usize>,
+    /// Octets non nuls après la structure signée.
+    pub trailing_nonzero: usize,
+}
+
+/// Position de la révision signée parmi les mises à jour incrémentales.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RevisionInfo {
+    /// Révision couverte par la signature (1 = version initiale), `None` si la
+    /// couverture ne tombe sur aucune fin de révision.
+    pub signed_revision: Option<usize>,
+    pub total_revisions: usize,
+    pub revisions_after: usize,
+    /// Des octets non blancs suivent la couverture de la signature.
+    pub modified_after: bool,
+    pub bytes_after: usize,
+}
+
+/// Nature d’une modification postérieure à la signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ModificationKind {
+    FormFill,
+    Annotation,
+    Signature,
+    DocTimestamp,
+    Dss,
+    Metadata,
+    PageContent,
+    Catalog,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Modification {
+    /// Objet PDF concerné (`"12 0 R"`).
+    pub object: String,
+    pub kind: ModificationKind,
+    /// Champ de formulaire concerné (remplissage).
+    pub field: Option<String>,
+    pub allowed: bool,
+}
+
+/// Variante d’attaque « shadow » (Mainka et al., NDSS 2021).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ShadowKind {
+    Hide,
+    Replace,
+    HideAndReplace,
+    ObjectIdReuse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowFinding {
+    pub kind: ShadowKind,
+    pub objects: Vec<String>,
+    pub detail: String,
+}
+
+/// Niveau PAdES baseline (ETSI EN 319 142-1 §6), du moins au plus exigeant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PadesLevel {
+    #[serde(rename = "B-B")]
+    BB,
+    #[serde(rename = "B-T")]
+    BT,
+    #[serde(rename = "B-LT")]
+    BLt,
+    #[serde(rename = "B-LTA")]
+    BLta,
+}
+
+impl std::fmt::Display for PadesLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PadesLevel::BB => "B-B",
+            PadesLevel::BT => "B-T",
+            PadesLevel::BLt => "B-LT",
+            PadesLevel::BLta => "B-LTA",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PadesConformance {
+    /// Plus haut niveau atteint, `None` si B-B n’est pas satisfait.
+    pub level: Option<PadesLevel>,
+    /// Exigences non satisfaites, préfixées du niveau qui les requiert.
+    pub missing: Vec<String>,
+}
+
+/// Résultat le plus sévère de chaque composant (intégrité, signature,
+/// chaîne, révocation, LTV) parmi `parts`, détail préfixé du libellé de la
+/// première partie concernée.
+fn aggregate(parts: &[(String, &Report)]) -> [Component; 5] {
+    let pick = |get: fn(&Report) -> &Component| -> Component {
+        let mut worst: Option<(&str, &Component)> = None;
+        for (label, report) in parts {
+            let c = get(report);
+            if worst.map_or(true, |(_, w)| c.status.worst(w.status) != w.status) {
+                worst = Some((label, c));
+            }
+        }
+        match worst {
+            Some((label, c)) => Component {
+                status: c.status,
+                detail: format!("{label} : {}", c.detail),
+            },
+            None => Component::default(),
+        }
+    };
+    [
+        pick(|r| &r.integrity),
+        pick(|r| &r.signature),
+        pick(|r| &r.chain),
+        pick(|r| &r.revocation),
+        pick(|r| &r.ltv),
+    ]
+}
+
+/// Remonte dans les composants globaux le résultat le plus sévère de chaque
+/// composant parmi les couches `r.layers` (détail de la première concernée).
+pub fn aggregate_layers(r: &mut Report) {
+    let parts: Vec<_> = r
+        .layers
+        .iter()
+        .map(|l| (format!("Couche #{}", l.index + 1), &l.report))
+        .collect();
+    [r.integrity, r.signature, r.chain, r.revocation, r.ltv] = aggregate(&parts);
+}
+
+/// Remonte dans les composants globaux le résultat le plus sévère de chaque
+/// composant parmi les SignerInfo `r.signers` (détail du premier concerné).
+pub fn aggregate_signers(r: &mut Report) {
+    let parts: Vec<_> = r
+        .signers
+        .iter()
+        .map(|s| {
+            let dn = s.report.signer_dn.as_deref().unwrap_or("inconnu");
+            (format!("Signataire #{} ({dn})", s.index + 1), &s.report)
+        })
+        .collect();
+    [r.integrity, r.signature, r.chain, r.revocation, r.ltv] = aggregate(&parts);
 }
 
 pub fn print_table(r: &Report) {
@@ -66,6 +414,138 @@ pub fn print_table(r: &Report) {
         Cell::new(format!("{:?}", r.verdict)),
     ]);
     println!("{t}");
+
+    if !r.signatures.is_empty() {
+        let mut t = Table::new();
+        t.set_header(vec![
+            "#",
+            "Champ",
+            "Signataire",
+            "Intégrité",
+            "Signature",
+            "Certificat/Chaîne",
+            "Révocation",
+            "Shadow",
+            "PAdES",
+            "Widget",
+            "Verdict",
+        ]);
+        for s in &r.signatures {
+            t.add_row(vec![
+                Cell::new(s.index + 1),
+                Cell::new(s.field_name.as_deref().unwrap_or("-")),
+                Cell::new(s.signer_dn.as_deref().unwrap_or("-")),
+                Cell::new(format!("{:?}", s.integrity.status)),
+                Cell::new(format!("{:?}", s.signature.status)),
+                Cell::new(format!("{:?}", s.chain.status)),
+                Cell::new(format!("{:?}", s.revocation.status)),
+                Cell::new(format!("{:?}", s.shadow.status)),
+                Cell::new(
+                    s.pades
+                        .as_ref()
+                        .and_then(|p| p.level)
+                        .map_or("-".to_string(), |l| l.to_string()),
+                ),
+                Cell::new(widget_summary(&s.widgets)),
+                Cell::new(format!("{:?}", s.verdict)),
+            ]);
+        }
+        println!("{t}");
+    }
+
+    if !r.pending_fields.is_empty() {
+        let fields: Vec<String> = r
+            .pending_fields
+            .iter()
+            .map(|f| {
+                let name = f.field_name.as_deref().unwrap_or("sans nom");
+                match f.page {
+                    Some(p) => format!("{name} (p. {p})"),
+                    None => name.to_string(),
+                }
+            })
+            .collect();
+        println!("Champs de signature en attente : {}", fields.join(", "));
+    }
+    if let Some(structure) = r
+        .structure
+        .as_ref()
+        .filter(|c| c.status != ReportVerdict::Valid)
+    {
+        println!("{}", structure.detail);
+    }
+    if let Some(xfa) = r.xfa.as_ref().filter(|c| c.status != ReportVerdict::Valid) {
+        println!("{}", xfa.detail);
+    }
+    if let Some(ur) = &r.usage_rights {
+        println!(
+            "Droits d’usage /UR3 (non vérifiés) : {}",
+            ur.name.as_deref().unwrap_or("sans nom")
+        );
+    }
+    for s in &r.signers {
+        println!(
+            "Signataire #{} : {:?}, {}",
+            s.index + 1,
+            s.report.verdict,
+            s.report.signer_dn.as_deref().unwrap_or("inconnu")
+        );
+    }
+    for l in &r.layers {
+        println!(
+            "Couche #{} ({}) : {:?}, signataire {}",
+            l.index + 1,
+            l.encoding,
+            l.report.verdict,
+            l.report.signer_dn.as_deref().unwrap_or("inconnu")
+        );
+    }
+    print_embedded(&r.embedded, 0);
+}
+
+/// Une ligne par pièce jointe, sous-pièces indentées.
+fn print_embedded(files: &[EmbeddedFile], depth: usize) {
+    for f in files {
+        let status = match (&f.report, &f.error) {
+            (Some(report), _) if report.signatures.is_empty() => {
+                format!("{:?} ({})", report.verdict, report.input_kind)
+            }
+            (Some(report), _) => format!(
+                "{:?} ({}, {} signature(s))",
+                report.verdict,
+                report.input_kind,
+                report.signatures.len()
+            ),
+            (None, Some(e)) => format!("non vérifiée : {e}"),
+            (None, None) => "non signée".to_string(),
+        };
+        println!(
+            "{}Pièce jointe « {} » ({} octets) : {status}",
+            "  ".repeat(depth),
+            f.name,
+            f.size
+        );
+        if let Some(report) = &f.report {
+            print_embedded(&report.embedded, depth + 1);
+        }
+    }
+}
+
+/// « p. 2 » pour une signature visible, « invisible » sinon ; « - » sans widget.
+fn widget_summary(widgets: &[SignatureWidget]) -> String {
+    if widgets.is_empty() {
+        return "-".into();
+    }
+    let pages: Vec<String> = widgets
+        .iter()
+        .filter(|w| w.visible)
+        .filter_map(|w| w.page.map(|p| format!("p. {p}")))
+        .collect();
+    if pages.is_empty() {
+        "invisible".into()
+    } else {
+        pages.join(", ")
+    }
 }
 
 pub fn write_json(r: &Report, path: &str) -> anyhow::Result<()> {
@@ -75,23 +555,101 @@ pub fn write_json(r: &Report, path: &str) -> anyhow::Result<()> {
 }
 
 pub fn final_verdict(r: &mut Report) {
+    r.verdict = r.signatures.iter().fold(
+        verdict_of(&r.signature, &r.integrity, &r.chain, &r.revocation),
+        |v, s| v.worst(s.verdict),
+    );
+    for c in [&r.unsigned_fields, &r.structure, &r.xfa]
+        .into_iter()
+        .flatten()
+    {
+        r.verdict = r.verdict.worst(c.status);
+    }
+    for l in &r.layers {
+        r.verdict = r.verdict.worst(l.report.verdict);
+    }
+    for s in &r.signers {
+        r.verdict = r.verdict.worst(s.report.verdict);
+    }
+    for e in &r.embedded {
+        r.verdict = match (&e.report, &e.error) {
+            (Some(report), _) => r.verdict.worst(report.verdict),
+            (None, Some(_)) => r.verdict.worst(ReportVerdict::Warning),
+            (None, None) => r.verdict,
+        };
+    }
+}
+
+pub fn signature_verdict(s: &mut SignatureReport) {
+    s.verdict = verdict_of(&s.signature, &s.integrity, &s.chain, &s.revocation)
+        .worst(s.shadow.status)
+        .worst(s.padding.status);
+}
+
+fn verdict_of(
+    sig: &Component,
+    integ: &Component,
+    chain: &Component,
+    revocation: &Component,
+) -> ReportVerdict {
     use ReportVerdict::*;
 
     // Critères essentiels
-    let sig = &r.signature.status;
-    let integ = &r.integrity.status;
-    let chain = &r.chain.status;
+    let (sig, integ, chain) = (sig.status, integ.status, chain.status);
 
-    if matches!(sig, Invalid) || matches!(integ, Invalid) || matches!(chain, Invalid) {
-        r.verdict = Invalid;
-        return;
+    // Un certificat révoqué invalide la signature ; un statut non établi non.
+    if matches!(sig, Invalid)
+        || matches!(integ, Invalid)
+        || matches!(chain, Invalid)
+        || matches!(revocation.status, Invalid)
+    {
+        return Invalid;
     }
 
     if matches!(sig, Valid) && matches!(integ, Valid) && matches!(chain, Valid) {
-        r.verdict = Valid;
-        return;
+        return Valid;
     }
 
     // Le reste (revocation/LTV non évalués) => Warning
-    r.verdict = Warning;
+    Warning
+}
+
+/// Remonte dans les composants globaux le résultat le plus sévère de chaque
+/// composant parmi `r.signatures` (détail de la première signature concernée).
+pub fn aggregate_signatures(r: &mut Report) {
+    let n = r.signatures.len();
+    let pick = |get: fn(&SignatureReport) -> &Component| -> Component {
+        let mut worst: Option<(&SignatureReport, &Component)> = None;
+        for s in &r.signatures {
+            let c = get(s);
+            if worst.map_or(true, |(_, w)| c.status.worst(w.status) != w.status) {
+                worst = Some((s, c));
+            }
+        }
+        match worst {
+            Some((_, c)) if n == 1 => c.clone(),
+            Some((s, c)) => Component {
+                status: c.status,
+                detail: format!(
+                    "Signature #{} ({}) : {}",
+                    s.index + 1,
+                    s.field_name.as_deref().unwrap_or("sans nom"),
+                    c.detail
+                ),
+            },
+            None => Component::default(),
+        }
+    };
+    r.integrity = pick(|s| &s.integrity);
+    r.signature = pick(|s| &s.signature);
+    r.chain = pick(|s| &s.chain);
+    r.revocation = pick(|s| &s.revocation);
+    r.ltv = pick(|s| &s.ltv);
+
+    if let Some(first) = r.signatures.first() {
+        r.signer_dn = first.signer_dn.clone();
+        r.certificate_chain = first.certificate_chain.clone();
+        r.signing_time = first.signing_time.clone();
+        r.timestamp_rfc3161 = first.timestamp_rfc3161.clone();
+    }
 }
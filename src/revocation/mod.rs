@@ -1,11 +1,540 @@
+pub mod ocsp;
+
+use crate::cms::verify::ValidationTime;
+use crate::infra::fs::{read_file_bounded, Limits};
 use crate::report::{Component, ReportVerdict};
+use crate::x509::name::display_name;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine as _;
+use der::{Decode, Encode};
+use ocsp::{single_responses, CertStatus, SingleResponse};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use x509_cert::crl::CertificateList;
+use x509_cert::Certificate;
 
-#[allow(dead_code)]
-pub fn evaluate_revocation_offline(_crl: &[String], _ocsp: &[String]) -> Component {
-    // MVP: prise en compte future (CRL/OCSP fournis en fichiers)
-    Component {
-        status: ReportVerdict::Warning,
-        detail: "Révocation offline non évaluée (MVP). Fournir CRL/OCSP et activer backend ultérieurement.".into(),
+/// Profondeur max de chaîne construite (défense contre les boucles).
+const MAX_CHAIN_DEPTH: usize = 10;
+
+/// Matériel de validation hors ligne (DER) : certificats, CRL, réponses OCSP.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationMaterial {
+    pub certs: Vec<Vec<u8>>,
+    pub crls: Vec<Vec<u8>>,
+    pub ocsps: Vec<Vec<u8>>,
+}
+
+impl ValidationMaterial {
+    /// CRL et réponses OCSP fournies en fichiers (`--crl`, `--ocsp`), DER ou PEM.
+    pub fn from_files(crl: &[String], ocsp: &[String], limits: &Limits) -> Result<Self> {
+        let load = |path: &String| -> Result<Vec<u8>> {
+            let raw = read_file_bounded(path, limits)?;
+            pem_or_der(&raw).with_context(|| format!("Fichier illisible: {path}"))
+        };
+        Ok(ValidationMaterial {
+            certs: Vec::new(),
+            crls: crl.iter().map(load).collect::<Result<_>>()?,
+            ocsps: ocsp.iter().map(load).collect::<Result<_>>()?,
+        })
+    }
+
+    pub fn extend(&mut self, other: &ValidationMaterial) {
+        for (into, from) in [
+            (&mut self.certs, &other.certs),
+            (&mut self.crls, &other.crls),
+            (&mut self.ocsps, &other.ocsps),
+        ] {
+            for item in from {
+                if !into.contains(item) {
+                    into.push(item.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Révocation et disponibilité LTV pour la chaîne d’un signataire.
+#[derive(Debug, Clone)]
+pub struct ChainAssessment {
+    pub revocation: Component,
+    pub ltv: Component,
+}
+
+/// Preuve de statut trouvée pour un certificat de la chaîne.
+struct Evidence {
+    status: CertStatus,
+    source: &'static str,
+    /// Signature de la CRL/réponse OCSP vérifiée (`None` : indéterminé,
+    /// algorithme non pris en charge ou émetteur inconnu).
+    authentic: Option<bool>,
+    embedded: bool,
+    /// Émission : thisUpdate d’une CRL, producedAt d’une réponse OCSP.
+    issued: SystemTime,
+    this_update: SystemTime,
+    next_update: Option<SystemTime>,
+    revoked_at: Option<SystemTime>,
+}
+
+impl Evidence {
+    /// Motif pour lequel la preuve ne vaut pas à l’instant `at` : périmée ou
+    /// datée du futur, ou, pour une signature horodatée, émise avant
+    /// l’horodatage et donc muette sur le statut à cette date.
+    fn staleness(&self, at: ValidationTime) -> Option<String> {
+        let now = SystemTime::now();
+        if seconds(self.issued) > seconds(now) || seconds(self.this_update) > seconds(now) {
+            return Some(format!(
+                "{} datée du futur ({})",
+                self.source,
+                date(self.issued)
+            ));
+        }
+        match at {
+            ValidationTime::Attested(t) if seconds(self.issued) < seconds(t) => Some(format!(
+                "{} émise le {}, avant l’horodatage de signature du {}",
+                self.source,
+                date(self.issued),
+                date(t)
+            )),
+            ValidationTime::Attested(_) => None,
+            ValidationTime::Now | ValidationTime::Claimed(_) => self
+                .next_update
+                .filter(|next| seconds(*next) < seconds(now))
+                .map(|next| format!("{} périmée depuis le {}", self.source, date(next))),
+        }
+    }
+
+    /// Statut à l’instant `at` : une révocation postérieure à l’horodatage de
+    /// signature ne remet pas en cause la signature.
+    fn status_at(&self, at: ValidationTime) -> CertStatus {
+        match (self.status, self.revoked_at, at) {
+            (CertStatus::Revoked, Some(when), ValidationTime::Attested(t))
+                if seconds(when) > seconds(t) =>
+            {
+                CertStatus::Good
+            }
+            (status, ..) => status,
+        }
+    }
+}
+
+/// Les dates sont comparées à la seconde : CRL et OCSP n’en portent pas de
+/// fraction, un genTime d’horodatage si.
+fn seconds(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn date(t: SystemTime) -> String {
+    OffsetDateTime::from(t).format(&Rfc3339).unwrap_or_default()
+}
+
+/// Construit la chaîne du signataire à partir de `pool` et des ancrages, puis
+/// cherche pour chaque certificat non racine une CRL ou une réponse OCSP dans
+/// le matériel embarqué (`embedded`, DSS) puis externe (`external`).
+///
+/// Seule compte une preuve qui vaut à l’instant de validation `at` : en
+/// vigueur à l’instant présent, ou émise après l’horodatage de signature.
+pub fn assess_chain(
+    at: ValidationTime,
+    signer: &Certificate,
+    pool: &[Certificate],
+    anchors: &[Certificate],
+    embedded: Option<&ValidationMaterial>,
+    external: &ValidationMaterial,
+) -> ChainAssessment {
+    let (chain, complete) = build_chain(signer, pool, anchors);
+    let chain_der: Vec<Vec<u8>> = chain.iter().filter_map(|c| c.to_der().ok()).collect();
+    let pool_der: Vec<Vec<u8>> = pool.iter().filter_map(|c| c.to_der().ok()).collect();
+    let anchors_der: Vec<Vec<u8>> = anchors.iter().filter_map(|c| c.to_der().ok()).collect();
+
+    let sources: Vec<(&ValidationMaterial, bool)> = embedded
+        .map(|m| (m, true))
+        .into_iter()
+        .chain([(external, false)])
+        .collect();
+
+    let mut problems = Vec::new();
+    let mut missing_embedded = Vec::new();
+    let mut revoked = None;
+    let mut checked = 0;
+    for (i, cert) in chain.iter().enumerate() {
+        if is_self_issued(cert) || anchors.contains(cert) {
+            continue;
+        }
+        let name = display_name(&cert.tbs_certificate.subject);
+        let issuer = chain
+            .get(i + 1)
+            .zip(chain_der.get(i + 1).map(Vec::as_slice));
+        let evidence = find_evidence(cert, issuer, &sources, pool, &pool_der, &anchors_der);
+        // Seules des données embarquées authentifiées et à jour rendent la
+        // signature vérifiable à long terme.
+        let current = |e: &&Evidence| e.staleness(at).is_none();
+        if !evidence
+            .iter()
+            .filter(current)
+            .any(|e| e.embedded && e.authentic == Some(true))
+        {
+            missing_embedded.push(name.clone());
+        }
+        let best = evidence
+            .iter()
+            .filter(|e| e.authentic != Some(false))
+            .filter(current)
+            .max_by_key(|e| {
+                (
+                    e.authentic == Some(true),
+                    e.status_at(at) == CertStatus::Revoked,
+                )
+            });
+        let stale = evidence
+            .iter()
+            .filter(|e| e.authentic != Some(false))
+            .find_map(|e| e.staleness(at));
+        match best {
+            None if evidence.is_empty() => {
+                problems.push(format!("aucune donnée de révocation pour « {name} »"))
+            }
+            None => match stale {
+                Some(reason) => problems.push(format!("« {name} » : {reason}")),
+                None => problems.push(format!(
+                    "signature des données de révocation de « {name} » invalide"
+                )),
+            },
+            Some(e) => match (e.status_at(at), e.authentic) {
+                (CertStatus::Revoked, Some(true)) => {
+                    revoked = Some(format!("Certificat « {name} » révoqué ({})", e.source))
+                }
+                (CertStatus::Good, Some(true)) => checked += 1,
+                (CertStatus::Revoked, None) => problems.push(format!(
+                    "« {name} » révoqué selon une {} non authentifiée",
+                    e.source
+                )),
+                (_, None) => problems.push(format!("{} de « {name} » non authentifiée", e.source)),
+                _ => problems.push(format!("« {name} » : statut inconnu ({})", e.source)),
+            },
+        }
+    }
+    if !complete {
+        problems.push("chaîne incomplète jusqu’à un ancrage".into());
+    }
+
+    let revocation = if let Some(detail) = revoked {
+        Component {
+            status: ReportVerdict::Invalid,
+            detail,
+        }
+    } else if problems.is_empty() {
+        Component {
+            status: ReportVerdict::Valid,
+            detail: format!("Aucun certificat révoqué ({checked} statut(s) vérifié(s) hors ligne)"),
+        }
+    } else {
+        Component {
+            status: ReportVerdict::Warning,
+            detail: format!("Révocation non établie : {}", problems.join(" ; ")),
+        }
+    };
+
+    let ltv = match embedded {
+        None => Component {
+            status: ReportVerdict::Warning,
+            detail: "DSS absent : validation à long terme dépendante de données externes".into(),
+        },
+        Some(_) if !complete => Component {
+            status: ReportVerdict::Warning,
+            detail: "LTV incomplet : chaîne non reconstituable avec les certificats embarqués"
+                .into(),
+        },
+        Some(_) if !missing_embedded.is_empty() => Component {
+            status: ReportVerdict::Warning,
+            detail: format!(
+                "LTV incomplet : pas de CRL/OCSP embarqué authentifié et à jour pour {}",
+                missing_embedded
+                    .iter()
+                    .map(|n| format!("« {n} »"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        },
+        Some(_) => Component {
+            status: ReportVerdict::Valid,
+            detail: "Vérifiable hors ligne : chaîne et données de révocation embarquées".into(),
+        },
+    };
+
+    ChainAssessment { revocation, ltv }
+}
+
+/// Chaîne signataire → … → ancrage ou certificat auto-émis, par
+/// correspondance de noms ; le booléen indique si elle est complète.
+fn build_chain(
+    signer: &Certificate,
+    pool: &[Certificate],
+    anchors: &[Certificate],
+) -> (Vec<Certificate>, bool) {
+    let mut chain = vec![signer.clone()];
+    while chain.len() < MAX_CHAIN_DEPTH {
+        let last = chain.last().expect("chaîne non vide");
+        if is_self_issued(last) || anchors.contains(last) {
+            return (chain, true);
+        }
+        let issuer = anchors.iter().chain(pool).find(|c| {
+            c.tbs_certificate.subject == last.tbs_certificate.issuer && !chain.contains(c)
+        });
+        match issuer {
+            Some(c) => chain.push(c.clone()),
+            None => return (chain, false),
+        }
+    }
+    (chain, false)
+}
+
+fn is_self_issued(cert: &Certificate) -> bool {
+    cert.tbs_certificate.subject == cert.tbs_certificate.issuer
+}
+
+/// CRL et réponses OCSP portant sur `cert`, authentifiées auprès de son
+/// émetteur `issuer` (certificat et DER) quand il est connu.
+fn find_evidence(
+    cert: &Certificate,
+    issuer: Option<(&Certificate, &[u8])>,
+    sources: &[(&ValidationMaterial, bool)],
+    pool: &[Certificate],
+    pool_der: &[Vec<u8>],
+    anchors_der: &[Vec<u8>],
+) -> Vec<Evidence> {
+    let serial = strip_zeros(cert.tbs_certificate.serial_number.as_bytes());
+    let issuer_name = cert.tbs_certificate.issuer.to_der().unwrap_or_default();
+    let mut out = Vec::new();
+    for (material, embedded) in sources {
+        for der in &material.ocsps {
+            let Ok(responses) = single_responses(der) else {
+                continue;
+            };
+            for r in responses.iter().filter(|r| {
+                strip_zeros(&r.serial) == serial
+                    && id_hash_matches(r, &issuer_name, &r.issuer_name_hash)
+                    && issuer.map_or(true, |(issuer, _)| {
+                        let key = &issuer.tbs_certificate.subject_public_key_info;
+                        let key = key.subject_public_key.raw_bytes();
+                        id_hash_matches(r, key, &r.issuer_key_hash)
+                    })
+            }) {
+                out.push(Evidence {
+                    status: r.status,
+                    source: "réponse OCSP",
+                    authentic: issuer.and_then(|(issuer, _)| {
+                        settle(
+                            native::ocsp_authentic(der, issuer, pool),
+                            backend::ocsp_authentic(der, pool_der, anchors_der),
+                        )
+                    }),
+                    embedded: *embedded,
+                    issued: r.produced_at,
+                    this_update: r.this_update,
+                    next_update: r.next_update,
+                    revoked_at: r.revocation_time,
+                });
+            }
+        }
+        for der in &material.crls {
+            let Ok(crl) = CertificateList::from_der(der) else {
+                continue;
+            };
+            if crl.tbs_cert_list.issuer != cert.tbs_certificate.issuer {
+                continue;
+            }
+            let entry = crl
+                .tbs_cert_list
+                .revoked_certificates
+                .iter()
+                .flatten()
+                .find(|r| strip_zeros(r.serial_number.as_bytes()) == serial);
+            let this_update = crl.tbs_cert_list.this_update.to_system_time();
+            out.push(Evidence {
+                status: if entry.is_some() {
+                    CertStatus::Revoked
+                } else {
+                    CertStatus::Good
+                },
+                source: "CRL",
+                authentic: issuer.and_then(|(issuer, issuer_der)| {
+                    settle(
+                        native::crl_signed_by(&crl, issuer),
+                        backend::crl_signed_by(der, issuer_der),
+                    )
+                }),
+                embedded: *embedded,
+                issued: this_update,
+                this_update,
+                next_update: crl.tbs_cert_list.next_update.map(|t| t.to_system_time()),
+                revoked_at: entry.map(|r| r.revocation_date.to_system_time()),
+            });
+        }
+    }
+    out
+}
+
+/// Empreinte `hash` d’un CertID (nom ou clé de l’émetteur) conforme à `data`.
+fn id_hash_matches(r: &SingleResponse, data: &[u8], hash: &[u8]) -> bool {
+    match r.hash_algorithm.to_string().as_str() {
+        "1.3.14.3.2.26" => Sha1::digest(data).as_slice() == hash,
+        "2.16.840.1.101.3.4.2.1" => Sha256::digest(data).as_slice() == hash,
+        _ => false,
+    }
+}
+
+fn strip_zeros(bytes: &[u8]) -> &[u8] {
+    let n = bytes.iter().take_while(|b| **b == 0).count();
+    &bytes[n..]
+}
+
+/// DER tel quel, ou contenu du premier bloc PEM.
+fn pem_or_der(raw: &[u8]) -> Result<Vec<u8>> {
+    if !raw.starts_with(b"-----BEGIN") {
+        return Ok(raw.to_vec());
+    }
+    let text = std::str::from_utf8(raw).context("PEM non UTF-8")?;
+    let body: String = text
+        .lines()
+        .skip(1)
+        .take_while(|l| !l.starts_with("-----END"))
+        .collect();
+    Ok(B64.decode(body.trim())?)
+}
+
+/// Résultat de l’authentification native, recoupé par OpenSSL quand le
+/// backend est compilé : un désaccord la rend indéterminée.
+fn settle(native: Option<bool>, openssl: Option<bool>) -> Option<bool> {
+    match (native, openssl) {
+        (Some(a), Some(b)) if a != b => None,
+        (Some(a), _) => Some(a),
+        (None, b) => b,
+    }
+}
+
+/// Authentification native des CRL et réponses OCSP (`cms::signature`) ;
+/// `None` si l’algorithme de signature n’est pas pris en charge.
+mod native {
+    use super::ocsp::basic_response;
+    use crate::cms::signature::{certificate_signed_by, verify, Indeterminate};
+    use crate::x509::chain::{extended_key_usage, valid_at, OCSP_SIGNING};
+    use anyhow::Result;
+    use der::Encode;
+    use x509_cert::crl::CertificateList;
+    use x509_cert::Certificate;
+
+    pub fn crl_signed_by(crl: &CertificateList, issuer: &Certificate) -> Option<bool> {
+        let (Ok(tbs), Some(signature)) = (crl.tbs_cert_list.to_der(), crl.signature.as_bytes())
+        else {
+            return Some(false);
+        };
+        conclude(verify(
+            &issuer.tbs_certificate.subject_public_key_info,
+            &crl.signature_algorithm,
+            None,
+            &tbs,
+            signature,
+        ))
+    }
+
+    /// Réponse signée par l’émetteur du certificat lui-même, ou par un
+    /// répondeur qu’il a délégué (id-kp-OCSPSigning, certificat valide à
+    /// producedAt), RFC 6960 §4.2.2.2.
+    pub fn ocsp_authentic(
+        resp_der: &[u8],
+        issuer: &Certificate,
+        pool: &[Certificate],
+    ) -> Option<bool> {
+        let Ok(basic) = basic_response(resp_der) else {
+            return Some(false);
+        };
+        let Some(responder) = basic
+            .certs
+            .iter()
+            .chain(pool)
+            .chain([issuer])
+            .find(|c| basic.responder.designates(c))
+        else {
+            return Some(false);
+        };
+        let delegated = || {
+            extended_key_usage(responder).contains(&OCSP_SIGNING)
+                && valid_at(responder, basic.produced_at)
+                && certificate_signed_by(responder, issuer).unwrap_or(false)
+        };
+        if responder != issuer && !delegated() {
+            return Some(false);
+        }
+        conclude(verify(
+            &responder.tbs_certificate.subject_public_key_info,
+            &basic.signature_algorithm,
+            None,
+            &basic.tbs_der,
+            &basic.signature,
+        ))
+    }
+
+    fn conclude(verified: Result<bool>) -> Option<bool> {
+        match verified {
+            Ok(ok) => Some(ok),
+            Err(e) if e.is::<Indeterminate>() => None,
+            Err(_) => Some(false),
+        }
+    }
+}
+
+/// Recoupement OpenSSL des CRL/OCSP ; `None` sans le backend.
+#[cfg(feature = "openssl-backend")]
+mod backend {
+    use openssl::ocsp::{OcspFlag, OcspResponse};
+    use openssl::stack::Stack;
+    use openssl::x509::store::X509StoreBuilder;
+    use openssl::x509::{X509Crl, X509};
+
+    pub fn crl_signed_by(crl_der: &[u8], issuer_der: &[u8]) -> Option<bool> {
+        let verify = || -> Result<bool, openssl::error::ErrorStack> {
+            let crl = X509Crl::from_der(crl_der)?;
+            let key = X509::from_der(issuer_der)?.public_key()?;
+            crl.verify(&key)
+        };
+        Some(verify().unwrap_or(false))
+    }
+
+    pub fn ocsp_authentic(resp_der: &[u8], pool: &[Vec<u8>], anchors: &[Vec<u8>]) -> Option<bool> {
+        let verify = || -> Result<bool, openssl::error::ErrorStack> {
+            let basic = OcspResponse::from_der(resp_der)?.basic()?;
+            let mut certs = Stack::new()?;
+            for der in pool {
+                certs.push(X509::from_der(der)?)?;
+            }
+            let mut store = X509StoreBuilder::new()?;
+            for der in anchors {
+                store.add_cert(X509::from_der(der)?)?;
+            }
+            Ok(basic
+                .verify(&certs, &store.build(), OcspFlag::empty())
+                .is_ok())
+        };
+        Some(verify().unwrap_or(false))
+    }
+}
+
+#[cfg(not(feature = "openssl-backend"))]
+mod backend {
+    pub fn crl_signed_by(_crl_der: &[u8], _issuer_der: &[u8]) -> Option<bool> {
+        None
+    }
+
+    pub fn ocsp_authentic(
+        _resp_der: &[u8],
+        _pool: &[Vec<u8>],
+        _anchors: &[Vec<u8>],
+    ) -> Option<bool> {
+        None
     }
 }
 
@@ -0,0 +1,240 @@
+use anyhow::{bail, Context, Result};
+use der::asn1::{BitString, GeneralizedTime, ObjectIdentifier};
+use der::{Any, Decode, Encode, Reader, SliceReader, Tag, Tagged};
+use sha1::{Digest, Sha1};
+use std::time::SystemTime;
+use x509_cert::name::Name;
+use x509_cert::spki::AlgorithmIdentifierOwned;
+use x509_cert::Certificate;
+
+/// Statut d’un certificat selon une réponse OCSP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertStatus {
+    Good,
+    Revoked,
+    Unknown,
+}
+
+/// SingleResponse (RFC 6960 §4.2.1), limitée à l’identification du
+/// certificat, à son statut et aux dates qui le bornent.
+#[derive(Debug, Clone)]
+pub struct SingleResponse {
+    pub hash_algorithm: ObjectIdentifier,
+    pub issuer_name_hash: Vec<u8>,
+    /// Empreinte de la clé publique de l’émetteur (BIT STRING sans octet de
+    /// bits inutilisés).
+    pub issuer_key_hash: Vec<u8>,
+    pub serial: Vec<u8>,
+    pub status: CertStatus,
+    /// revocationTime d’un statut révoqué.
+    pub revocation_time: Option<SystemTime>,
+    pub this_update: SystemTime,
+    pub next_update: Option<SystemTime>,
+    /// producedAt de la réponse qui la contient.
+    pub produced_at: SystemTime,
+}
+
+/// Identification du répondeur (ResponderID, RFC 6960 §4.2.1).
+#[derive(Debug, Clone)]
+pub enum ResponderId {
+    ByName(Name),
+    /// SHA-1 de la clé publique du répondeur.
+    ByKey(Vec<u8>),
+}
+
+impl ResponderId {
+    /// `cert` est-il le certificat désigné ?
+    pub fn designates(&self, cert: &Certificate) -> bool {
+        match self {
+            ResponderId::ByName(name) => &cert.tbs_certificate.subject == name,
+            ResponderId::ByKey(hash) => {
+                let key = &cert.tbs_certificate.subject_public_key_info;
+                Sha1::digest(key.subject_public_key.raw_bytes()).as_slice() == hash
+            }
+        }
+    }
+}
+
+/// BasicOCSPResponse : partie signée, signature et certificats joints.
+#[derive(Debug, Clone)]
+pub struct BasicResponse {
+    /// tbsResponseData, octets couverts par la signature.
+    pub tbs_der: Vec<u8>,
+    pub responder: ResponderId,
+    pub produced_at: SystemTime,
+    pub signature_algorithm: AlgorithmIdentifierOwned,
+    pub signature: Vec<u8>,
+    /// Certificats joints (répondeur délégué et sa chaîne).
+    pub certs: Vec<Certificate>,
+}
+
+/// Décode la BasicOCSPResponse d’une OCSPResponse (ou nue).
+pub fn basic_response(der: &[u8]) -> Result<BasicResponse> {
+    let items = children(&basic(der)?)?;
+    let [tbs, alg, signature, rest @ ..] = items.as_slice() else {
+        bail!("OCSP : BasicOCSPResponse incomplète");
+    };
+    let tbs_fields = children(tbs)?;
+    let produced_at = produced_at(&tbs_fields)?;
+    let responder = tbs_fields
+        .into_iter()
+        .find_map(|a| match a.tag() {
+            Tag::ContextSpecific { number, .. } if matches!(number.value(), 1 | 2) => {
+                Some((number.value(), a))
+            }
+            _ => None,
+        })
+        .context("OCSP : responderID absent")?;
+    let inner = children(&responder.1)?
+        .into_iter()
+        .next()
+        .context("OCSP : responderID vide")?;
+    let responder = match responder.0 {
+        1 => ResponderId::ByName(
+            Name::from_der(&inner.to_der()?).context("OCSP : responderID invalide")?,
+        ),
+        _ => ResponderId::ByKey(inner.value().to_vec()),
+    };
+    let certs = match rest.first() {
+        Some(certs) => match children(certs)?.first() {
+            Some(seq) => children(seq)?
+                .iter()
+                .map(|c| Certificate::from_der(&c.to_der()?).context("OCSP : certificat invalide"))
+                .collect::<Result<_>>()?,
+            None => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+    Ok(BasicResponse {
+        tbs_der: tbs.to_der()?,
+        responder,
+        produced_at,
+        signature_algorithm: AlgorithmIdentifierOwned::from_der(&alg.to_der()?)
+            .context("OCSP : signatureAlgorithm invalide")?,
+        signature: signature
+            .decode_as::<BitString>()
+            .context("OCSP : signature invalide")?
+            .raw_bytes()
+            .to_vec(),
+        certs,
+    })
+}
+
+/// Réponses individuelles d’une OCSPResponse (ou d’une BasicOCSPResponse
+/// nue, forme rencontrée dans certains DSS).
+pub fn single_responses(der: &[u8]) -> Result<Vec<SingleResponse>> {
+    let tbs = children(&basic(der)?)?
+        .into_iter()
+        .next()
+        .context("OCSP : tbsResponseData absent")?;
+    // version [0], responderID [1]/[2], producedAt, responses, extensions [1]
+    let fields = children(&tbs)?;
+    let produced_at = produced_at(&fields)?;
+    let responses = fields
+        .iter()
+        .find(|a| a.tag() == Tag::Sequence)
+        .context("OCSP : responses absent")?;
+
+    children(responses)?
+        .iter()
+        .map(|r| single_response(r, produced_at))
+        .collect()
+}
+
+/// BasicOCSPResponse, extraite des responseBytes d’une OCSPResponse.
+fn basic(der: &[u8]) -> Result<Any> {
+    let top = Any::from_der(der).context("OCSP : DER invalide")?;
+    let items = children(&top)?;
+    let basic = match items.first().map(Any::tag) {
+        Some(Tag::Enumerated) => {
+            if items[0].value() != [0] {
+                bail!(
+                    "OCSP : réponse en échec (responseStatus {:?})",
+                    items[0].value()
+                );
+            }
+            // responseBytes [0] EXPLICIT SEQUENCE { responseType, response OCTET STRING }
+            let bytes = items.get(1).context("OCSP : responseBytes absent")?;
+            let inner = children(bytes)?;
+            let rb = children(inner.first().context("OCSP : responseBytes vide")?)?;
+            let octets = rb.get(1).context("OCSP : response absente")?;
+            Any::from_der(octets.value()).context("OCSP : BasicOCSPResponse invalide")?
+        }
+        _ => top,
+    };
+    Ok(basic)
+}
+
+fn single_response(any: &Any, produced_at: SystemTime) -> Result<SingleResponse> {
+    let fields = children(any)?;
+    let cert_id = children(fields.first().context("OCSP : certID absent")?)?;
+    let [alg, name_hash, key_hash, serial] = cert_id.as_slice() else {
+        bail!("OCSP : certID invalide");
+    };
+    let hash_algorithm = children(alg)?
+        .first()
+        .context("OCSP : hashAlgorithm invalide")?
+        .decode_as::<ObjectIdentifier>()
+        .context("OCSP : hashAlgorithm invalide")?;
+    let cert_status = fields.get(1).context("OCSP : certStatus absent")?;
+    let (status, revocation_time) = match cert_status.tag() {
+        Tag::ContextSpecific { number, .. } => match number.value() {
+            0 => (CertStatus::Good, None),
+            // revoked [1] IMPLICIT RevokedInfo { revocationTime, revocationReason }
+            1 => {
+                let info = children(cert_status)?;
+                let at = time(info.first().context("OCSP : revocationTime absent")?)?;
+                (CertStatus::Revoked, Some(at))
+            }
+            _ => (CertStatus::Unknown, None),
+        },
+        _ => bail!("OCSP : certStatus invalide"),
+    };
+    let this_update = time(fields.get(2).context("OCSP : thisUpdate absent")?)?;
+    // nextUpdate [0] EXPLICIT GeneralizedTime OPTIONAL
+    let next_update = match fields.get(3) {
+        Some(a) if matches!(a.tag(), Tag::ContextSpecific { number, .. } if number.value() == 0) => {
+            Some(time(
+                children(a)?.first().context("OCSP : nextUpdate vide")?,
+            )?)
+        }
+        _ => None,
+    };
+    Ok(SingleResponse {
+        hash_algorithm,
+        issuer_name_hash: name_hash.value().to_vec(),
+        issuer_key_hash: key_hash.value().to_vec(),
+        serial: serial.value().to_vec(),
+        status,
+        revocation_time,
+        this_update,
+        next_update,
+        produced_at,
+    })
+}
+
+/// producedAt, parmi les champs de tbsResponseData.
+fn produced_at(tbs_fields: &[Any]) -> Result<SystemTime> {
+    tbs_fields
+        .iter()
+        .find(|a| a.tag() == Tag::GeneralizedTime)
+        .context("OCSP : producedAt absent")
+        .and_then(time)
+}
+
+fn time(any: &Any) -> Result<SystemTime> {
+    Ok(any
+        .decode_as::<GeneralizedTime>()
+        .context("OCSP : date invalide")?
+        .to_system_time())
+}
+
+/// Éléments d’un type construit.
+fn children(any: &Any) -> Result<Vec<Any>> {
+    let mut reader = SliceReader::new(any.value())?;
+    let mut out = Vec::new();
+    while !reader.is_finished() {
+        out.push(reader.decode()?);
+    }
+    Ok(out)
+}
@@ -1,4 +1,163 @@
+use crate::cms::signature::certificate_signed_by;
 use crate::report::{Component, ReportVerdict};
+use crate::x509::name::display_name;
+use anyhow::{bail, Result};
+use der::asn1::ObjectIdentifier;
+use der::Decode;
+use std::time::SystemTime;
+use x509_cert::ext::pkix::{BasicConstraints, ExtendedKeyUsage, KeyUsage, KeyUsages};
+use x509_cert::Certificate;
+
+/// Usage étendu id-kp-timeStamping (RFC 3161 §2.3).
+pub const TIME_STAMPING: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.8");
+
+/// Usage étendu id-kp-OCSPSigning d’un répondeur délégué (RFC 6960 §4.2.2.2).
+pub const OCSP_SIGNING: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.3.9");
+
+/// Profondeur max de chaîne explorée.
+const MAX_CHAIN_DEPTH: usize = 10;
+
+/// Extensions dont la sémantique est prise en compte (ou sans effet sur la
+/// validation) : toute autre extension critique rend le certificat
+/// inutilisable (RFC 5280 §4.2).
+const KNOWN_EXTENSIONS: &[&str] = &[
+    "2.5.29.14", // subjectKeyIdentifier
+    "2.5.29.15", // keyUsage
+    "2.5.29.17", // subjectAltName
+    "2.5.29.18", // issuerAltName
+    "2.5.29.19", // basicConstraints
+    "2.5.29.35", // authorityKeyIdentifier
+    "2.5.29.37", // extKeyUsage
+];
+
+/// Certificats des ancrages PEM (`--trust`) ; les blocs illisibles sont ignorés.
+pub fn anchor_certificates(anchors_pem: &[String]) -> Vec<Certificate> {
+    anchors_pem
+        .iter()
+        .filter_map(|pem| Certificate::load_pem_chain(pem.as_bytes()).ok())
+        .flatten()
+        .collect()
+}
+
+/// Chaîne de `leaf` jusqu’à un ancrage, vérifiée nativement : signature de
+/// chaque certificat par son émetteur, période de validité à l’instant `at`,
+/// émetteurs intermédiaires CA habilités à signer des certificats dans la
+/// limite de leur pathLenConstraint, absence d’extension critique inconnue
+/// et, si demandé, usage étendu `purpose` du certificat terminal. Retourne la
+/// chaîne, du certificat terminal à l’ancrage.
+pub fn verify_chain(
+    leaf: &Certificate,
+    pool: &[Certificate],
+    anchors: &[Certificate],
+    purpose: Option<ObjectIdentifier>,
+    at: SystemTime,
+) -> Result<Vec<Certificate>> {
+    let name = display_name(&leaf.tbs_certificate.subject);
+    if !valid_at(leaf, at) {
+        bail!("Certificat « {name} » hors de sa période de validité");
+    }
+    if let Some(oid) = unknown_critical_extension(leaf) {
+        bail!("Certificat « {name} » : extension critique non prise en charge ({oid})");
+    }
+    if let Some(purpose) = purpose {
+        if !extended_key_usage(leaf).contains(&purpose) {
+            bail!("Certificat « {name} » sans l’usage étendu requis ({purpose})");
+        }
+    }
+    let mut chain = vec![leaf.clone()];
+    if !extend(&mut chain, pool, anchors, at) {
+        bail!("Certificat « {name} » non rattaché aux anchors fournis");
+    }
+    Ok(chain)
+}
+
+/// Prolonge `chain` par un émetteur dont la signature est vérifiée, jusqu’à
+/// un ancrage ; retour arrière si la branche n’aboutit pas.
+fn extend(
+    chain: &mut Vec<Certificate>,
+    pool: &[Certificate],
+    anchors: &[Certificate],
+    at: SystemTime,
+) -> bool {
+    let last = chain.last().expect("chaîne non vide").clone();
+    if anchors.contains(&last) {
+        return true;
+    }
+    if chain.len() >= MAX_CHAIN_DEPTH {
+        return false;
+    }
+    for issuer in anchors.iter().chain(pool) {
+        if issuer.tbs_certificate.subject != last.tbs_certificate.issuer
+            || chain.contains(issuer)
+            || !valid_at(issuer, at)
+            || !(anchors.contains(issuer) || may_issue(issuer, chain))
+            || !certificate_signed_by(&last, issuer).unwrap_or(false)
+        {
+            continue;
+        }
+        chain.push(issuer.clone());
+        if extend(chain, pool, anchors, at) {
+            return true;
+        }
+        chain.pop();
+    }
+    false
+}
+
+/// `at` est-il dans la période de validité de `cert` ?
+pub fn valid_at(cert: &Certificate, at: SystemTime) -> bool {
+    let validity = &cert.tbs_certificate.validity;
+    validity.not_before.to_system_time() <= at && at <= validity.not_after.to_system_time()
+}
+
+/// `issuer`, intermédiaire, peut-il émettre le dernier certificat de `chain` ?
+/// CA, keyCertSign s’il déclare un keyUsage, pathLenConstraint couvrant les
+/// intermédiaires non auto-émis déjà dans la chaîne, et aucune extension
+/// critique inconnue.
+fn may_issue(issuer: &Certificate, chain: &[Certificate]) -> bool {
+    let Some(bc) = extension::<BasicConstraints>(issuer, "2.5.29.19").filter(|bc| bc.ca) else {
+        return false;
+    };
+    let below = chain.iter().skip(1).filter(|c| !is_self_issued(c)).count();
+    let key_cert_sign = extension::<KeyUsage>(issuer, "2.5.29.15")
+        .map_or(true, |ku| ku.0.contains(KeyUsages::KeyCertSign));
+    key_cert_sign
+        && bc
+            .path_len_constraint
+            .map_or(true, |max| below <= usize::from(max))
+        && unknown_critical_extension(issuer).is_none()
+}
+
+fn is_self_issued(cert: &Certificate) -> bool {
+    cert.tbs_certificate.subject == cert.tbs_certificate.issuer
+}
+
+/// Première extension critique dont la sémantique n’est pas prise en charge.
+fn unknown_critical_extension(cert: &Certificate) -> Option<ObjectIdentifier> {
+    cert.tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .find(|e| e.critical && !KNOWN_EXTENSIONS.contains(&e.extn_id.to_string().as_str()))
+        .map(|e| e.extn_id)
+}
+
+/// Usages étendus (extKeyUsage) déclarés par `cert`.
+pub fn extended_key_usage(cert: &Certificate) -> Vec<ObjectIdentifier> {
+    extension::<ExtendedKeyUsage>(cert, "2.5.29.37")
+        .map(|eku| eku.0)
+        .unwrap_or_default()
+}
+
+fn extension<'a, T: Decode<'a>>(cert: &'a Certificate, oid: &str) -> Option<T> {
+    cert.tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .find(|e| e.extn_id.to_string() == oid)
+        .and_then(|e| T::from_der(e.extn_value.as_bytes()).ok())
+}
+
 #[allow(dead_code)]
 pub struct ChainResult {
     pub component: Component,
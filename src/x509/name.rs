@@ -0,0 +1,15 @@
+use der::asn1::ObjectIdentifier;
+use x509_cert::name::Name;
+
+const CN: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.3");
+
+/// CN du nom, à défaut la valeur du premier attribut (même règle que le
+/// backend OpenSSL).
+pub fn display_name(name: &Name) -> String {
+    let atvs = || name.0.iter().flat_map(|rdn| rdn.0.iter());
+    atvs()
+        .find(|atv| atv.oid == CN)
+        .or_else(|| atvs().next())
+        .map(|atv| String::from_utf8_lossy(atv.value.value()).into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,1332 @@
+#!/usr/bin/env python3
+"""Génère les PDF/CMS de test utilisés par `tests/*.rs`.
+
+Dépendances : python3 et la CLI `openssl` (>= 3.0). Seules les fixtures
+absentes sont produites ; `--force` régénère tout (PKI comprise). Les clés
+`*.key` sont des clés de TEST, sans aucune valeur hors de ce répertoire.
+
+    python3 tests/fixtures/gen_fixtures.py [--force]
+"""
+import base64
+import hashlib
+import os
+import struct
+import subprocess
+import sys
+import tempfile
+import time
+import zlib
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+CONTENTS_HEX_LEN = 16384  # réserve /Contents (octets hex)
+
+
+def openssl(*args, stdin=None):
+    return subprocess.run(
+        ["openssl", *args], input=stdin, check=True, capture_output=True
+    ).stdout
+
+
+def path(name):
+    return os.path.join(HERE, name)
+
+
+# --- PKI de test --------------------------------------------------------------
+
+
+def gen_pki():
+    openssl("req", "-x509", "-newkey", "rsa:2048", "-nodes", "-days", "10000",
+            "-subj", "/CN=Notar Test Root CA", "-keyout", path("ca.key"),
+            "-out", path("ca.pem"),
+            "-addext", "basicConstraints=critical,CA:TRUE",
+            "-addext", "keyUsage=critical,keyCertSign,cRLSign")
+    openssl("req", "-newkey", "rsa:2048", "-nodes", "-subj", "/CN=Maitre Dupont",
+            "-keyout", path("signer.key"), "-out", path("signer.csr"))
+    with tempfile.NamedTemporaryFile("w", suffix=".cnf", delete=False) as ext:
+        ext.write("basicConstraints=CA:FALSE\nkeyUsage=critical,digitalSignature,nonRepudiation\n")
+    openssl("x509", "-req", "-in", path("signer.csr"), "-CA", path("ca.pem"),
+            "-CAkey", path("ca.key"), "-CAcreateserial", "-days", "9000",
+            "-extfile", ext.name, "-out", path("signer.pem"))
+    os.unlink(ext.name)
+    for leftover in ("signer.csr", "ca.srl"):
+        if os.path.exists(path(leftover)):
+            os.unlink(path(leftover))
+
+
+TSA_CONFIG = """
+[ tsa ]
+default_tsa = tsa_config
+[ tsa_config ]
+serial = {serial}
+crypto_device = builtin
+signer_digest = sha256
+default_policy = 1.2.250.1.999.1
+digests = sha256, sha384, sha512
+accuracy = secs:1
+ordering = yes
+ess_cert_id_alg = sha256
+"""
+
+
+def gen_tsa():
+    # Autorité d’horodatage émise par la même racine (EKU timeStamping critique).
+    openssl("req", "-newkey", "rsa:2048", "-nodes", "-subj", "/CN=Notar Test TSA",
+            "-keyout", path("tsa.key"), "-out", path("tsa.csr"))
+    with tempfile.NamedTemporaryFile("w", suffix=".cnf", delete=False) as ext:
+        ext.write("basicConstraints=CA:FALSE\nkeyUsage=critical,digitalSignature\n"
+                  "extendedKeyUsage=critical,timeStamping\n")
+    openssl("x509", "-req", "-in", path("tsa.csr"), "-CA", path("ca.pem"),
+            "-CAkey", path("ca.key"), "-CAcreateserial", "-days", "9000",
+            "-extfile", ext.name, "-out", path("tsa.pem"))
+    os.unlink(ext.name)
+    for leftover in ("tsa.csr", "ca.srl"):
+        if os.path.exists(path(leftover)):
+            os.unlink(path(leftover))
+
+
+def gen_signer(name, newkey):
+    # Signataire émis par la racine de test avec une autre famille de clé.
+    if os.path.exists(path(name + ".pem")):
+        return
+    openssl("req", "-newkey", *newkey, "-nodes", "-subj", "/CN=Maitre Dupont " + name.upper(),
+            "-keyout", path(name + ".key"), "-out", path(name + ".csr"))
+    with tempfile.NamedTemporaryFile("w", suffix=".cnf", delete=False) as ext:
+        ext.write("basicConstraints=CA:FALSE\nkeyUsage=critical,digitalSignature,nonRepudiation\n")
+    openssl("x509", "-req", "-in", path(name + ".csr"), "-CA", path("ca.pem"),
+            "-CAkey", path("ca.key"), "-CAcreateserial", "-days", "9000",
+            "-extfile", ext.name, "-out", path(name + ".pem"))
+    os.unlink(ext.name)
+    for leftover in (name + ".csr", "ca.srl"):
+        if os.path.exists(path(leftover)):
+            os.unlink(path(leftover))
+
+
+SIGNER_KEYS = {
+    "p256": ["ec", "-pkeyopt", "ec_paramgen_curve:P-256"],
+    "p384": ["ec", "-pkeyopt", "ec_paramgen_curve:P-384"],
+    "p521": ["ec", "-pkeyopt", "ec_paramgen_curve:P-521"],
+    "ed25519": ["ed25519"],
+    "ed448": ["ed448"],
+}
+
+
+def timestamp_token(data):
+    with tempfile.TemporaryDirectory() as tmp:
+        def t(name):
+            return os.path.join(tmp, name)
+        with open(t("data"), "wb") as f:
+            f.write(data)
+        with open(t("tsa.cnf"), "w") as f:
+            f.write(TSA_CONFIG.format(serial=t("serial")))
+        openssl("ts", "-query", "-data", t("data"), "-sha256", "-cert", "-no_nonce",
+                "-out", t("query.tsq"))
+        openssl("ts", "-reply", "-config", t("tsa.cnf"), "-queryfile", t("query.tsq"),
+                "-signer", path("tsa.pem"), "-inkey", path("tsa.key"),
+                "-chain", path("ca.pem"), "-token_out", "-out", t("token.der"))
+        with open(t("token.der"), "rb") as f:
+            return f.read()
+
+
+def cms_detached(data, cades=False, *opts):
+    with tempfile.NamedTemporaryFile(delete=False) as f:
+        f.write(data)
+    try:
+        return openssl("cms", "-sign", "-binary", "-in", f.name, "-signer",
+                       path("signer.pem"), "-inkey", path("signer.key"),
+                       "-certfile", path("ca.pem"), "-md", "sha256", "-outform", "DER",
+                       *(["-cades"] if cades else []), *opts)
+    finally:
+        os.unlink(f.name)
+
+
+def ber_indefinite(der):
+    """Réencode ContentInfo et son [0] explicite en longueurs indéfinies (BER)."""
+    def split(tlv):
+        n = tlv[1] & 0x7f if tlv[1] & 0x80 else 0
+        length = int.from_bytes(tlv[2:2 + n], "big") if n else tlv[1]
+        return tlv[:1], tlv[2 + n:2 + n + length]
+
+    tag, body = split(der)
+    oid_len = 2 + body[1]
+    inner_tag, inner = split(body[oid_len:])
+    return (tag + b"\x80" + body[:oid_len] + inner_tag + b"\x80" + inner
+            + b"\0\0" + b"\0\0")
+
+
+def der_tlv(tag, body):
+    n = len(body)
+    if n < 0x80:
+        return bytes([tag, n]) + body
+    size = n.to_bytes((n.bit_length() + 7) // 8, "big")
+    return bytes([tag, 0x80 | len(size)]) + size + body
+
+
+def der_elements(body):
+    """Découpe le contenu d’un TLV DER en (tag, contenu, TLV complet)."""
+    out = []
+    while body:
+        n = body[1] & 0x7f if body[1] & 0x80 else 0
+        length = int.from_bytes(body[2:2 + n], "big") if n else body[1]
+        end = 2 + n + length
+        out.append((body[0], body[2 + n:end], body[:end]))
+        body = body[end:]
+    return out
+
+
+def edit_signer_info(der, edit):
+    """Réécrit les éléments du dernier SignerInfo (ContentInfo / [0] /
+    SignedData / signerInfos) et recalcule les longueurs englobantes."""
+    def descend(tlv, depth):
+        tag, body, _ = der_elements(tlv)[0]
+        elements = [raw for _, _, raw in der_elements(body)]
+        if depth == 0:
+            elements = edit(elements)
+        else:
+            elements[-1] = descend(elements[-1], depth - 1)
+        return der_tlv(tag, b"".join(elements))
+    return descend(der, 4)
+
+
+def signature_value(der):
+    found = []
+    edit_signer_info(der, lambda e: found.append(der_elements(e[5])[0][1]) or e)
+    return found[0]
+
+
+def backdate(der, utc_time):
+    """Remplace le signingTime d’une signature RSA et re-signe ses attributs."""
+    def edit(elements):
+        attrs = elements[3]
+        at = attrs.index(bytes.fromhex("06092a864886f70d010905"))
+        value = attrs.index(b"\x17\x0d", at) + 2
+        attrs = attrs[:value] + utc_time + attrs[value + 13:]
+        sig = openssl("dgst", "-sha256", "-sign", path("signer.key"), stdin=b"\x31" + attrs[1:])
+        return elements[:3] + [attrs, elements[4], der_tlv(0x04, sig)]
+    return edit_signer_info(der, edit)
+
+
+def with_signature_timestamp(der, stamped=None):
+    """Ajoute l’attribut non signé signatureTimeStampToken, horodatant la
+    valeur de signature (ou `stamped`)."""
+    token = timestamp_token(stamped if stamped is not None else signature_value(der))
+    attr = der_tlv(0x30, bytes.fromhex("060b2a864886f70d010910020e") + der_tlv(0x31, token))
+    return edit_signer_info(der, lambda e: e + [der_tlv(0xA1, attr)])
+
+
+def cms_cades(data):
+    # CAdES : attribut signé ESS signing-certificate-v2.
+    return cms_detached(data, cades=True)
+
+
+def cms_sha1_digest(data):
+    # adbe.pkcs7.sha1 : l’empreinte SHA-1 est le contenu encapsulé.
+    return cms_attached(hashlib.sha1(data).digest())
+
+
+def cms_sha1_digest_mismatch(data):
+    # Empreinte SHA-1 encapsulée conforme aux données, mais messageDigest
+    # calculé sur une autre empreinte (contenu substitué après signature).
+    other = hashlib.sha1(b"autre acte").digest()
+    return cms_attached(other).replace(other, hashlib.sha1(data).digest())
+
+
+def token_gen_time_altered(data):
+    # TSTInfo dont le genTime (année) est réécrit après signature du jeton :
+    # l’empreinte horodatée reste conforme, plus le messageDigest.
+    token = bytearray(timestamp_token(data))
+    year = token.index(b"\x18\x0f") + 5
+    token[year] = ord("0") if token[year] != ord("0") else ord("1")
+    return bytes(token)
+
+
+def tamper_last_signature(der):
+    def edit(elements):
+        sig = der_elements(elements[5])[0][1]
+        return elements[:5] + [der_tlv(0x04, sig[:-1] + bytes([sig[-1] ^ 1]))]
+    return edit_signer_info(der, edit)
+
+
+def cms_sha1_cosigned_tampered(data):
+    # Empreinte SHA-1 cosignée par la clé P-256, la dernière valeur de
+    # signature (dans l’ordre DER) altérée.
+    digest = hashlib.sha1(data).digest()
+    return tamper_last_signature(
+        merge_signed_data(cms_attached(digest), cms_attached(digest, "p256")))
+
+
+def token_cosigned_tampered(data):
+    # Jeton d’horodatage dont le TSTInfo est cosigné par la clé P-256, la
+    # dernière valeur de signature (dans l’ordre DER) altérée.
+    token = timestamp_token(data)
+    content = der_elements(der_elements(token)[0][1])[1][1]
+    encap = der_elements(der_elements(content)[0][1])[2][1]
+    tst_info = der_elements(der_elements(encap)[1][1])[0][1]
+    with tempfile.NamedTemporaryFile(delete=False) as f:
+        f.write(tst_info)
+    try:
+        cosigned = openssl("cms", "-sign", "-binary", "-nodetach", "-in", f.name,
+                           "-econtent_type", "1.2.840.113549.1.9.16.1.4",
+                           "-signer", path("p256.pem"), "-inkey", path("p256.key"),
+                           "-certfile", path("ca.pem"), "-md", "sha256", "-outform", "DER")
+    finally:
+        os.unlink(f.name)
+    return tamper_last_signature(merge_signed_data(token, cosigned))
+
+
+def cms_attached(content, signer="signer"):
+    with tempfile.NamedTemporaryFile(delete=False) as f:
+        f.write(content)
+    try:
+        return openssl("cms", "-sign", "-binary", "-nodetach", "-in", f.name, "-signer",
+                       path(signer + ".pem"), "-inkey", path(signer + ".key"),
+                       "-certfile", path("ca.pem"), "-md", "sha256", "-outform", "DER")
+    finally:
+        os.unlink(f.name)
+
+
+def pkcs1_sha1(data):
+    # adbe.x509.rsa_sha1 : signature PKCS#1 brute dans une OCTET STRING DER.
+    raw = openssl("dgst", "-sha1", "-sign", path("signer.key"), stdin=data)
+    return b"\x04\x82" + len(raw).to_bytes(2, "big") + raw
+
+
+def cert_der(name):
+    return openssl("x509", "-in", path(name), "-outform", "DER")
+
+
+CA_CONFIG = """
+[ca]
+default_ca = test_ca
+
+[test_ca]
+database = {dir}/index.txt
+crlnumber = {dir}/crlnumber
+default_md = sha256
+default_crl_days = 9000
+"""
+
+
+def revocation_data(revoked, *crl_dates, ca="ca"):
+    """CRL et réponse OCSP (DER) de la racine `ca` sur le certificat du
+    signataire.
+
+    `crl_dates` (lastUpdate, nextUpdate) fixe la période de validité de la CRL.
+    """
+    serial = openssl("x509", "-in", path("signer.pem"), "-noout", "-serial")
+    serial = serial.decode().strip().split("=", 1)[1]
+    status = "R\t491231235959Z\t240101000000Z" if revoked else "V\t491231235959Z\t"
+    with tempfile.TemporaryDirectory() as tmp:
+        with open(os.path.join(tmp, "index.txt"), "w") as f:
+            f.write("%s\t%s\tunknown\t/CN=Maitre Dupont\n" % (status, serial))
+        with open(os.path.join(tmp, "crlnumber"), "w") as f:
+            f.write("01\n")
+        with open(os.path.join(tmp, "ca.cnf"), "w") as f:
+            f.write(CA_CONFIG.format(dir=tmp))
+        crl = os.path.join(tmp, "crl.pem")
+        openssl("ca", "-gencrl", "-config", os.path.join(tmp, "ca.cnf"),
+                "-cert", path(ca + ".pem"), "-keyfile", path(ca + ".key"), "-out", crl,
+                *(["-crl_lastupdate", crl_dates[0], "-crl_nextupdate", crl_dates[1]]
+                  if crl_dates else []))
+        req, resp = os.path.join(tmp, "req.der"), os.path.join(tmp, "resp.der")
+        openssl("ocsp", "-issuer", path(ca + ".pem"), "-cert", path("signer.pem"),
+                "-no_nonce", "-reqout", req)
+        openssl("ocsp", "-index", os.path.join(tmp, "index.txt"), "-rsigner", path(ca + ".pem"),
+                "-rkey", path(ca + ".key"), "-CA", path(ca + ".pem"), "-reqin", req,
+                "-respout", resp, "-ndays", "9000")
+        with open(resp, "rb") as f:
+            return openssl("crl", "-in", crl, "-outform", "DER"), f.read()
+
+
+# --- Chiffrement (gestionnaire de sécurité standard) ---------------------------
+
+
+PASSWORD_PAD = bytes.fromhex("28bf4e5e4e758a4164004e56fffa01082e2e00b6d0683e802f0ca9fe6453697a")
+
+
+def rc4(key, data):
+    s, j = list(range(256)), 0
+    for i in range(256):
+        j = (j + s[i] + key[i % len(key)]) % 256
+        s[i], s[j] = s[j], s[i]
+    out, i, j = bytearray(), 0, 0
+    for b in data:
+        i = (i + 1) % 256
+        j = (j + s[i]) % 256
+        s[i], s[j] = s[j], s[i]
+        out.append(b ^ s[(s[i] + s[j]) % 256])
+    return bytes(out)
+
+
+def aes(key, data, iv=None, pad=False):
+    mode = "ecb" if iv is None else "cbc"
+    args = ["enc", "-aes-%d-%s" % (len(key) * 8, mode), "-K", key.hex()]
+    args += ["-iv", iv.hex()] if iv is not None else []
+    return openssl(*args, *([] if pad else ["-nopad"]), stdin=data)
+
+
+def hash_2b(password, salt, udata):
+    k = hashlib.sha256(password + salt + udata).digest()
+    rounds = 0
+    while True:
+        e = aes(k[:16], (password + k + udata) * 64, iv=k[16:32])
+        k = (hashlib.sha256, hashlib.sha384, hashlib.sha512)[sum(e[:16]) % 3](e).digest()
+        rounds += 1
+        if rounds >= 64 and e[-1] <= rounds - 32:
+            return k[:32]
+
+
+class StandardSecurity:
+    """Chiffrement des fixtures : RC4 128 bits (R3), AES-128 (R4) ou AES-256 (R6)."""
+
+    P = -1028
+
+    def __init__(self, revision, user, owner=b"proprio"):
+        self.revision = revision
+        self.id0 = hashlib.md5(b"notar-verify R%d" % revision).digest()
+        p = struct.pack("<i", self.P)
+        if revision in (3, 4):
+            pad = lambda pw: (pw + PASSWORD_PAD)[:32]
+            h = hashlib.md5(pad(owner)).digest()
+            for _ in range(50):
+                h = hashlib.md5(h).digest()
+            o = pad(user)
+            for i in range(20):
+                o = rc4(bytes(b ^ i for b in h), o)
+            key = hashlib.md5(pad(user) + o + p + self.id0).digest()
+            for _ in range(50):
+                key = hashlib.md5(key).digest()
+            u = hashlib.md5(PASSWORD_PAD + self.id0).digest()
+            for i in range(20):
+                u = rc4(bytes(b ^ i for b in key), u)
+            self.key = key
+            filters = (b" /CF << /StdCF << /AuthEvent /DocOpen /CFM /AESV2 /Length 16 >> >>"
+                       b" /StmF /StdCF /StrF /StdCF" if revision == 4 else b"")
+            self.dictionary = (b"<< /Filter /Standard /V %d /R %d /Length 128 /P %d%s /O <%s> /U <%s> >>"
+                               % (revision - 1 if revision == 3 else 4, revision, self.P, filters,
+                                  o.hex().encode(), (u + bytes(16)).hex().encode()))
+        else:
+            key = os.urandom(32)
+            uvs, uks, ovs, oks = (os.urandom(8) for _ in range(4))
+            u = hash_2b(user, uvs, b"") + uvs + uks
+            ue = aes(hash_2b(user, uks, b""), key, iv=bytes(16))
+            o = hash_2b(owner, ovs, u) + ovs + oks
+            oe = aes(hash_2b(owner, oks, u), key, iv=bytes(16))
+            perms = aes(key, p + b"\xff" * 4 + b"Tadb" + os.urandom(4))
+            self.key = key
+            self.dictionary = (
+                b"<< /Filter /Standard /V 5 /R 6 /Length 256 /P %d"
+                b" /CF << /StdCF << /AuthEvent /DocOpen /CFM /AESV3 /Length 32 >> >>"
+                b" /StmF /StdCF /StrF /StdCF" % self.P +
+                b"".join(b" /%s <%s>" % (k, v.hex().encode())
+                         for k, v in ((b"O", o), (b"U", u), (b"OE", oe), (b"UE", ue), (b"Perms", perms)))
+                + b" >>")
+
+    def trailer(self, num):
+        ident = self.id0.hex().encode()
+        return b" /Encrypt %d 0 R /ID [<%s> <%s>]" % (num, ident, ident)
+
+    def encrypt(self, num, data):
+        object_key = self.key + num.to_bytes(3, "little") + b"\0\0"
+        if self.revision == 3:
+            return rc4(hashlib.md5(object_key).digest(), data)
+        key = hashlib.md5(object_key + b"sAlT").digest() if self.revision == 4 else self.key
+        iv = os.urandom(16)
+        return iv + aes(key, data, iv=iv, pad=True)
+
+    def string(self, num, data):
+        return b"<%s>" % self.encrypt(num, data).hex().encode()
+
+    def stream(self, num, data):
+        data = self.encrypt(num, data)
+        return b"<< /Length %d >>\nstream\n" % len(data) + data + b"\nendstream"
+
+
+# --- Écriture PDF (révisions incrémentales) -----------------------------------
+
+
+class Pdf:
+    def __init__(self, xref_shift=0):
+        self.data = bytearray(b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n")
+        self.prev = None
+        self.size = 1
+        # Décalage des offsets startxref/Prev (appliance défectueuse).
+        self.xref_shift = xref_shift
+
+    def revision(self, objs, root=1, trailer_extra=b""):
+        """Ajoute une révision contenant `objs` ({numéro: corps}) + xref + trailer."""
+        offsets = {}
+        for num in sorted(objs):
+            offsets[num] = len(self.data)
+            self.data += b"%d 0 obj\n" % num + objs[num] + b"\nendobj\n"
+        xref = len(self.data) + self.xref_shift
+        self.data += b"xref\n"
+        if self.prev is None:
+            self.data += b"0 1\n0000000000 65535 f \n"
+        for num in sorted(offsets):
+            self.data += b"%d 1\n%010d 00000 n \n" % (num, offsets[num])
+        self.size = max(self.size, max(objs) + 1)
+        self.data += b"trailer\n<< /Size %d /Root %d 0 R" % (self.size, root)
+        if self.prev is not None:
+            self.data += b" /Prev %d" % self.prev
+        self.data += trailer_extra + b" >>\nstartxref\n%d\n%%%%EOF\n" % xref
+        self.prev = xref
+
+    def revision_xref_stream(self, objs, packed, root=1):
+        """Comme `revision`, avec une table en flux /XRef (PDF 1.5) et les objets
+        `packed` regroupés dans un flux d’objets."""
+        offsets = {}
+        for num in sorted(objs):
+            offsets[num] = len(self.data)
+            self.data += b"%d 0 obj\n" % num + objs[num] + b"\nendobj\n"
+        container = max([self.size, *objs, *packed]) + 1
+        header, body = b"", b""
+        for num in sorted(packed):
+            header += b"%d %d " % (num, len(body))
+            body += packed[num] + b"\n"
+        stm = zlib.compress(header + body)
+        offsets[container] = len(self.data)
+        self.data += (b"%d 0 obj\n<< /Type /ObjStm /N %d /First %d /Filter /FlateDecode"
+                      b" /Length %d >>\nstream\n" % (container, len(packed), len(header), len(stm))
+                      + stm + b"\nendstream\nendobj\n")
+        xref_num = container + 1
+        xref = len(self.data)
+        offsets[xref_num] = xref
+        entries = {num: struct.pack(">BIH", 1, off, 0) for num, off in offsets.items()}
+        for index, num in enumerate(sorted(packed)):
+            entries[num] = struct.pack(">BIH", 2, container, index)
+        self.size = max(self.size, xref_num + 1)
+        index = b" ".join(b"%d 1" % num for num in sorted(entries))
+        rows = zlib.compress(b"".join(entries[num] for num in sorted(entries)))
+        self.data += (b"%d 0 obj\n<< /Type /XRef /W [1 4 2] /Index [%s] /Size %d /Root %d 0 R"
+                      b" /Prev %d /Filter /FlateDecode /Length %d >>\nstream\n"
+                      % (xref_num, index, self.size, root, self.prev, len(rows))
+                      + rows + b"\nendstream\nendobj\nstartxref\n%d\n%%%%EOF\n" % xref)
+        self.prev = xref
+
+    def sign_last(self, signer=cms_detached, ranges=None):
+        """Renseigne /ByteRange et /Contents du dernier placeholder de signature.
+
+        `ranges(lt, gt, n)` permet de forger un ByteRange non conforme.
+        """
+        lt = self.data.rindex(b"/Contents <") + len(b"/Contents ")
+        gt = self.data.index(b">", lt)
+        br_at = self.data.rindex(b"/ByteRange [", 0, lt)
+        br_end = self.data.index(b"]", br_at) + 1
+        br = (ranges or default_ranges)(lt, gt, len(self.data))
+        text = b"/ByteRange [" + b" ".join(b"%d" % v for v in br)
+        width = br_end - br_at - 1
+        self.data[br_at:br_end] = text.ljust(width) + b"]"
+        signed = b"".join(bytes(self.data[br[i]:br[i] + br[i + 1]]) for i in range(0, len(br), 2))
+        der = signer(signed).hex().encode()
+        assert len(der) <= gt - lt - 1, "réserve /Contents insuffisante"
+        self.data[lt + 1:gt] = der.ljust(gt - lt - 1, b"0")
+
+    def write(self, name):
+        with open(path(name), "wb") as f:
+            f.write(self.data)
+
+
+def default_ranges(lt, gt, n):
+    return (0, lt, gt + 1, n - gt - 1)
+
+
+def sig_placeholder(extra=b"", sub_filter=b"adbe.pkcs7.detached", typ=b"Sig"):
+    return (b"<< /Type /%s /Filter /Adobe.PPKLite /SubFilter /%s" % (typ, sub_filter) +
+            b" /ByteRange [0 " + b" " * 32 + b"]"
+            b" /Contents <" + b"0" * CONTENTS_HEX_LEN + b">" + extra + b" >>")
+
+
+def text_field(name, value=None):
+    v = b" /V (%s)" % value if value else b""
+    return (b"<< /FT /Tx /Type /Annot /Subtype /Widget /T (%s) /Rect [72 600 300 620]"
+            b" /P 3 0 R /DA (/Helv 0 Tf 0 g)%s >>" % (name, v))
+
+
+def docmdp_reference(p):
+    return (b" /Reference [<< /Type /SigRef /TransformMethod /DocMDP"
+            b" /TransformParams << /Type /TransformParams /P %d /V /1.2 >> >>]" % p)
+
+
+def fieldmdp_reference(fields):
+    names = b" ".join(b"(%s)" % f for f in fields)
+    return (b" /Reference [<< /Type /SigRef /TransformMethod /FieldMDP"
+            b" /TransformParams << /Type /TransformParams /Action /Include"
+            b" /Fields [%s] /V /1.2 >> >>]" % names)
+
+
+def sig_field(name, page=3, sig=None):
+    v = b" /V %d 0 R" % sig if sig else b""
+    return (b"<< /FT /Sig /Type /Annot /Subtype /Widget /T (%s) /F 132"
+            b" /Rect [0 0 0 0] /P %d 0 R%s >>" % (name, page, v))
+
+
+def base_document(fields, page_extra=b"", catalog_extra=b""):
+    """Catalogue (1), pages (2), page (3), contenu (4), police (5) + champs.
+
+    `fields` est une liste ordonnée de (numéro, corps) : c’est l’ordre AcroForm.
+    """
+    refs = b" ".join(b"%d 0 R" % num for num, _ in fields)
+    content = b"BT /F1 18 Tf 72 720 Td (Acte notarie) Tj ET"
+    objs = {
+        1: b"<< /Type /Catalog /Pages 2 0 R /AcroForm << /Fields [%s] /SigFlags 3 >>%s >>"
+           % (refs, catalog_extra),
+        2: b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        3: b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R"
+           b" /Resources << /Font << /F1 5 0 R >> >> /Annots [%s]%s >>" % (refs, page_extra),
+        4: b"<< /Length %d >>\nstream\n%s\nendstream" % (len(content), content),
+        5: b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>",
+    }
+    objs.update(dict(fields))
+    return objs
+
+
+# --- Fixtures -----------------------------------------------------------------
+
+
+def two_signatures():
+    # Ordre AcroForm volontairement inverse de l’ordre des numéros d’objets.
+    pdf = Pdf()
+    pdf.revision(base_document([(7, sig_field(b"Client")), (6, sig_field(b"Notaire"))]))
+    pdf.revision({7: sig_field(b"Client", sig=8), 8: sig_placeholder()})
+    pdf.sign_last()
+    pdf.revision({6: sig_field(b"Notaire", sig=9), 9: sig_placeholder()})
+    pdf.sign_last()
+    pdf.write("two_signatures.pdf")
+
+
+def byterange_gap():
+    # Trou élargi d’un octet avant `<` et couverture arrêtée avant `%%EOF`.
+    pdf = Pdf()
+    pdf.revision(base_document([(6, sig_field(b"Notaire"))]))
+    pdf.revision({6: sig_field(b"Notaire", sig=7), 7: sig_placeholder()})
+    pdf.sign_last(ranges=lambda lt, gt, n: (0, lt - 1, gt + 1, n - gt - 8))
+    pdf.write("byterange_gap.pdf")
+
+
+def content_changed_after_signing():
+    # Signature sur la révision 2, puis remplacement du contenu de page (4).
+    pdf = Pdf()
+    pdf.revision(base_document([(6, sig_field(b"Notaire"))]))
+    pdf.revision({6: sig_field(b"Notaire", sig=7), 7: sig_placeholder()})
+    pdf.sign_last()
+    forged = b"BT /F1 18 Tf 72 720 Td (Acte modifie) Tj ET"
+    pdf.revision({4: b"<< /Length %d >>\nstream\n%s\nendstream" % (len(forged), forged)})
+    pdf.write("content_changed_after_signing.pdf")
+
+
+def disguised_content_change(name, catalog_extra=b"", trailer_extra=b""):
+    # Même remplacement du contenu de page (4), que la révision ajoutée
+    # rattache aussi au catalogue (/Metadata, /DSS) ou au trailer (/Info)
+    # pour le faire passer pour une mise à jour de métadonnées ou de DSS.
+    def build():
+        fields = [(6, sig_field(b"Notaire"))]
+        pdf = Pdf()
+        pdf.revision(base_document(fields))
+        pdf.revision({6: sig_field(b"Notaire", sig=7), 7: sig_placeholder()})
+        pdf.sign_last()
+        forged = b"BT /F1 18 Tf 72 720 Td (Acte modifie) Tj ET"
+        pdf.revision({
+            1: base_document(fields, catalog_extra=catalog_extra)[1],
+            4: b"<< /Length %d >>\nstream\n%s\nendstream" % (len(forged), forged),
+        }, trailer_extra=trailer_extra)
+        pdf.write(name)
+    return build
+
+
+def certified_then_filled(p):
+    # Certification DocMDP /P p, puis remplissage du champ texte « Montant ».
+    def build():
+        fields = [(6, sig_field(b"Notaire")), (8, text_field(b"Montant"))]
+        pdf = Pdf()
+        pdf.revision(base_document(fields))
+        perms = base_document(fields, catalog_extra=b" /Perms << /DocMDP 7 0 R >>")[1]
+        pdf.revision({1: perms, 6: sig_field(b"Notaire", sig=7),
+                      7: sig_placeholder(docmdp_reference(p))})
+        pdf.sign_last()
+        pdf.revision({8: text_field(b"Montant", b"120000 EUR")})
+        pdf.write("certified_p%d_then_filled.pdf" % p)
+    return build
+
+
+def content_retagged_objstm():
+    # Certification /P 2, puis contenu de page (4) réécrit sous /Type /ObjStm
+    # (/N, /First compris) pour passer pour un flux d’objets de la révision.
+    fields = [(6, sig_field(b"Notaire")), (8, text_field(b"Montant"))]
+    pdf = Pdf()
+    pdf.revision(base_document(fields))
+    perms = base_document(fields, catalog_extra=b" /Perms << /DocMDP 7 0 R >>")[1]
+    pdf.revision({1: perms, 6: sig_field(b"Notaire", sig=7),
+                  7: sig_placeholder(docmdp_reference(2))})
+    pdf.sign_last()
+    forged = b"BT /F1 18 Tf 72 720 Td (Acte modifie) Tj ET"
+    pdf.revision({4: b"<< /Type /ObjStm /N 1 /First 4 /Length %d >>\nstream\n%s\nendstream"
+                  % (len(forged), forged)})
+    pdf.write("content_retagged_objstm.pdf")
+
+
+def locked_field_filled():
+    # Signature d’approbation verrouillant « Montant » (FieldMDP), puis remplissage.
+    fields = [(6, sig_field(b"Notaire")), (8, text_field(b"Montant"))]
+    lock = b" /Lock << /Type /SigFieldLock /Action /Include /Fields [(Montant)] >>"
+    pdf = Pdf()
+    pdf.revision(base_document(fields))
+    pdf.revision({6: sig_field(b"Notaire", sig=7)[:-3] + lock + b" >>",
+                  7: sig_placeholder(fieldmdp_reference([b"Montant"]))})
+    pdf.sign_last()
+    pdf.revision({8: text_field(b"Montant", b"1 EUR")})
+    pdf.write("locked_field_filled.pdf")
+
+
+def hide_and_replace(name, retag=b""):
+    # Contenu de substitution (10) présent mais non référencé dans la révision
+    # signée, puis raccroché à la page par une mise à jour incrémentale.
+    # `retag` le fait passer pour un flux de structure (/Type /ObjStm…).
+    def build():
+        pdf = Pdf()
+        objs = base_document([(6, sig_field(b"Notaire"))])
+        forged = b"BT /F1 18 Tf 72 720 Td (Acte falsifie) Tj ET"
+        objs[10] = b"<<%s /Length %d >>\nstream\n%s\nendstream" % (retag, len(forged), forged)
+        pdf.revision(objs)
+        pdf.revision({6: sig_field(b"Notaire", sig=7), 7: sig_placeholder()})
+        pdf.sign_last()
+        page = base_document([(6, None)])[3].replace(b"/Contents 4 0 R", b"/Contents 10 0 R")
+        pdf.revision({3: page})
+        pdf.write(name)
+    return build
+
+
+def document_timestamp():
+    # Signature du notaire (révision 2) puis horodatage de document (révision 3).
+    fields = [(6, sig_field(b"Notaire")), (8, sig_field(b"Horodatage"))]
+    pdf = Pdf()
+    pdf.revision(base_document(fields))
+    pdf.revision({6: sig_field(b"Notaire", sig=7), 7: sig_placeholder()})
+    pdf.sign_last()
+    pdf.revision({8: sig_field(b"Horodatage", sig=9),
+                  9: sig_placeholder(sub_filter=b"ETSI.RFC3161", typ=b"DocTimeStamp")})
+    pdf.sign_last(timestamp_token)
+    pdf.write("document_timestamp.pdf")
+
+
+def dss_revision(pdf, fields, revoked=False, stale=False):
+    """Révision ajoutant un DSS (certificats, CRL, OCSP) et l’entrée VRI de la
+    dernière signature ; objets 10 à 14. `stale` : CRL périmée, sans OCSP."""
+    lt = pdf.data.rindex(b"/Contents <") + len(b"/Contents <")
+    contents = bytes.fromhex(pdf.data[lt:pdf.data.index(b">", lt)].decode())
+    vri_key = hashlib.sha1(contents).hexdigest().upper().encode()
+
+    crl_dates = ("240101000000Z", "240201000000Z") if stale else ()
+    crl, ocsp = revocation_data(revoked, *crl_dates)
+    streams = {11: cert_der("ca.pem"), 12: cert_der("signer.pem"), 13: crl, 14: ocsp}
+    if stale:
+        del streams[14]
+    objs = {num: b"<< /Length %d >>\nstream\n" % len(data) + data + b"\nendstream"
+            for num, data in streams.items()}
+    objs[1] = base_document(fields, catalog_extra=b" /DSS 10 0 R")[1]
+    ocsps, vri_ocsp = (b"", b"") if stale else (b" /OCSPs [14 0 R]", b" /OCSP [14 0 R]")
+    objs[10] = (b"<< /Type /DSS /Certs [11 0 R 12 0 R] /CRLs [13 0 R]%s"
+                b" /VRI << /%s << /Cert [12 0 R]%s >> >> >>" % (ocsps, vri_key, vri_ocsp))
+    pdf.revision(objs)
+
+
+def ltv_dss(name, revoked, stale=False):
+    # Signature (révision 2) puis DSS : certificats, CRL, OCSP et entrée VRI.
+    def build():
+        fields = [(6, sig_field(b"Notaire"))]
+        pdf = Pdf()
+        pdf.revision(base_document(fields))
+        pdf.revision({6: sig_field(b"Notaire", sig=7), 7: sig_placeholder()})
+        pdf.sign_last()
+        dss_revision(pdf, fields, revoked, stale)
+        pdf.write(name)
+    return build
+
+
+def pades_lta():
+    # Signature CAdES (révision 2), DSS (révision 3), horodatage de document (révision 4).
+    fields = [(6, sig_field(b"Notaire")), (8, sig_field(b"Horodatage"))]
+    pdf = Pdf()
+    pdf.revision(base_document(fields))
+    pdf.revision({6: sig_field(b"Notaire", sig=7),
+                  7: sig_placeholder(sub_filter=b"ETSI.CAdES.detached")})
+    pdf.sign_last(cms_cades)
+    dss_revision(pdf, fields)
+    pdf.revision({8: sig_field(b"Horodatage", sig=9),
+                  9: sig_placeholder(sub_filter=b"ETSI.RFC3161", typ=b"DocTimeStamp")})
+    pdf.sign_last(timestamp_token)
+    pdf.write("pades_lta.pdf")
+
+
+def pades_bt(name, stamped):
+    # Signature CAdES portant un horodatage de signature (B-T), éventuellement
+    # émis sur une autre valeur que celle de la signature.
+    def build():
+        fields = [(6, sig_field(b"Notaire"))]
+        pdf = Pdf()
+        pdf.revision(base_document(fields))
+        pdf.revision({6: sig_field(b"Notaire", sig=7),
+                      7: sig_placeholder(sub_filter=b"ETSI.CAdES.detached")})
+        pdf.sign_last(lambda data: with_signature_timestamp(cms_cades(data), stamped))
+        pdf.write(name)
+    return build
+
+
+def signature_metadata():
+    # /M proche de la signature (Client) ou très antérieur (Notaire) ; textes
+    # en UTF-16BE et en PDFDocEncoding.
+    now = time.strftime("D:%Y%m%d%H%M%S+00'00'", time.gmtime()).encode()
+    name = b"<" + ("\ufeffMa\u00eetre Dupont".encode("utf-16-be")).hex().encode() + b">"
+    client = (b" /M (%s) /Name %s /Reason (Acte \x84 lecture faite) /Location (Lyon)"
+              b" /ContactInfo (etude@example.test)" % (now, name))
+    notaire = b" /M (D:20010101120000+01'00') /Name (Ma\xeetre Martin)"
+    fields = [(6, sig_field(b"Client")), (8, sig_field(b"Notaire"))]
+    pdf = Pdf()
+    pdf.revision(base_document(fields))
+    pdf.revision({6: sig_field(b"Client", sig=7), 7: sig_placeholder(client)})
+    pdf.sign_last()
+    pdf.revision({8: sig_field(b"Notaire", sig=9), 9: sig_placeholder(notaire)})
+    pdf.sign_last()
+    pdf.write("signature_metadata.pdf")
+
+
+def signature_widgets():
+    # Client : champ parent et widget enfant visible en page 2, avec apparence ;
+    # Notaire : champ fusionné avec un widget invisible (Rect nul) en page 1.
+    def client(sig=None):
+        v = b" /V %d 0 R" % sig if sig else b""
+        return b"<< /FT /Sig /T (Client) /Kids [10 0 R]%s >>" % v
+
+    stamp = b"BT /F1 10 Tf 4 30 Td (Signe par Client) Tj ET"
+    objs = base_document([(6, client()), (8, sig_field(b"Notaire"))])
+    objs[2] = b"<< /Type /Pages /Kids [3 0 R 12 0 R] /Count 2 >>"
+    objs[3] = objs[3].replace(b"/Annots [6 0 R 8 0 R]", b"/Annots [8 0 R]")
+    objs[10] = (b"<< /Type /Annot /Subtype /Widget /Parent 6 0 R /F 4 /P 12 0 R"
+                b" /Rect [350 50 550 120] /AP << /N 11 0 R >> >>")
+    objs[11] = (b"<< /Type /XObject /Subtype /Form /BBox [0 0 200 70]"
+                b" /Resources << /Font << /F1 5 0 R >> >> /Length %d >>\nstream\n%s\nendstream"
+                % (len(stamp), stamp))
+    objs[12] = (b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792]"
+                b" /Annots [10 0 R] >>")
+    pdf = Pdf()
+    pdf.revision(objs)
+    pdf.revision({6: client(sig=7), 7: sig_placeholder()})
+    pdf.sign_last()
+    pdf.revision({8: sig_field(b"Notaire", sig=9), 9: sig_placeholder()})
+    pdf.sign_last()
+    pdf.write("signature_widgets.pdf")
+
+
+def pending_fields():
+    # Trois champs de signature, seul Client est signé : Témoin (visible) et
+    # Notaire (widget enfant) restent vides.
+    temoin = sig_field(b"Temoin").replace(b"/Rect [0 0 0 0]", b"/Rect [72 72 272 142]")
+    notaire = b"<< /FT /Sig /T (Notaire) /Kids [11 0 R] >>"
+    fields = [(6, sig_field(b"Client")), (8, temoin), (10, notaire)]
+    objs = base_document(fields)
+    objs[3] = objs[3].replace(b"10 0 R]", b"11 0 R]")
+    objs[11] = (b"<< /Type /Annot /Subtype /Widget /Parent 10 0 R /F 4 /P 3 0 R"
+                b" /Rect [350 72 550 142] >>")
+    pdf = Pdf()
+    pdf.revision(objs)
+    pdf.revision({6: sig_field(b"Client", sig=7), 7: sig_placeholder()})
+    pdf.sign_last()
+    pdf.write("pending_fields.pdf")
+
+
+def broken_xref():
+    # Offsets startxref et /Prev décalés : la structure est illisible, seules
+    # les signatures restent repérables dans les octets.
+    pdf = Pdf(xref_shift=7)
+    pdf.revision(base_document([(7, sig_field(b"Client")), (6, sig_field(b"Notaire"))]))
+    pdf.revision({7: sig_field(b"Client", sig=8), 8: sig_placeholder()})
+    pdf.sign_last()
+    pdf.revision({6: sig_field(b"Notaire", sig=9), 9: sig_placeholder()})
+    pdf.sign_last()
+    pdf.write("broken_xref.pdf")
+
+
+def contents_padding():
+    # Client : octets non nuls glissés après la CMS, dans le bourrage de
+    # /Contents ; Notaire : CMS BER à longueurs indéfinies, bourrage nul.
+    pdf = Pdf()
+    pdf.revision(base_document([(6, sig_field(b"Client")), (8, sig_field(b"Notaire"))]))
+    pdf.revision({6: sig_field(b"Client", sig=7), 7: sig_placeholder()})
+    pdf.sign_last(lambda data: cms_detached(data) + b"\0\0cache")
+    pdf.revision({8: sig_field(b"Notaire", sig=9), 9: sig_placeholder()})
+    pdf.sign_last(lambda data: ber_indefinite(cms_detached(data)))
+    pdf.write("contents_padding.pdf")
+
+
+def portfolio_bytes(files):
+    """Portfolio non signé : un fichier intégré (FlateDecode) par entrée de
+    `files` ({nom: octets}), arbre des noms /EmbeddedFiles en deux /Kids."""
+    objs = {
+        1: b"<< /Type /Catalog /Pages 2 0 R /Names << /EmbeddedFiles 6 0 R >>"
+           b" /Collection << /Type /Collection /View /D >> >>",
+        2: b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        3: b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] >>",
+    }
+    leaves = []
+    num = 10
+    for name in sorted(files):
+        packed = zlib.compress(files[name])
+        objs[num] = (b"<< /Type /EmbeddedFile /Filter /FlateDecode /Params << /Size %d >>"
+                     b" /Length %d >>\nstream\n" % (len(files[name]), len(packed))
+                     + packed + b"\nendstream")
+        objs[num + 1] = (b"<< /Type /Filespec /F (%s) /UF (%s) /EF << /F %d 0 R >> >>"
+                         % (name.encode(), name.encode(), num))
+        leaves.append(b"(%s) %d 0 R" % (name.encode(), num + 1))
+        num += 2
+    half = (len(leaves) + 1) // 2
+    objs[7] = b"<< /Names [%s] >>" % b" ".join(leaves[:half])
+    objs[8] = b"<< /Names [%s] >>" % b" ".join(leaves[half:])
+    objs[6] = b"<< /Kids [7 0 R 8 0 R] >>"
+    pdf = Pdf()
+    pdf.revision(objs)
+    return bytes(pdf.data)
+
+
+def portfolio():
+    # Dossier : acte signé, annexe + signature détachée, pièce non signée et
+    # sous-dossier contenant lui-même un PDF signé.
+    def read(name):
+        with open(path(name), "rb") as f:
+            return f.read()
+
+    annexe = b"Annexe : etat des lieux\n"
+    files = {
+        "acte.pdf": read("two_signatures.pdf"),
+        "annexe.txt": annexe,
+        "annexe.txt.p7s": cms_detached(annexe),
+        "note.txt": b"Pi\xe8ce non sign\xe9e\n",
+        "sous-dossier.pdf": portfolio_bytes({"ancien.pdf": read("pkcs7_sha1.pdf")}),
+    }
+    with open(path("portfolio.pdf"), "wb") as f:
+        f.write(portfolio_bytes(files))
+
+
+def portfolio_bomb():
+    # Pièce jointe de 4 Mio de zéros, quelques Kio une fois compressée.
+    with open(path("portfolio_bomb.pdf"), "wb") as f:
+        f.write(portfolio_bytes({"bombe.bin": bytes(4 << 20)}))
+
+
+def xfa_usage_rights():
+    # Formulaire XFA dynamique activé par une signature de droits d’usage UR3
+    # (/Perms), puis signé par le client dans une seconde révision.
+    ur3 = (b" /Name (ARE Acrobat Product v8.0 P23 0002337) /M (D:20240105090000Z)"
+           b" /Reference [<< /Type /SigRef /TransformMethod /UR3"
+           b" /TransformParams << /Type /TransformParams /V /2.2 /Document [/FullSave]"
+           b" /Form [/FillIn /Import] /Signature [/Modify] >> >>]")
+    config = zlib.compress(b"<config><present><script><dynamicRender>required"
+                           b"</dynamicRender></script></present></config>")
+    template = b"<template><subform name=\"acte\"/></template>"
+    fields = [(6, sig_field(b"Client"))]
+    objs = base_document(fields, catalog_extra=b" /Perms << /UR3 10 0 R >>")
+    objs[1] = objs[1].replace(b"/SigFlags 3", b"/SigFlags 3 /XFA [(config) 11 0 R (template) 12 0 R]")
+    objs[10] = sig_placeholder(ur3)
+    objs[11] = (b"<< /Filter /FlateDecode /Length %d >>\nstream\n" % len(config)
+                + config + b"\nendstream")
+    objs[12] = b"<< /Length %d >>\nstream\n%s\nendstream" % (len(template), template)
+    pdf = Pdf()
+    pdf.revision(objs)
+    pdf.sign_last()
+    pdf.revision({6: sig_field(b"Client", sig=7), 7: sig_placeholder()})
+    pdf.sign_last()
+    pdf.write("xfa_usage_rights.pdf")
+
+
+def detached_cms():
+    # Signature CMS détachée (.p7s) d’un acte, certificats et attributs signés.
+    data = b"Acte de vente - minute 2024/117\n"
+    with open(path("detached.txt"), "wb") as f:
+        f.write(data)
+    with open(path("detached.p7s"), "wb") as f:
+        f.write(cms_detached(data))
+
+
+def attached_p7m():
+    # Acte enveloppé dans sa signature (.p7m), en DER puis en BER à
+    # longueurs indéfinies comme en produisent les outils de signature en flux.
+    with open(path("detached.txt"), "rb") as f:
+        der = cms_attached(f.read())
+    with open(path("attached.p7m"), "wb") as f:
+        f.write(der)
+    with open(path("attached_ber.p7m"), "wb") as f:
+        f.write(ber_indefinite(der))
+
+
+def attached_tampered_p7m():
+    # attached.p7m dont l’acte enveloppé est altéré après signature.
+    with open(path("attached.p7m"), "rb") as f:
+        der = f.read()
+    with open(path("attached_tampered.p7m"), "wb") as f:
+        f.write(der.replace(b"minute 2024/117", b"minute 2024/118"))
+
+
+def nested_p7m():
+    # attached.p7m contresigné en l’enveloppant à nouveau (.p7m.p7m) par un
+    # second signataire, la couche interne en DER puis en Base64 (lignes de 76).
+    gen_signer("p256", SIGNER_KEYS["p256"])
+    with open(path("attached.p7m"), "rb") as f:
+        inner = f.read()
+    with open(path("nested.p7m.p7m"), "wb") as f:
+        f.write(cms_attached(inner, "p256"))
+    b64 = base64.encodebytes(inner)
+    with open(path("nested_b64.p7m.p7m"), "wb") as f:
+        f.write(cms_attached(b64, "p256"))
+
+
+def detached_with(name, signer, md, *opts):
+    # Même acte que detached.p7s, signé avec une autre clé / empreinte.
+    def build():
+        key = "signer" if signer == "rsa" else signer
+        if signer != "rsa":
+            gen_signer(signer, SIGNER_KEYS[signer])
+        with open(path("detached.txt"), "rb") as f:
+            data = f.read()
+        with tempfile.NamedTemporaryFile(delete=False) as f:
+            f.write(data)
+        try:
+            der = openssl("cms", "-sign", "-binary", "-in", f.name, "-signer",
+                          path(key + ".pem"), "-inkey", path(key + ".key"),
+                          "-certfile", path("ca.pem"), "-md", md, "-outform", "DER", *opts)
+        finally:
+            os.unlink(f.name)
+        with open(path(name), "wb") as f:
+            f.write(der)
+    return build
+
+
+def backdated_cms(name, stamped):
+    # detached.p7s dont le signingTime déclare une date antérieure au
+    # certificat du signataire, avec ou sans horodatage de signature.
+    def build():
+        with open(path("detached.txt"), "rb") as f:
+            der = backdate(cms_detached(f.read()), b"190101000000Z")
+        if stamped:
+            der = with_signature_timestamp(der)
+        with open(path(name), "wb") as f:
+            f.write(der)
+    return build
+
+
+def revocation_files(name, *crl_dates):
+    # CRL (DER) de la racine sur le signataire, dans sa période de validité ou
+    # périmée selon `crl_dates`.
+    def build():
+        crl, _ = revocation_data(False, *crl_dates)
+        with open(path(name), "wb") as f:
+            f.write(crl)
+    return build
+
+
+def ocsp_twin_ca():
+    # Réponse OCSP d’une autre racine de même DN (clé différente) : seul
+    # l’issuerKeyHash du CertID la distingue de la racine de test.
+    openssl("req", "-x509", "-newkey", "rsa:2048", "-nodes", "-days", "10000",
+            "-subj", "/CN=Notar Test Root CA", "-keyout", path("twin_ca.key"),
+            "-out", path("twin_ca.pem"))
+    try:
+        with open(path("ocsp_twin_ca.der"), "wb") as f:
+            f.write(revocation_data(False, ca="twin_ca")[1])
+    finally:
+        for leftover in ("twin_ca.key", "twin_ca.pem"):
+            os.unlink(path(leftover))
+
+
+def ocsp_around_signing():
+    # Réponse OCSP produite avant une signature horodatée, puis une autre
+    # produite après : seule la seconde renseigne le statut à l’horodatage.
+    def write(name, data):
+        with open(path(name), "wb") as f:
+            f.write(data)
+    write("ocsp_before_signing.der", revocation_data(False)[1])
+    time.sleep(2)
+    with open(path("detached.txt"), "rb") as f:
+        write("detached_stamped.p7s", with_signature_timestamp(cms_detached(f.read())))
+    time.sleep(2)
+    write("ocsp_after_signing.der", revocation_data(False)[1])
+
+
+CA_EXTENSIONS = "basicConstraints=critical,CA:TRUE\nkeyUsage=critical,keyCertSign,cRLSign\n"
+LEAF_EXTENSIONS = "basicConstraints=CA:FALSE\nkeyUsage=critical,digitalSignature,nonRepudiation\n"
+
+
+def chain_cms(name, issuers, leaf_extensions=LEAF_EXTENSIONS):
+    # detached.txt signé par un certificat émis sous la racine via les
+    # intermédiaires `issuers` (liste de (CN, extensions)), jointes à la CMS.
+    def build():
+        with tempfile.TemporaryDirectory() as tmp:
+            def t(name):
+                return os.path.join(tmp, name)
+
+            def issue(cn, extensions, issuer):
+                with open(t("ext.cnf"), "w") as f:
+                    f.write(extensions)
+                openssl("req", "-newkey", "rsa:2048", "-nodes", "-subj", "/CN=" + cn,
+                        "-keyout", t(cn + ".key"), "-out", t("req.csr"))
+                openssl("x509", "-req", "-in", t("req.csr"), "-CA", issuer + ".pem",
+                        "-CAkey", issuer + ".key", "-set_serial", str(len(os.listdir(tmp))),
+                        "-days", "9000", "-extfile", t("ext.cnf"), "-out", t(cn + ".pem"))
+                return t(cn)
+
+            issuer = path("ca")
+            bundle = []
+            for cn, extensions in issuers:
+                issuer = issue(cn, extensions, issuer)
+                bundle.append(issuer + ".pem")
+            leaf = issue("Maitre Dupont", leaf_extensions, issuer)
+            with open(t("bundle.pem"), "w") as out:
+                for pem in [*bundle, path("ca.pem")]:
+                    with open(pem) as f:
+                        out.write(f.read())
+            der = openssl("cms", "-sign", "-binary", "-in", path("detached.txt"),
+                          "-signer", leaf + ".pem", "-inkey", leaf + ".key",
+                          "-certfile", t("bundle.pem"), "-md", "sha256", "-outform", "DER")
+        with open(path(name), "wb") as f:
+            f.write(der)
+    return build
+
+
+def cosigned_cms(name, tampered):
+    # detached.p7s cosigné par la clé P-256 (deux SignerInfo), la seconde
+    # valeur de signature éventuellement altérée.
+    def build():
+        with open(path("detached.txt"), "rb") as f:
+            data = f.read()
+        with tempfile.NamedTemporaryFile(delete=False) as f:
+            f.write(data)
+        try:
+            der = openssl("cms", "-sign", "-binary", "-in", f.name,
+                          "-signer", path("signer.pem"), "-inkey", path("signer.key"),
+                          "-signer", path("p256.pem"), "-inkey", path("p256.key"),
+                          "-certfile", path("ca.pem"), "-md", "sha256", "-outform", "DER")
+        finally:
+            os.unlink(f.name)
+        if tampered:
+            der = tamper_last_signature(der)
+        with open(path(name), "wb") as f:
+            f.write(der)
+    return build
+
+
+def large_scan():
+    # Acte numérisé : image de 2 Mio non compressée (au-delà d’un budget
+    # mémoire de 1 Mio), deux signatures ; la seconde révision utilise un flux
+    # /XRef et range le champ dans un flux d’objets.
+    fields = [(6, sig_field(b"Client")), (8, sig_field(b"Notaire"))]
+    scan = bytes(range(256)) * (2 << 12)
+    pdf = Pdf()
+    objs = base_document(fields, page_extra=b" /Thumb 10 0 R")
+    objs[10] = (b"<< /Type /XObject /Subtype /Image /Width 1024 /Height 2048"
+                b" /ColorSpace /DeviceGray /BitsPerComponent 8 /Length %d >>\nstream\n"
+                % len(scan) + scan + b"\nendstream")
+    pdf.revision(objs)
+    pdf.revision({6: sig_field(b"Client", sig=7),
+                  7: sig_placeholder(sub_filter=b"ETSI.CAdES.detached")})
+    pdf.sign_last(cms_cades)
+    pdf.revision_xref_stream({9: sig_placeholder()}, {8: sig_field(b"Notaire", sig=9)})
+    pdf.sign_last()
+    pdf.write("large_scan.pdf")
+
+
+def merge_signed_data(a, b):
+    """SignedData de `a` augmentée des algorithmes, certificats et SignerInfo
+    de `b` (même contenu), ensembles triés selon DER."""
+    def signed_data(der):
+        content = der_elements(der_elements(der)[0][1])[1][1]
+        return [raw for _, _, raw in der_elements(der_elements(content)[0][1])]
+
+    def union(x, y):
+        tag, body, _ = der_elements(x)[0]
+        items = {raw for _, _, raw in der_elements(body)}
+        items |= {raw for _, _, raw in der_elements(der_elements(y)[0][1])}
+        return der_tlv(tag, b"".join(sorted(items)))
+
+    sa, sb = signed_data(a), signed_data(b)
+    merged = [sa[0], union(sa[1], sb[1]), sa[2], union(sa[3], sb[3]), union(sa[-1], sb[-1])]
+    oid = der_elements(der_elements(a)[0][1])[0][2]
+    return der_tlv(0x30, oid + der_tlv(0xA0, der_tlv(0x30, b"".join(merged))))
+
+
+def cms_cosigned_p384(data):
+    # Signature RSA/SHA-512 et cosignature P-384/SHA-384 : aucune empreinte
+    # n’est le SHA-256 toujours calculé.
+    with tempfile.NamedTemporaryFile(delete=False) as f:
+        f.write(data)
+    try:
+        a, b = (openssl("cms", "-sign", "-binary", "-in", f.name, "-signer",
+                        path(key + ".pem"), "-inkey", path(key + ".key"),
+                        "-certfile", path("ca.pem"), "-md", md, "-outform", "DER")
+                for key, md in [("signer", "sha512"), ("p384", "sha384")])
+    finally:
+        os.unlink(f.name)
+    return merge_signed_data(a, b)
+
+
+def large_scan_signed(name, signer):
+    # Acte numérisé de 1,1 Mio : lu en flux sous un budget de 1 Mio, le
+    # contenu n’est connu que par ses empreintes.
+    def build():
+        fields = [(6, sig_field(b"Client"))]
+        scan = bytes(range(256)) * (9 << 9)
+        pdf = Pdf()
+        objs = base_document(fields, page_extra=b" /Thumb 10 0 R")
+        objs[10] = (b"<< /Type /XObject /Subtype /Image /Width 1024 /Height 1152"
+                    b" /ColorSpace /DeviceGray /BitsPerComponent 8 /Length %d >>\nstream\n"
+                    % len(scan) + scan + b"\nendstream")
+        pdf.revision(objs)
+        pdf.revision({6: sig_field(b"Client", sig=7), 7: sig_placeholder()})
+        pdf.sign_last(signer)
+        pdf.write(name)
+    return build
+
+
+def encrypted(name, revision, user):
+    # Deux signatures dans un PDF chiffré : seul /Contents reste en clair.
+    def build():
+        sec = StandardSecurity(revision, user)
+        trailer = sec.trailer(20)
+
+        def field(title, num, sig=None):
+            return sig_field(title, sig=sig).replace(b"(%s)" % title, sec.string(num, title))
+
+        def placeholder(num):
+            return sig_placeholder(b" /Reason " + sec.string(num, b"Lecture faite"))
+
+        fields = [(6, field(b"Client", 6)), (8, field(b"Notaire", 8))]
+        objs = base_document(fields)
+        objs[4] = sec.stream(4, b"BT /F1 18 Tf 72 720 Td (Acte notarie) Tj ET")
+        objs[20] = sec.dictionary
+        pdf = Pdf()
+        pdf.revision(objs, trailer_extra=trailer)
+        pdf.revision({6: field(b"Client", 6, sig=7), 7: placeholder(7)}, trailer_extra=trailer)
+        pdf.sign_last()
+        pdf.revision({8: field(b"Notaire", 8, sig=9), 9: placeholder(9)}, trailer_extra=trailer)
+        pdf.sign_last()
+        pdf.write(name)
+    return build
+
+
+def legacy_sub_filter(name, sub_filter, signer, extra=b""):
+    # Signature unique encodée selon `sub_filter`.
+    def build():
+        pdf = Pdf()
+        pdf.revision(base_document([(6, sig_field(b"Notaire"))]))
+        pdf.revision({6: sig_field(b"Notaire", sig=7),
+                      7: sig_placeholder(extra(), sub_filter)})
+        pdf.sign_last(signer)
+        pdf.write(name)
+    return build
+
+
+FIXTURES = {
+    "two_signatures.pdf": two_signatures,
+    "byterange_gap.pdf": byterange_gap,
+    "content_changed_after_signing.pdf": content_changed_after_signing,
+    "content_retagged_objstm.pdf": content_retagged_objstm,
+    "content_as_metadata.pdf": disguised_content_change(
+        "content_as_metadata.pdf", catalog_extra=b" /Metadata 4 0 R"),
+    "content_as_info.pdf": disguised_content_change(
+        "content_as_info.pdf", trailer_extra=b" /Info 4 0 R"),
+    "content_as_dss.pdf": disguised_content_change(
+        "content_as_dss.pdf", catalog_extra=b" /DSS << /Certs [4 0 R] >>"),
+    "certified_p1_then_filled.pdf": certified_then_filled(1),
+    "certified_p2_then_filled.pdf": certified_then_filled(2),
+    "locked_field_filled.pdf": locked_field_filled,
+    "hide_and_replace.pdf": hide_and_replace("hide_and_replace.pdf"),
+    "hide_and_replace_retagged.pdf": hide_and_replace(
+        "hide_and_replace_retagged.pdf", b" /Type /ObjStm /N 1 /First 4"),
+    "pkcs7_sha1.pdf": legacy_sub_filter(
+        "pkcs7_sha1.pdf", b"adbe.pkcs7.sha1", cms_sha1_digest, lambda: b""),
+    "x509_rsa_sha1.pdf": legacy_sub_filter(
+        "x509_rsa_sha1.pdf", b"adbe.x509.rsa_sha1", pkcs1_sha1,
+        lambda: b" /Cert <%s>" % cert_der("signer.pem").hex().encode()),
+    "unknown_sub_filter.pdf": legacy_sub_filter(
+        "unknown_sub_filter.pdf", b"x.notar.experimental", cms_detached, lambda: b""),
+    "pkcs7_sha1_tampered.pdf": legacy_sub_filter(
+        "pkcs7_sha1_tampered.pdf", b"adbe.pkcs7.sha1", cms_sha1_digest_mismatch, lambda: b""),
+    "pkcs7_sha1_cosigned_tampered.pdf": legacy_sub_filter(
+        "pkcs7_sha1_cosigned_tampered.pdf", b"adbe.pkcs7.sha1", cms_sha1_cosigned_tampered,
+        lambda: b""),
+    "document_timestamp.pdf": document_timestamp,
+    "document_timestamp_cosigned_tampered.pdf": legacy_sub_filter(
+        "document_timestamp_cosigned_tampered.pdf", b"ETSI.RFC3161", token_cosigned_tampered,
+        lambda: b""),
+    "document_timestamp_tampered.pdf": legacy_sub_filter(
+        "document_timestamp_tampered.pdf", b"ETSI.RFC3161", token_gen_time_altered, lambda: b""),
+    "ltv_dss.pdf": ltv_dss("ltv_dss.pdf", revoked=False),
+    "ltv_dss_revoked.pdf": ltv_dss("ltv_dss_revoked.pdf", revoked=True),
+    "ltv_dss_stale.pdf": ltv_dss("ltv_dss_stale.pdf", revoked=False, stale=True),
+    "pades_lta.pdf": pades_lta,
+    "pades_bt.pdf": pades_bt("pades_bt.pdf", None),
+    "pades_bt_forged.pdf": pades_bt("pades_bt_forged.pdf", b"autre signature"),
+    "signature_metadata.pdf": signature_metadata,
+    "signature_widgets.pdf": signature_widgets,
+    "pending_fields.pdf": pending_fields,
+    "broken_xref.pdf": broken_xref,
+    "contents_padding.pdf": contents_padding,
+    "portfolio.pdf": portfolio,
+    "portfolio_bomb.pdf": portfolio_bomb,
+    "xfa_usage_rights.pdf": xfa_usage_rights,
+    "detached.p7s": detached_cms,
+    "attached.p7m": attached_p7m,
+    "attached_tampered.p7m": attached_tampered_p7m,
+    "nested.p7m.p7m": nested_p7m,
+    "detached_pss.p7s": detached_with(
+        "detached_pss.p7s", "rsa", "sha256", "-keyopt", "rsa_padding_mode:pss",
+        "-keyopt", "rsa_pss_saltlen:32"),
+    "detached_sha3.p7s": detached_with("detached_sha3.p7s", "rsa", "sha3-256"),
+    "detached_p256.p7s": detached_with("detached_p256.p7s", "p256", "sha256"),
+    "detached_p384.p7s": detached_with("detached_p384.p7s", "p384", "sha384"),
+    "detached_p521.p7s": detached_with("detached_p521.p7s", "p521", "sha512"),
+    "detached_ed25519.p7s": detached_with("detached_ed25519.p7s", "ed25519", "sha512"),
+    "detached_ed448.p7s": detached_with("detached_ed448.p7s", "ed448", "sha512"),
+    "detached_backdated.p7s": backdated_cms("detached_backdated.p7s", False),
+    "detached_backdated_ts.p7s": backdated_cms("detached_backdated_ts.p7s", True),
+    "crl.der": revocation_files("crl.der"),
+    "crl_expired.der": revocation_files("crl_expired.der", "240101000000Z", "240201000000Z"),
+    "ocsp_twin_ca.der": ocsp_twin_ca,
+    "detached_stamped.p7s": ocsp_around_signing,
+    "ocsp_before_signing.der": ocsp_around_signing,
+    "ocsp_after_signing.der": ocsp_around_signing,
+    "detached_intermediate.p7s": chain_cms(
+        "detached_intermediate.p7s", [("Notar Test CA 1", CA_EXTENSIONS)]),
+    "detached_issuer_no_keycertsign.p7s": chain_cms(
+        "detached_issuer_no_keycertsign.p7s",
+        [("Notar Test CA 1", "basicConstraints=critical,CA:TRUE\nkeyUsage=critical,digitalSignature\n")]),
+    "detached_path_len_exceeded.p7s": chain_cms(
+        "detached_path_len_exceeded.p7s",
+        [("Notar Test CA 1", CA_EXTENSIONS.replace("CA:TRUE", "CA:TRUE,pathlen:0")),
+         ("Notar Test CA 2", CA_EXTENSIONS)]),
+    "detached_unknown_critical.p7s": chain_cms(
+        "detached_unknown_critical.p7s", [],
+        LEAF_EXTENSIONS + "1.3.6.1.4.1.99999.1=critical,DER:0500\n"),
+    "detached_cosigned.p7s": cosigned_cms("detached_cosigned.p7s", False),
+    "detached_cosigned_tampered.p7s": cosigned_cms("detached_cosigned_tampered.p7s", True),
+    "large_scan.pdf": large_scan,
+    "large_scan_noattr.pdf": large_scan_signed(
+        "large_scan_noattr.pdf", lambda data: cms_detached(data, False, "-noattr")),
+    "large_scan_cosigned.pdf": large_scan_signed("large_scan_cosigned.pdf", cms_cosigned_p384),
+    "encrypted_rc4.pdf": encrypted("encrypted_rc4.pdf", 3, b""),
+    "encrypted_aes128.pdf": encrypted("encrypted_aes128.pdf", 4, b""),
+    "encrypted_aes256.pdf": encrypted("encrypted_aes256.pdf", 6, b"secret"),
+}
+
+
+def main():
+    force = "--force" in sys.argv[1:]
+    if force or not os.path.exists(path("signer.key")):
+        gen_pki()
+        force = True
+    if force or not os.path.exists(path("tsa.key")):
+        gen_tsa()
+    for name, build in FIXTURES.items():
+        if force or not os.path.exists(path(name)):
+            print("génération", name)
+            build()
+
+
+if __name__ == "__main__":
+    main()
@@ -1,9 +1,14 @@
 use assert_cmd::Command;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::fs;
+use std::path::{Path, PathBuf};
 use tempfile::tempdir;
 
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
 #[test]
-fn cli_shows_warning_without_backend() {
+fn cli_rejects_unreadable_cms() {
     let dir = tempdir().unwrap();
     let data = dir.path().join("data.bin");
     let sig = dir.path().join("detached.sig.p7s");
@@ -21,9 +26,8 @@ fn cli_shows_warning_without_backend() {
         .arg(&out)
         .assert();
 
-    // Ne pas exiger un exit code 0 : selon la build/features, le binaire peut retourner
-    // 1/2 (Invalid/Warning) tout en ayant écrit le rapport JSON. Vérifions seulement
-    // que le fichier a bien été créé avant de le lire.
+    // Le binaire retourne un code non nul (INVALID) tout en ayant écrit le
+    // rapport JSON, identique avec ou sans openssl-backend.
     assert!(
         out.exists(),
         "Le rapport JSON attendu n'a pas été créé : {:?}",
@@ -31,5 +35,434 @@ fn cli_shows_warning_without_backend() {
     );
 
     let json = fs::read_to_string(out).unwrap();
-    insta::assert_snapshot!("report_warning", json);
+    insta::assert_snapshot!("report_unreadable", json);
+}
+
+/// Lance la CLI sur une signature (détachée si `data` est fourni) et relit
+/// le rapport JSON.
+fn cms_report(sig: &Path, data: Option<&Path>) -> Value {
+    cms_report_trusting(sig, data, Some(&fixture("ca.pem")))
+}
+
+fn cms_report_trusting(sig: &Path, data: Option<&Path>, trust: Option<&Path>) -> Value {
+    cms_report_with(sig, data, trust, &[])
+}
+
+/// Comme [`cms_report_trusting`], avec des options supplémentaires.
+fn cms_report_with(sig: &Path, data: Option<&Path>, trust: Option<&Path>, args: &[&Path]) -> Value {
+    let dir = tempdir().unwrap();
+    let out = dir.path().join("report.json");
+    Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--sig")
+        .arg(sig)
+        .args(data.map(|d| [Path::new("--data"), d]).into_iter().flatten())
+        .args(
+            trust
+                .map(|t| [Path::new("--trust"), t])
+                .into_iter()
+                .flatten(),
+        )
+        .args(args)
+        .arg("--out")
+        .arg(&out)
+        .assert();
+    serde_json::from_str(&fs::read_to_string(out).unwrap()).unwrap()
+}
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(FIXTURES).join(name)
+}
+
+#[test]
+fn detached_cms_structure_is_decoded_natively() {
+    let report = cms_report(&fixture("detached.p7s"), Some(&fixture("detached.txt")));
+
+    assert_eq!(report["signer_dn"], "Maitre Dupont");
+    assert_eq!(report["algorithms"][0], "SHA-256");
+    assert_eq!(report["integrity"]["status"], "VALID");
+
+    let cms = &report["cms"];
+    assert_eq!(cms["content_type"], "data");
+    assert_eq!(cms["encapsulated"], false);
+    assert_eq!(cms["certificates"], 2);
+    let signer = &cms["signers"][0];
+    assert_eq!(signer["signer_dn"], "Maitre Dupont");
+    let attrs = signer["signed_attributes"].as_array().unwrap();
+    for name in ["contentType", "messageDigest", "signingTime"] {
+        assert!(
+            attrs.iter().any(|a| a == name),
+            "{name} absent de {attrs:?}"
+        );
+    }
+}
+
+#[test]
+fn signer_algorithms_are_verified_natively() {
+    for (name, algorithm) in [
+        ("detached.p7s", "rsaEncryption"),
+        ("detached_pss.p7s", "RSASSA-PSS"),
+        ("detached_sha3.p7s", "rsaEncryption"),
+        ("detached_p256.p7s", "ecdsa-with-SHA256"),
+        ("detached_p384.p7s", "ecdsa-with-SHA384"),
+        ("detached_p521.p7s", "ecdsa-with-SHA512"),
+        ("detached_ed25519.p7s", "Ed25519"),
+        ("detached_ed448.p7s", "Ed448"),
+    ] {
+        let report = cms_report(&fixture(name), Some(&fixture("detached.txt")));
+        assert_eq!(report["algorithms"][1], algorithm, "{name}");
+        // OpenSSL < 3.2 ne vérifie pas EdDSA en CMS : le recoupement est
+        // alors indéterminé, jamais invalide.
+        if cfg!(feature = "openssl-backend") && algorithm.starts_with("Ed") {
+            assert_ne!(report["signature"]["status"], "INVALID", "{report:#}");
+            continue;
+        }
+        assert_eq!(report["signature"]["status"], "VALID", "{name}: {report:#}");
+        assert_eq!(report["chain"]["status"], "VALID", "{name}");
+        assert_eq!(report["verdict"], "VALID", "{name}");
+    }
+}
+
+#[test]
+fn altered_data_or_signature_value_is_invalid() {
+    let dir = tempdir().unwrap();
+    let data = dir.path().join("data.txt");
+    fs::write(&data, b"Acte de vente - minute 2024/118\n").unwrap();
+    let report = cms_report(&fixture("detached_p256.p7s"), Some(&data));
+    assert_eq!(report["integrity"]["status"], "INVALID");
+    assert_eq!(report["verdict"], "INVALID");
+    let detail = report["integrity"]["detail"].as_str().unwrap();
+    assert!(detail.contains("messageDigest"), "{detail}");
+
+    // Dernier octet de la valeur de signature, en fin de SignerInfo.
+    for name in ["detached.p7s", "detached_ed25519.p7s", "detached_ed448.p7s"] {
+        let mut der = fs::read(fixture(name)).unwrap();
+        *der.last_mut().unwrap() ^= 1;
+        let sig = dir.path().join(name);
+        fs::write(&sig, der).unwrap();
+        let report = cms_report(&sig, Some(&fixture("detached.txt")));
+        assert_eq!(report["signature"]["status"], "INVALID", "{name}");
+        assert_eq!(report["verdict"], "INVALID", "{name}");
+    }
+}
+
+#[test]
+fn unestablished_chain_does_not_invalidate_the_signature() {
+    // Sans anchor, puis avec un anchor étranger à la chaîne du signataire.
+    for trust in [None, Some(fixture("p256.pem"))] {
+        let report = cms_report_trusting(
+            &fixture("detached.p7s"),
+            Some(&fixture("detached.txt")),
+            trust.as_deref(),
+        );
+        assert_eq!(report["signature"]["status"], "VALID", "{report:#}");
+        assert_eq!(report["integrity"]["status"], "VALID", "{report:#}");
+        assert_eq!(report["chain"]["status"], "WARNING", "{report:#}");
+        assert_eq!(report["verdict"], "WARNING", "{report:#}");
+        assert_eq!(report["signer_dn"], "Maitre Dupont");
+    }
+}
+
+#[test]
+fn every_signer_info_is_verified() {
+    let report = cms_report(
+        &fixture("detached_cosigned.p7s"),
+        Some(&fixture("detached.txt")),
+    );
+    assert_eq!(report["verdict"], "VALID", "{report:#}");
+    let signers = report["signers"].as_array().unwrap();
+    assert_eq!(signers.len(), 2, "{report:#}");
+    let mut dns: Vec<_> = signers
+        .iter()
+        .map(|s| {
+            assert_eq!(s["report"]["verdict"], "VALID", "{s:#}");
+            s["report"]["signer_dn"].as_str().unwrap()
+        })
+        .collect();
+    dns.sort();
+    assert_eq!(dns, ["Maitre Dupont", "Maitre Dupont P256"]);
+
+    // Une seule valeur de signature altérée suffit à invalider l’ensemble.
+    let report = cms_report(
+        &fixture("detached_cosigned_tampered.p7s"),
+        Some(&fixture("detached.txt")),
+    );
+    assert_eq!(report["verdict"], "INVALID", "{report:#}");
+    let verdicts: Vec<_> = report["signers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["report"]["verdict"].as_str().unwrap())
+        .collect();
+    assert_eq!(verdicts, ["VALID", "INVALID"], "{report:#}");
+    let detail = report["signature"]["detail"].as_str().unwrap();
+    assert!(detail.starts_with("Signataire #2"), "{detail}");
+}
+
+#[test]
+fn chain_is_evaluated_at_the_attested_signing_time() {
+    // signingTime déclaré antérieur au certificat du signataire : la chaîne
+    // ne vaut pas à cette date.
+    let report = cms_report(
+        &fixture("detached_backdated.p7s"),
+        Some(&fixture("detached.txt")),
+    );
+    assert_eq!(report["signature"]["status"], "VALID", "{report:#}");
+    assert_eq!(report["chain"]["status"], "WARNING", "{report:#}");
+    let detail = report["chain"]["detail"].as_str().unwrap();
+    assert!(detail.contains("signingTime"), "{detail}");
+    assert!(report["timestamp_rfc3161"].is_null());
+
+    // Un horodatage de signature vérifié fait foi sur le signingTime.
+    let report = cms_report(
+        &fixture("detached_backdated_ts.p7s"),
+        Some(&fixture("detached.txt")),
+    );
+    assert_eq!(report["chain"]["status"], "VALID", "{report:#}");
+    assert_eq!(report["verdict"], "VALID", "{report:#}");
+    assert!(report["timestamp_rfc3161"].is_string(), "{report:#}");
+}
+
+#[test]
+fn enveloping_p7m_is_verified_and_its_content_extracted() {
+    let content = fs::read(fixture("detached.txt")).unwrap();
+    let dir = tempdir().unwrap();
+    for name in ["attached.p7m", "attached_ber.p7m"] {
+        let report = cms_report(&fixture(name), None);
+        assert_eq!(report["signature"]["status"], "VALID", "{name}: {report:#}");
+        assert_eq!(report["cms"]["encapsulated"], true, "{name}");
+        assert_eq!(
+            report["ltv"]["detail"], "Non applicable (CMS enveloppé / P7M)",
+            "{name}"
+        );
+        assert_eq!(
+            report["document_sha256"],
+            hex::encode(Sha256::digest(&content)),
+            "{name}"
+        );
+
+        let extracted = dir.path().join(name).with_extension("txt");
+        Command::cargo_bin("notar-verify")
+            .unwrap()
+            .arg("--sig")
+            .arg(fixture(name))
+            .arg("--extract-content")
+            .arg(&extracted)
+            .assert();
+        assert_eq!(fs::read(&extracted).unwrap(), content, "{name}");
+    }
+
+    // Signature détachée sans --data : rien à vérifier ni à extraire.
+    let report = cms_report(&fixture("detached.p7s"), None);
+    assert_eq!(report["signature"]["status"], "WARNING");
+    Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--sig")
+        .arg(fixture("detached.p7s"))
+        .arg("--extract-content")
+        .arg(dir.path().join("none.txt"))
+        .assert()
+        .failure();
+    assert!(!dir.path().join("none.txt").exists());
+
+    // Acte altéré après signature : verdict INVALID, rien n’est écrit sans --force.
+    let tampered = dir.path().join("tampered.txt");
+    let output = Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--sig")
+        .arg(fixture("attached_tampered.p7m"))
+        .arg("--extract-content")
+        .arg(&tampered)
+        .assert()
+        .code(1)
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8_lossy(&output);
+    assert!(stderr.contains("Contenu signé non écrit"), "{stderr}");
+    assert!(!tampered.exists());
+    Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--sig")
+        .arg(fixture("attached_tampered.p7m"))
+        .arg("--extract-content")
+        .arg(&tampered)
+        .arg("--force")
+        .assert()
+        .code(1);
+    assert_ne!(fs::read(&tampered).unwrap(), content);
+}
+
+#[test]
+fn signed_data_files_are_bounded_by_the_memory_budget() {
+    // 2 Mio sous --max-mib mais au-delà d’un budget mémoire de 1 Mio.
+    let output = Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--sig")
+        .arg(fixture("detached.p7s"))
+        .arg("--data")
+        .arg(fixture("large_scan.pdf"))
+        .args(["--max-memory-mib", "1"])
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8_lossy(&output);
+    assert!(stderr.contains("budget mémoire"), "{stderr}");
+}
+
+#[test]
+fn nested_p7m_layers_are_each_verified() {
+    let content = fs::read(fixture("detached.txt")).unwrap();
+    let dir = tempdir().unwrap();
+    for (name, encoding) in [("nested.p7m.p7m", "DER"), ("nested_b64.p7m.p7m", "Base64")] {
+        let report = cms_report(&fixture(name), None);
+        assert_eq!(report["verdict"], "VALID", "{name}: {report:#}");
+        let layers = report["layers"].as_array().unwrap();
+        let summary: Vec<_> = layers
+            .iter()
+            .map(|l| {
+                (
+                    l["index"].as_u64().unwrap(),
+                    l["encoding"].as_str().unwrap(),
+                    l["report"]["signer_dn"].as_str().unwrap(),
+                    l["report"]["signature"]["status"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (0, "DER", "Maitre Dupont P256", "VALID"),
+                (1, encoding, "Maitre Dupont", "VALID"),
+            ],
+            "{name}"
+        );
+        // L’empreinte porte sur l’acte, pas sur la signature qu’il enveloppe.
+        assert_eq!(
+            report["document_sha256"],
+            hex::encode(Sha256::digest(&content)),
+            "{name}"
+        );
+
+        let extracted = dir.path().join(name).with_extension("txt");
+        Command::cargo_bin("notar-verify")
+            .unwrap()
+            .arg("--sig")
+            .arg(fixture(name))
+            .arg("--extract-content")
+            .arg(&extracted)
+            .assert();
+        assert_eq!(fs::read(&extracted).unwrap(), content, "{name}");
+    }
+
+    // Une seule couche : pas de liste.
+    let report = cms_report(&fixture("attached.p7m"), None);
+    assert_eq!(report["layers"], serde_json::json!([]));
+}
+
+#[test]
+fn signed_attributes_are_checked_against_the_content() {
+    let report = cms_report(&fixture("attached.p7m"), None);
+    assert_eq!(report["integrity"]["status"], "VALID");
+    let signing_time = report["signing_time"].as_str().unwrap();
+    assert!(
+        time::OffsetDateTime::parse(signing_time, &time::format_description::well_known::Rfc3339)
+            .is_ok(),
+        "{signing_time}"
+    );
+
+    // eContentType id-data remplacé par id-envelopedData (hors attributs
+    // signés) : le contentType signé ne lui correspond plus.
+    let id_data = [
+        0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01,
+    ];
+    let mut der = fs::read(fixture("attached.p7m")).unwrap();
+    let at = der
+        .windows(id_data.len())
+        .position(|w| w == id_data)
+        .unwrap();
+    der[at + id_data.len() - 1] = 0x03;
+    let dir = tempdir().unwrap();
+    let sig = dir.path().join("content_type.p7m");
+    fs::write(&sig, der).unwrap();
+    let report = cms_report(&sig, None);
+    assert_eq!(report["integrity"]["status"], "INVALID", "{report:#}");
+    assert_eq!(report["verdict"], "INVALID");
+    let detail = report["integrity"]["detail"].as_str().unwrap();
+    assert!(detail.contains("contentType"), "{detail}");
+}
+
+#[test]
+fn revocation_evidence_must_be_current_at_validation_time() {
+    let data = fixture("detached.txt");
+    let revocation = |sig: &str, option: &str, evidence: &str| {
+        let evidence = fixture(evidence);
+        let report = cms_report_with(
+            &fixture(sig),
+            Some(&data),
+            Some(&fixture("ca.pem")),
+            &[Path::new(option), &evidence],
+        );
+        assert_eq!(report["signature"]["status"], "VALID", "{report:#}");
+        report["revocation"].clone()
+    };
+
+    let current = revocation("detached.p7s", "--crl", "crl.der");
+    assert_eq!(current["status"], "VALID", "{current:#}");
+
+    let expired = revocation("detached.p7s", "--crl", "crl_expired.der");
+    assert_eq!(expired["status"], "WARNING");
+    let detail = expired["detail"].as_str().unwrap();
+    assert!(
+        detail.contains("CRL périmée depuis le 2024-02-01"),
+        "{detail}"
+    );
+
+    // Signature horodatée : seule une réponse produite après l’horodatage
+    // renseigne le statut du certificat à cette date.
+    let after = revocation("detached_stamped.p7s", "--ocsp", "ocsp_after_signing.der");
+    assert_eq!(after["status"], "VALID", "{after:#}");
+
+    // Réponse d’une autre racine de même DN : issuerKeyHash différent.
+    let twin = revocation("detached.p7s", "--ocsp", "ocsp_twin_ca.der");
+    assert_eq!(twin["status"], "WARNING");
+    let detail = twin["detail"].as_str().unwrap();
+    assert!(detail.contains("aucune donnée de révocation"), "{detail}");
+
+    let before = revocation("detached_stamped.p7s", "--ocsp", "ocsp_before_signing.der");
+    assert_eq!(before["status"], "WARNING");
+    let detail = before["detail"].as_str().unwrap();
+    assert!(
+        detail.contains("avant l’horodatage de signature"),
+        "{detail}"
+    );
+}
+
+#[test]
+fn issuer_constraints_and_critical_extensions_are_enforced() {
+    let data = fixture("detached.txt");
+    let report = cms_report(&fixture("detached_intermediate.p7s"), Some(&data));
+    assert_eq!(report["chain"]["status"], "VALID", "{report:#}");
+    assert_eq!(report["verdict"], "VALID");
+
+    for (name, reason) in [
+        (
+            "detached_issuer_no_keycertsign.p7s",
+            "non rattaché aux anchors",
+        ),
+        ("detached_path_len_exceeded.p7s", "non rattaché aux anchors"),
+        (
+            "detached_unknown_critical.p7s",
+            "extension critique non prise en charge (1.3.6.1.4.1.99999.1)",
+        ),
+    ] {
+        let report = cms_report(&fixture(name), Some(&data));
+        assert_eq!(report["signature"]["status"], "VALID", "{name}");
+        assert_eq!(report["chain"]["status"], "WARNING", "{name}");
+        let detail = report["chain"]["detail"].as_str().unwrap();
+        assert!(detail.contains(reason), "{name}: {detail}");
+        assert_ne!(report["verdict"], "VALID", "{name}");
+    }
 }
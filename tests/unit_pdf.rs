@@ -1,5 +1,773 @@
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// Lance la CLI sur un PDF de `tests/fixtures` (ou un chemin absolu) et relit
+/// le rapport JSON.
+fn pdf_report(name: &str) -> Value {
+    pdf_report_with(name, &[])
+}
+
+/// Comme [`pdf_report`], avec des options supplémentaires.
+fn pdf_report_with(name: &str, args: &[&str]) -> Value {
+    let dir = tempdir().unwrap();
+    let out = dir.path().join("report.json");
+    Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--in")
+        .arg(Path::new(FIXTURES).join(name))
+        .arg("--trust")
+        .arg(format!("{FIXTURES}/ca.pem"))
+        .arg("--out")
+        .arg(&out)
+        .args(args)
+        .assert();
+    serde_json::from_str(&fs::read_to_string(out).unwrap()).unwrap()
+}
+
+/// Lance la CLI sur un PDF qui doit être refusé ; renvoie sa sortie d’erreur.
+fn pdf_failure(name: &str, args: &[&str]) -> String {
+    let output = Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--in")
+        .arg(format!("{FIXTURES}/{name}"))
+        .args(args)
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn every_signature_field_is_verified_in_acroform_order() {
+    let report = pdf_report("two_signatures.pdf");
+    let sigs = report["signatures"].as_array().unwrap();
+
+    let names: Vec<_> = sigs.iter().map(|s| s["field_name"].as_str()).collect();
+    assert_eq!(names, [Some("Client"), Some("Notaire")]);
+    assert_ne!(sigs[0]["byte_range"], sigs[1]["byte_range"]);
+    let after: Vec<_> = sigs
+        .iter()
+        .map(|s| s["revision"]["revisions_after"].as_u64())
+        .collect();
+    assert_eq!(after, [Some(1), Some(0)]);
+}
+
+#[test]
+fn byterange_violations_are_reported_individually() {
+    let report = pdf_report("byterange_gap.pdf");
+    let integrity = &report["signatures"][0]["integrity"];
+    assert_eq!(integrity["status"], "INVALID");
+
+    let detail = integrity["detail"].as_str().unwrap();
+    assert!(detail.contains("/Contents"), "{detail}");
+    assert!(detail.contains("%%EOF"), "{detail}");
+    assert_eq!(report["verdict"], "INVALID");
+}
+
+#[test]
+fn modification_after_signing_prevents_valid() {
+    let report = pdf_report("content_changed_after_signing.pdf");
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["revision"]["signed_revision"], 2);
+    assert_eq!(sig["revision"]["total_revisions"], 3);
+    assert_eq!(sig["revision"]["modified_after"], true);
+    assert_eq!(sig["modifications"][0]["kind"], "PAGE_CONTENT");
+    assert_eq!(sig["integrity"]["status"], "INVALID");
+    assert_eq!(report["verdict"], "INVALID");
+}
+
+#[test]
+fn page_content_cannot_pass_for_metadata_or_dss() {
+    for name in [
+        "content_as_metadata.pdf",
+        "content_as_info.pdf",
+        "content_as_dss.pdf",
+    ] {
+        let report = pdf_report(name);
+        let sig = &report["signatures"][0];
+        let content = sig["modifications"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["object"] == "4 0 R")
+            .unwrap_or_else(|| panic!("{name}: {sig:#}"));
+        assert_eq!(content["kind"], "PAGE_CONTENT", "{name}");
+        assert_eq!(content["allowed"], false, "{name}");
+        assert_eq!(sig["integrity"]["status"], "INVALID", "{name}");
+        assert_eq!(report["verdict"], "INVALID", "{name}");
+    }
+}
+
+#[test]
+fn page_content_retagged_as_object_stream_is_compared() {
+    let report = pdf_report("content_retagged_objstm.pdf");
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["docmdp"], 2);
+    assert_eq!(sig["modifications"][0]["object"], "4 0 R", "{sig:#}");
+    assert_eq!(sig["modifications"][0]["kind"], "PAGE_CONTENT");
+    assert_eq!(sig["modifications"][0]["allowed"], false);
+    assert_eq!(sig["integrity"]["status"], "INVALID");
+    assert_eq!(report["verdict"], "INVALID");
+}
+
+#[test]
+fn form_fill_follows_docmdp_and_fieldmdp() {
+    let status = |name: &str| {
+        let report = pdf_report(name);
+        let sig = &report["signatures"][0];
+        assert_eq!(sig["modifications"][0]["kind"], "FORM_FILL", "{sig:#}");
+        assert_eq!(sig["modifications"][0]["field"], "Montant");
+        (sig["docmdp"].as_u64(), sig["integrity"]["status"].clone())
+    };
+    assert_eq!(
+        status("certified_p1_then_filled.pdf"),
+        (Some(1), "INVALID".into())
+    );
+    assert_eq!(
+        status("certified_p2_then_filled.pdf"),
+        (Some(2), "VALID".into())
+    );
+    assert_eq!(status("locked_field_filled.pdf"), (None, "INVALID".into()));
+}
+
+#[test]
+fn shadow_patterns_are_flagged() {
+    for name in ["hide_and_replace.pdf", "hide_and_replace_retagged.pdf"] {
+        let report = pdf_report(name);
+        let sig = &report["signatures"][0];
+        let kinds: Vec<&str> = sig["shadow_findings"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["kind"].as_str().unwrap())
+            .collect();
+        assert!(kinds.contains(&"HIDE_AND_REPLACE"), "{name}: {kinds:?}");
+        assert_eq!(sig["shadow"]["status"], "INVALID", "{name}");
+    }
+
+    for name in [
+        "content_changed_after_signing.pdf",
+        "content_as_metadata.pdf",
+        "content_as_info.pdf",
+        "content_as_dss.pdf",
+        "content_retagged_objstm.pdf",
+    ] {
+        let report = pdf_report(name);
+        let sig = &report["signatures"][0];
+        assert_eq!(sig["shadow_findings"][0]["kind"], "REPLACE", "{name}");
+        assert_eq!(sig["shadow_findings"][0]["objects"][0], "4 0 R", "{name}");
+    }
+
+    let report = pdf_report("two_signatures.pdf");
+    assert_eq!(report["signatures"][0]["shadow"]["status"], "VALID");
+}
+
+#[test]
+fn extracted_revision_is_the_signed_prefix() {
+    let dir = tempdir().unwrap();
+    let extracted = dir.path().join("client.pdf");
+    let source = format!("{FIXTURES}/two_signatures.pdf");
+    Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--in")
+        .arg(&source)
+        .arg("--extract-revision")
+        .arg("1")
+        .arg("--out-pdf")
+        .arg(&extracted)
+        .assert()
+        .success();
+
+    let full = fs::read(&source).unwrap();
+    let signed = fs::read(&extracted).unwrap();
+    assert!(signed.len() < full.len());
+    assert!(full.starts_with(&signed));
+    assert!(signed.ends_with(b"%%EOF\n"));
+
+    Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--in")
+        .arg(&source)
+        .arg("--extract-revision")
+        .arg("3")
+        .arg("--out-pdf")
+        .arg(dir.path().join("none.pdf"))
+        .assert()
+        .failure();
+}
+
+#[test]
+fn unknown_sub_filter_is_indeterminate() {
+    let report = pdf_report("x509_rsa_sha1.pdf");
+    assert_eq!(report["signatures"][0]["sub_filter"], "adbe.x509.rsa_sha1");
+
+    let report = pdf_report("unknown_sub_filter.pdf");
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["sub_filter"], "x.notar.experimental");
+    assert_eq!(sig["signature"]["status"], "WARNING");
+    let detail = sig["signature"]["detail"].as_str().unwrap();
+    assert!(detail.contains("x.notar.experimental"), "{detail}");
+    assert_eq!(report["verdict"], "WARNING");
+}
+
+#[test]
+fn document_timestamp_imprint_and_gen_time() {
+    let report = pdf_report("document_timestamp.pdf");
+    let ts = &report["signatures"][1];
+    assert_eq!(ts["sub_filter"], "ETSI.RFC3161");
+    assert_eq!(ts["signer_dn"], "Notar Test TSA");
+    let gen_time = ts["timestamp_rfc3161"].as_str().unwrap();
+    assert!(
+        gen_time.ends_with('Z') && gen_time.contains('T'),
+        "{gen_time}"
+    );
+    assert_ne!(ts["signature"]["status"], "INVALID", "{ts:#}");
+    // L’horodatage de document est une modification admise de la signature précédente.
+    assert_eq!(report["signatures"][0]["integrity"]["status"], "VALID");
+}
+
+#[test]
+fn encapsulated_content_is_checked_against_signed_attributes() {
+    // Contenu encapsulé (empreinte SHA-1, TSTInfo) réécrit après signature :
+    // la signature des attributs tient, le messageDigest non.
+    for name in ["pkcs7_sha1_tampered.pdf", "document_timestamp_tampered.pdf"] {
+        let report = pdf_report(name);
+        let sig = &report["signatures"][0];
+        assert_eq!(sig["signature"]["status"], "VALID", "{name}: {sig:#}");
+        assert_eq!(sig["integrity"]["status"], "INVALID", "{name}: {sig:#}");
+        let detail = sig["integrity"]["detail"].as_str().unwrap();
+        assert!(detail.starts_with("messageDigest différent"), "{detail}");
+        assert_eq!(report["verdict"], "INVALID", "{name}");
+    }
+}
+
+#[test]
+fn every_signer_info_of_encapsulated_content_is_verified() {
+    // Empreinte SHA-1 et TSTInfo cosignés, la seconde signature altérée :
+    // chaque SignerInfo est vérifié, pas seulement le premier.
+    for name in [
+        "pkcs7_sha1_cosigned_tampered.pdf",
+        "document_timestamp_cosigned_tampered.pdf",
+    ] {
+        let report = pdf_report(name);
+        let sig = &report["signatures"][0];
+        assert_eq!(sig["signature"]["status"], "INVALID", "{name}: {sig:#}");
+        let detail = sig["signature"]["detail"].as_str().unwrap();
+        assert!(detail.starts_with("Signataire #2 "), "{detail}");
+        assert_eq!(sig["integrity"]["status"], "VALID", "{name}: {sig:#}");
+        assert_eq!(report["verdict"], "INVALID", "{name}");
+    }
+}
+
+#[test]
+fn dss_provides_ltv_material() {
+    let report = pdf_report("ltv_dss.pdf");
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["ltv"]["status"], "VALID", "{sig:#}");
+    assert_eq!(sig["vri"].as_str().map(str::len), Some(40));
+
+    // CRL embarquée périmée : ni révocation établie, ni LTV.
+    let report = pdf_report("ltv_dss_stale.pdf");
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["revocation"]["status"], "WARNING", "{sig:#}");
+    assert!(sig["revocation"]["detail"]
+        .as_str()
+        .unwrap()
+        .contains("CRL périmée"));
+    assert_eq!(sig["ltv"]["status"], "WARNING");
+
+    let report = pdf_report("two_signatures.pdf");
+    for sig in report["signatures"].as_array().unwrap() {
+        assert_eq!(sig["ltv"]["status"], "WARNING");
+        assert!(sig["ltv"]["detail"]
+            .as_str()
+            .unwrap()
+            .starts_with("DSS absent"));
+    }
+}
+
+#[test]
+fn pades_baseline_level_and_missing_requirements() {
+    let report = pdf_report("pades_lta.pdf");
+    let sigs = report["signatures"].as_array().unwrap();
+    assert_eq!(sigs[0]["pades"]["level"], "B-LTA", "{:#}", sigs[0]);
+    assert_eq!(sigs[0]["pades"]["missing"], serde_json::json!([]));
+    assert!(sigs[1]["pades"].is_null(), "horodatage de document");
+
+    let report = pdf_report("ltv_dss.pdf");
+    let pades = &report["signatures"][0]["pades"];
+    assert!(pades["level"].is_null());
+    let missing: Vec<_> = pades["missing"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m.as_str().unwrap())
+        .collect();
+    assert!(missing.iter().any(|m| m.contains("signing-certificate-v2")));
+    assert!(missing.iter().any(|m| m.starts_with("B-T : ")));
+    assert!(!missing.iter().any(|m| m.starts_with("B-LT : ")));
+
+    let report = pdf_report("pades_bt.pdf");
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["pades"]["level"], "B-T", "{sig:#}");
+    assert!(sig["timestamp_rfc3161"].is_string());
+
+    // Jeton émis sur une autre valeur que celle de la signature.
+    let report = pdf_report("pades_bt_forged.pdf");
+    let pades = &report["signatures"][0]["pades"];
+    assert_eq!(pades["level"], "B-B", "{pades:#}");
+    let missing = pades["missing"][0].as_str().unwrap();
+    assert!(
+        missing.starts_with("B-T : horodatage de signature non valide"),
+        "{missing}"
+    );
+}
+
+#[test]
+fn signature_dictionary_metadata_is_decoded() {
+    let report = pdf_report("signature_metadata.pdf");
+    let sigs = report["signatures"].as_array().unwrap();
+    let client = &sigs[0]["metadata"];
+    assert_eq!(client["name"], "Maître Dupont");
+    assert_eq!(client["reason"], "Acte — lecture faite");
+    assert_eq!(client["location"], "Lyon");
+    assert_eq!(client["contact_info"], "etude@example.test");
+    assert_eq!(sigs[0]["claimed_time"]["status"], "VALID", "{:#}", sigs[0]);
+
+    let notaire = &sigs[1];
+    assert_eq!(notaire["metadata"]["name"], "Maître Martin");
+    assert_eq!(
+        notaire["metadata"]["claimed_time"],
+        "2001-01-01T12:00:00+01:00"
+    );
+    assert_eq!(notaire["claimed_time"]["status"], "WARNING");
+
+    // Tolérance assez large pour couvrir l’écart.
+    let report = pdf_report_with(
+        "signature_metadata.pdf",
+        &["--time-tolerance", "4000000000"],
+    );
+    assert_eq!(report["signatures"][1]["claimed_time"]["status"], "VALID");
+}
+
+#[test]
+fn signature_widget_placement_and_appearance() {
+    let report = pdf_report("signature_widgets.pdf");
+    let sigs = report["signatures"].as_array().unwrap();
+
+    // Widget enfant du champ, placé en page 2 par les /Annots de la page.
+    let client = &sigs[0]["widgets"][0];
+    assert_eq!(client["object"], "10 0 R");
+    assert_eq!(client["page"], 2);
+    assert_eq!(
+        client["rect"],
+        serde_json::json!([350.0, 50.0, 550.0, 120.0])
+    );
+    assert_eq!(client["visible"], true);
+    assert_eq!(client["appearance"], true);
+
+    let notaire = &sigs[1]["widgets"].as_array().unwrap()[..];
+    assert_eq!(notaire.len(), 1, "{notaire:?}");
+    assert_eq!(notaire[0]["page"], 1);
+    assert_eq!(notaire[0]["visible"], false);
+    assert_eq!(notaire[0]["appearance"], false);
+}
+
+#[test]
+fn unsigned_signature_fields_are_pending() {
+    let report = pdf_report("pending_fields.pdf");
+    let names: Vec<_> = report["signatures"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["field_name"].as_str())
+        .collect();
+    assert_eq!(names, [Some("Client")]);
+    let pending = report["pending_fields"].as_array().unwrap();
+    let pending: Vec<_> = pending
+        .iter()
+        .map(|f| (f["field_name"].as_str(), f["page"].as_u64()))
+        .collect();
+    assert_eq!(
+        pending,
+        [(Some("Temoin"), Some(1)), (Some("Notaire"), Some(1))]
+    );
+
+    // Politique par défaut : listés sans effet sur le verdict.
+    assert_eq!(report["unsigned_fields"]["status"], "VALID");
+    for (policy, status) in [("warning", "WARNING"), ("invalid", "INVALID")] {
+        let report = pdf_report_with("pending_fields.pdf", &["--unsigned-fields", policy]);
+        assert_eq!(report["unsigned_fields"]["status"], status);
+        assert_eq!(report["verdict"], status);
+    }
+
+    // Le widget enfant d’un champ signé n’est pas un champ en attente.
+    let report = pdf_report_with("signature_widgets.pdf", &["--unsigned-fields", "invalid"]);
+    assert_eq!(report["pending_fields"], serde_json::json!([]));
+    assert_eq!(report["unsigned_fields"]["status"], "VALID");
+}
+
+#[test]
+fn encrypted_pdf_is_decrypted_but_hashed_raw() {
+    for args in [&[][..], &["--pdf-password", "proprio"]] {
+        let report = pdf_report_with("encrypted_rc4.pdf", args);
+        let sigs = report["signatures"].as_array().unwrap();
+        let names: Vec<_> = sigs.iter().map(|s| s["field_name"].as_str()).collect();
+        assert_eq!(names, [Some("Client"), Some("Notaire")]);
+        for sig in sigs {
+            assert_eq!(sig["integrity"]["status"], "VALID", "{sig:#}");
+            assert_eq!(sig["metadata"]["reason"], "Lecture faite");
+        }
+        assert_eq!(sigs[0]["modifications"].as_array().unwrap().len(), 2);
+    }
+
+    let stderr = pdf_failure("encrypted_rc4.pdf", &["--pdf-password", "faux"]);
+    assert!(stderr.contains("Mot de passe PDF incorrect"), "{stderr}");
+}
+
+#[test]
+fn contents_padding_and_reserved_size() {
+    let report = pdf_report("contents_padding.pdf");
+    let sigs = report["signatures"].as_array().unwrap();
+
+    let client = &sigs[0];
+    assert_eq!(client["contents"]["reserved"], 8192);
+    assert_eq!(client["contents"]["trailing_nonzero"], 5);
+    assert_eq!(client["padding"]["status"], "WARNING");
+    assert_ne!(client["verdict"], "VALID");
+
+    // CMS BER à longueurs indéfinies : mesurée jusqu’aux marqueurs de fin.
+    let notaire = &sigs[1];
+    let used = notaire["contents"]["used"].as_u64().unwrap();
+    assert!(used > 0 && used < 8192, "{notaire:#}");
+    assert_eq!(notaire["contents"]["trailing_nonzero"], 0);
+    assert_eq!(notaire["padding"]["status"], "VALID");
+}
+
+#[test]
+fn portfolio_members_are_verified_recursively() {
+    let stderr = pdf_failure("portfolio.pdf", &[]);
+    assert!(stderr.contains("Aucune signature PDF détectée"), "{stderr}");
+
+    let report = pdf_report_with("portfolio.pdf", &["--embedded"]);
+    assert_eq!(report["signatures"], serde_json::json!([]));
+    assert_ne!(report["verdict"], "VALID");
+    let members = report["embedded"].as_array().unwrap();
+    let names: Vec<_> = members
+        .iter()
+        .map(|m| m["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "acte.pdf",
+            "annexe.txt",
+            "annexe.txt.p7s",
+            "note.txt",
+            "sous-dossier.pdf"
+        ]
+    );
+
+    let kinds: Vec<_> = members
+        .iter()
+        .map(|m| m["report"]["input_kind"].as_str())
+        .collect();
+    assert_eq!(kinds, [Some("PDF"), None, Some("CMS"), None, Some("PDF")]);
+    assert!(members.iter().all(|m| m["error"].is_null()), "{members:#?}");
+    assert_eq!(
+        members[0]["report"]["signatures"].as_array().unwrap().len(),
+        2
+    );
+    // Pièce signée au second niveau d’imbrication.
+    let nested = &members[4]["report"]["embedded"][0];
+    assert_eq!(nested["name"], "ancien.pdf");
+    assert_eq!(
+        nested["report"]["signatures"][0]["sub_filter"],
+        "adbe.pkcs7.sha1"
+    );
+}
+
+#[test]
+fn usage_rights_signature_is_reported_apart_from_signers() {
+    let report = pdf_report("xfa_usage_rights.pdf");
+    let signatures = report["signatures"].as_array().unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0]["field_name"], "Client");
+    assert_eq!(signatures[0]["certification"], false);
+
+    let rights = &report["usage_rights"];
+    assert_eq!(rights["name"], "ARE Acrobat Product v8.0 P23 0002337");
+    assert_eq!(rights["claimed_time"], "2024-01-05T09:00:00Z");
+    assert_eq!(
+        rights["rights"],
+        serde_json::json!([
+            "Document/FullSave",
+            "Form/FillIn",
+            "Form/Import",
+            "Signature/Modify"
+        ])
+    );
+    assert_eq!(report["xfa"]["status"], "WARNING");
+    assert_ne!(report["verdict"], "VALID");
+
+    let certified = pdf_report("certified_p2_then_filled.pdf");
+    assert_eq!(certified["signatures"][0]["certification"], true);
+    assert!(certified["usage_rights"].is_null());
+    assert!(certified["xfa"].is_null());
+}
+
+#[test]
+fn unreadable_structure_falls_back_to_byte_scan() {
+    assert_eq!(
+        pdf_report("two_signatures.pdf")["structure"]["status"],
+        "VALID"
+    );
+
+    let report = pdf_report("broken_xref.pdf");
+    assert_eq!(report["structure"]["status"], "WARNING");
+    let detail = report["structure"]["detail"].as_str().unwrap();
+    assert!(detail.contains("Analyse structurelle dégradée"), "{detail}");
+    assert_ne!(report["verdict"], "VALID");
+
+    // Signatures retrouvées dans l’ordre des octets, sans nom de champ.
+    let sigs = report["signatures"].as_array().unwrap();
+    assert_eq!(sigs.len(), 2);
+    assert_eq!(sigs[0]["field_name"], Value::Null);
+    assert_eq!(sigs[0]["shadow"]["status"], "WARNING");
+    assert_eq!(sigs[1]["integrity"]["status"], "VALID", "{:#}", sigs[1]);
+    assert_eq!(sigs[1]["revision"]["signed_revision"], 3);
+}
+
+#[test]
+fn large_pdf_is_streamed_within_the_memory_budget() {
+    // 2 Mio : au-delà d’un budget de 1 Mio, le fichier est lu en flux.
+    let loaded = pdf_report("large_scan.pdf");
+    let streamed = pdf_report_with("large_scan.pdf", &["--max-memory-mib", "1"]);
+    assert_eq!(streamed["document_sha256"], loaded["document_sha256"]);
+    assert_eq!(streamed["signatures"], loaded["signatures"]);
+    assert_eq!(streamed["verdict"], loaded["verdict"]);
+
+    let sigs = streamed["signatures"].as_array().unwrap();
+    assert_eq!(sigs[0]["field_name"], "Client");
+    assert_eq!(sigs[0]["integrity"]["status"], "VALID", "{:#}", sigs[0]);
+    assert_eq!(sigs[0]["modifications"].as_array().unwrap().len(), 2);
+    // Second champ rangé dans un flux d’objets, table en flux /XRef.
+    assert_eq!(sigs[1]["field_name"], "Notaire");
+    assert_eq!(sigs[1]["integrity"]["status"], "VALID", "{:#}", sigs[1]);
+
+    let stderr = pdf_failure("large_scan.pdf", &["--max-mib", "1"]);
+    assert!(stderr.contains("Fichier trop volumineux"), "{stderr}");
+}
+
+#[test]
+fn streamed_signature_without_signed_attributes_is_indeterminate() {
+    let loaded = pdf_report("large_scan_noattr.pdf");
+    assert_eq!(loaded["signatures"][0]["signature"]["status"], "VALID");
+
+    // Lu en flux, le contenu n’est connu que par ses empreintes.
+    let report = pdf_report_with("large_scan_noattr.pdf", &["--max-memory-mib", "1"]);
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["signature"]["status"], "WARNING", "{sig:#}");
+    assert!(sig["signature"]["detail"]
+        .as_str()
+        .unwrap()
+        .contains("messageDigest"));
+    assert_eq!(report["verdict"], "WARNING");
+}
+
+#[test]
+fn streamed_cosigned_signature_is_digested_for_every_signer() {
+    // SHA-512 et SHA-384 : une empreinte par SignerInfo, aucune n’étant le
+    // SHA-256 toujours calculé.
+    let report = pdf_report_with("large_scan_cosigned.pdf", &["--max-memory-mib", "1"]);
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["cms"]["signers"].as_array().unwrap().len(), 2);
+    assert_eq!(sig["integrity"]["status"], "VALID", "{sig:#}");
+    assert_eq!(sig["signature"]["status"], "VALID", "{sig:#}");
+}
+
+#[test]
+fn signed_revision_is_extracted_in_streaming_mode() {
+    let dir = tempdir().unwrap();
+    let extracted = dir.path().join("client.pdf");
+    let source = format!("{FIXTURES}/large_scan.pdf");
+    Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--in")
+        .arg(&source)
+        .args([
+            "--max-memory-mib",
+            "1",
+            "--extract-revision",
+            "1",
+            "--out-pdf",
+        ])
+        .arg(&extracted)
+        .assert()
+        .success();
+
+    let full = fs::read(&source).unwrap();
+    let signed = fs::read(&extracted).unwrap();
+    assert!(signed.len() > 2 << 20 && signed.len() < full.len());
+    assert!(full.starts_with(&signed));
+}
+
+#[test]
+fn signature_verification_covers_the_byterange_content() {
+    let report = pdf_report("two_signatures.pdf");
+    for s in report["signatures"].as_array().unwrap() {
+        assert_eq!(s["signature"]["status"], "VALID", "{s:#}");
+    }
+    assert_eq!(report["verdict"], "VALID");
+}
+
+#[test]
+fn signature_verification_covers_legacy_sub_filters() {
+    for name in ["pkcs7_sha1.pdf", "x509_rsa_sha1.pdf"] {
+        let report = pdf_report(name);
+        let sig = &report["signatures"][0];
+        assert_eq!(sig["signature"]["status"], "VALID", "{name}: {sig:#}");
+        assert_eq!(sig["signer_dn"], "Maitre Dupont", "{name}");
+    }
+}
+
+#[test]
+fn signature_verification_covers_byte_scanned_signatures() {
+    let report = pdf_report("broken_xref.pdf");
+    for sig in report["signatures"].as_array().unwrap() {
+        assert_eq!(sig["signature"]["status"], "VALID", "{sig:#}");
+        assert_eq!(sig["signer_dn"], "Maitre Dupont");
+    }
+}
+
+#[test]
+fn attachments_are_inflated_within_the_memory_budget() {
+    let report = pdf_report_with("portfolio_bomb.pdf", &["--embedded"]);
+    let member = &report["embedded"][0];
+    assert!(member["error"].is_null(), "{member:#}");
+    assert_eq!(member["size"], 4 << 20);
+
+    // 4 Mio décodés pour un budget de 1 Mio : le décodage s’interrompt.
+    let report = pdf_report_with(
+        "portfolio_bomb.pdf",
+        &["--embedded", "--max-memory-mib", "1"],
+    );
+    let error = report["embedded"][0]["error"].as_str().unwrap();
+    assert!(error.contains("flux de plus de 1048576 octets"), "{error}");
+}
+
+#[test]
+fn signature_verification_covers_portfolio_members() {
+    let report = pdf_report_with("portfolio.pdf", &["--embedded"]);
+    let members = report["embedded"].as_array().unwrap();
+    assert_eq!(members[0]["report"]["signature"]["status"], "VALID");
+    // P7S détachée vérifiée contre la pièce jointe de même nom.
+    assert_eq!(members[2]["report"]["signature"]["status"], "VALID");
+    assert_eq!(members[2]["report"]["integrity"]["status"], "VALID");
+    let nested = &members[4]["report"]["embedded"][0]["report"];
+    assert_eq!(nested["signature"]["status"], "VALID");
+}
+
+#[test]
+fn signature_verification_covers_document_timestamps() {
+    let report = pdf_report("document_timestamp.pdf");
+    let ts = &report["signatures"][1];
+    assert_eq!(ts["signature"]["status"], "VALID", "{ts:#}");
+    assert_eq!(ts["chain"]["status"], "VALID");
+    assert_eq!(report["verdict"], "VALID");
+}
+
+#[test]
+fn embedded_revocation_data_is_authenticated() {
+    let report = pdf_report("ltv_dss.pdf");
+    assert_eq!(report["signatures"][0]["revocation"]["status"], "VALID");
+    assert_eq!(report["verdict"], "VALID");
+
+    let report = pdf_report("ltv_dss_revoked.pdf");
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["revocation"]["status"], "INVALID", "{sig:#}");
+    assert_eq!(report["verdict"], "INVALID");
+
+    // Dernier octet de la CRL (13) et de la réponse OCSP (14) du DSS altéré :
+    // ni révocation établie, ni LTV.
+    let mut bytes = fs::read(format!("{FIXTURES}/ltv_dss_revoked.pdf")).unwrap();
+    for object in [b"\n13 0 obj", b"\n14 0 obj"] {
+        let at = bytes
+            .windows(object.len())
+            .rposition(|w| w == object)
+            .unwrap();
+        let end = at
+            + bytes[at..]
+                .windows(10)
+                .position(|w| w == b"\nendstream")
+                .unwrap();
+        bytes[end - 1] ^= 1;
+    }
+    let dir = tempdir().unwrap();
+    let forged = dir.path().join("forged_dss.pdf");
+    fs::write(&forged, bytes).unwrap();
+    let report = pdf_report(forged.to_str().unwrap());
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["revocation"]["status"], "WARNING", "{sig:#}");
+    assert_eq!(sig["ltv"]["status"], "WARNING", "{sig:#}");
+}
+
+#[test]
+fn aes_encrypted_pdfs_are_decrypted() {
+    let stderr = pdf_failure("encrypted_aes256.pdf", &[]);
+    assert!(stderr.contains("Mot de passe PDF incorrect"), "{stderr}");
+
+    for (name, passwords) in [
+        ("encrypted_aes128.pdf", &["", "proprio"]),
+        ("encrypted_aes256.pdf", &["secret", "proprio"]),
+    ] {
+        for password in passwords {
+            let report = pdf_report_with(name, &["--pdf-password", password]);
+            let sigs = report["signatures"].as_array().unwrap();
+            assert_eq!(sigs[0]["field_name"], "Client", "{name}");
+            assert_eq!(sigs[0]["metadata"]["reason"], "Lecture faite", "{name}");
+            assert_eq!(sigs[0]["modifications"].as_array().unwrap().len(), 2);
+            assert_eq!(report["verdict"], "VALID", "{name}: {report:#}");
+        }
+    }
+}
+
 #[test]
-fn placeholder() {
-    // Placeholder pour garder un test unitaire vert.
-    assert!(true);
+fn signature_verification_covers_streamed_signatures_by_digest() {
+    let report = pdf_report_with("large_scan.pdf", &["--max-memory-mib", "1"]);
+    for sig in report["signatures"].as_array().unwrap() {
+        assert_eq!(sig["signature"]["status"], "VALID", "{sig:#}");
+    }
+    assert_eq!(report["verdict"], "VALID");
+
+    // Un octet de l’image numérisée modifié : messageDigest ne correspond plus.
+    let dir = tempdir().unwrap();
+    let tampered = dir.path().join("tampered.pdf");
+    let mut bytes = fs::read(format!("{FIXTURES}/large_scan.pdf")).unwrap();
+    bytes[1 << 20] ^= 1;
+    fs::write(&tampered, bytes).unwrap();
+    let out = dir.path().join("report.json");
+    Command::cargo_bin("notar-verify")
+        .unwrap()
+        .arg("--in")
+        .arg(&tampered)
+        .args(["--max-memory-mib", "1", "--trust"])
+        .arg(format!("{FIXTURES}/ca.pem"))
+        .arg("--out")
+        .arg(&out)
+        .assert();
+    let report: Value = serde_json::from_str(&fs::read_to_string(out).unwrap()).unwrap();
+    let sig = &report["signatures"][0];
+    assert_eq!(sig["integrity"]["status"], "INVALID", "{sig:#}");
+    assert!(sig["integrity"]["detail"]
+        .as_str()
+        .unwrap()
+        .contains("messageDigest"));
+    assert_eq!(report["verdict"], "INVALID");
 }